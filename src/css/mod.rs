@@ -1,29 +1,242 @@
 use std::fmt;
 
+mod named_colors;
+
 pub type Specificity = (usize, usize, usize);
 
+// How an attribute selector compares the value of the attribute
+// https://drafts.csswg.org/selectors/#attribute-selectors
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AttributeOperator {
+  Exists,    // '[attr]'
+  Equals,    // '[attr=value]'
+  Includes,  // '[attr~=value]', one of the whitespace-separated words of the attribute
+  DashMatch, // '[attr|=value]', the value or the value followed by '-'
+  Prefix,    // '[attr^=value]'
+  Suffix,    // '[attr$=value]'
+  Substring, // '[attr*=value]'
+}
+
+// Whether an attribute selector compares values ignoring ASCII case, from its 'i' or 's' flag.
+// Without a flag, it depends on the attribute
+// https://drafts.csswg.org/selectors/#attribute-case
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AttributeCase {
+  Default,
+  Sensitive,
+  Insensitive,
+}
+
+#[derive(Clone, Debug)]
+pub struct AttributeSelector {
+  name: String,
+  operator: AttributeOperator,
+  value: String,
+  case: AttributeCase,
+}
+
+impl PartialEq for AttributeSelector {
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name
+      && self.operator == other.operator
+      && self.value == other.value
+      && self.case == other.case
+  }
+}
+
+impl AttributeSelector {
+  pub fn new(
+    name: String,
+    operator: AttributeOperator,
+    value: String,
+    case: AttributeCase,
+  ) -> Self {
+    Self {
+      name,
+      operator,
+      value,
+      case,
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn operator(&self) -> AttributeOperator {
+    self.operator
+  }
+
+  pub fn value(&self) -> &str {
+    &self.value
+  }
+
+  pub fn case(&self) -> AttributeCase {
+    self.case
+  }
+}
+
+// The 'An+B' argument of ':nth-child()' and similar pseudo-classes, which matches the elements
+// at the positions An+B (counting from 1) for any n >= 0
+// https://drafts.csswg.org/css-syntax/#anb-microsyntax
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Nth {
+  a: i32,
+  b: i32,
+}
+
+impl Nth {
+  pub fn new(a: i32, b: i32) -> Self {
+    Self { a, b }
+  }
+
+  pub fn a(&self) -> i32 {
+    self.a
+  }
+
+  pub fn b(&self) -> i32 {
+    self.b
+  }
+
+  // Is there an n >= 0 such that An+B is 'position'?
+  pub fn matches(&self, position: usize) -> bool {
+    let offset: i64 = position as i64 - self.b as i64;
+    match self.a as i64 {
+      0 => offset == 0,
+      a => offset % a == 0 && offset / a >= 0,
+    }
+  }
+}
+
+// Pseudo-classes that select elements by their place in the tree, the logical ones that take
+// selectors as arguments, and the dynamic ones that depend on the state of the element
+// https://drafts.csswg.org/selectors/#structural-pseudos
+// https://drafts.csswg.org/selectors/#logical-combination
+// https://drafts.csswg.org/selectors/#useraction-pseudos
+#[derive(Clone, PartialEq, Debug)]
+pub enum PseudoClass {
+  Hover,
+  Active,
+  Focus,
+  FocusWithin,
+  Checked,
+  Disabled,
+  Enabled,
+  Link,
+  Visited,
+  Root,
+  Empty,
+  FirstChild,
+  LastChild,
+  OnlyChild,
+  FirstOfType,
+  LastOfType,
+  OnlyOfType,
+  // ':nth-child(An+B of S)' only counts the siblings that match S. Without 'of S', the list of
+  // selectors is empty
+  NthChild(Nth, Vec<Selector>),
+  NthLastChild(Nth, Vec<Selector>),
+  NthOfType(Nth),
+  NthLastOfType(Nth),
+  Not(Vec<Selector>),
+  Is(Vec<Selector>),
+  Where(Vec<Selector>),
+  // Relative selectors, which start with Selector::Anchor, e.g. ':has(> img)'
+  Has(Vec<Selector>),
+}
+
+// The specificity of the most specific selector of a list, or zero for an empty list
+fn max_specificity(selectors: &[Selector]) -> Specificity {
+  selectors
+    .iter()
+    .map(|selector: &Selector| selector.specificity())
+    .max()
+    .unwrap_or((0, 0, 0))
+}
+
+impl PseudoClass {
+  // Pseudo-classes count as classes, except for the logical ones, which count as their most
+  // specific argument, and ':where()', which never adds specificity
+  // https://drafts.csswg.org/selectors/#specificity-rules
+  pub fn specificity(&self) -> Specificity {
+    match self {
+      PseudoClass::Where(_) => (0, 0, 0),
+      PseudoClass::Not(selectors) | PseudoClass::Is(selectors) | PseudoClass::Has(selectors) => {
+        max_specificity(selectors)
+      }
+      PseudoClass::NthChild(_, selectors) | PseudoClass::NthLastChild(_, selectors) => {
+        let (a, b, c): Specificity = max_specificity(selectors);
+        (a, b + 1, c)
+      }
+      _ => (0, 1, 0),
+    }
+  }
+}
+
+// Pseudo-elements, which select boxes generated for an element rather than the element itself
+// https://drafts.csswg.org/css-pseudo/#generated-content
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PseudoElement {
+  Before,
+  After,
+}
+
+impl fmt::Display for PseudoElement {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PseudoElement::Before => write!(f, "::before"),
+      PseudoElement::After => write!(f, "::after"),
+    }
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct SimpleSelector {
+  // The namespace URL an element must have, e.g. from 'svg|rect' or a default @namespace rule.
+  // None matches elements in any namespace
+  namespace: Option<String>,
   tag_name: Option<String>,
   id: Option<String>,
   classes: Vec<String>,
+  attributes: Vec<AttributeSelector>,
+  pseudo_classes: Vec<PseudoClass>,
+  // The pseudo-element the selector selects instead of the element, which ends the selector
+  pseudo_element: Option<PseudoElement>,
 }
 
 impl PartialEq for SimpleSelector {
   fn eq(&self, other: &Self) -> bool {
-    self.tag_name == other.tag_name && self.id == other.id && self.classes == other.classes
+    self.namespace == other.namespace
+      && self.tag_name == other.tag_name
+      && self.id == other.id
+      && self.classes == other.classes
+      && self.attributes == other.attributes
+      && self.pseudo_classes == other.pseudo_classes
+      && self.pseudo_element == other.pseudo_element
   }
 }
 
 impl SimpleSelector {
   pub fn new(tag_name: Option<String>, id: Option<String>, classes: Vec<String>) -> Self {
     Self {
+      namespace: None,
       tag_name,
       id,
       classes,
+      attributes: vec![],
+      pseudo_classes: vec![],
+      pseudo_element: None,
     }
   }
 
+  pub fn namespace(&self) -> &Option<String> {
+    &self.namespace
+  }
+
+  pub fn set_namespace(&mut self, namespace: Option<String>) {
+    self.namespace = namespace;
+  }
+
   pub fn tag_name(&self) -> &Option<String> {
     &self.tag_name
   }
@@ -47,17 +260,73 @@ impl SimpleSelector {
   pub fn add_class(&mut self, class: String) {
     self.classes.push(class);
   }
+
+  pub fn attributes(&self) -> &Vec<AttributeSelector> {
+    &self.attributes
+  }
+
+  pub fn add_attribute(&mut self, attribute: AttributeSelector) {
+    self.attributes.push(attribute);
+  }
+
+  pub fn pseudo_classes(&self) -> &Vec<PseudoClass> {
+    &self.pseudo_classes
+  }
+
+  pub fn add_pseudo_class(&mut self, pseudo_class: PseudoClass) {
+    self.pseudo_classes.push(pseudo_class);
+  }
+
+  pub fn pseudo_element(&self) -> Option<PseudoElement> {
+    self.pseudo_element
+  }
+
+  pub fn set_pseudo_element(&mut self, pseudo_element: Option<PseudoElement>) {
+    self.pseudo_element = pseudo_element;
+  }
+
+  // The number of id, class and type selectors. Attribute selectors count as classes,
+  // pseudo-elements as types, and pseudo-classes as their own specificity
+  pub fn specificity(&self) -> Specificity {
+    let a: usize = self.id.iter().count();
+    let b: usize = self.classes.len() + self.attributes.len();
+    let c: usize = self.tag_name.iter().count() + self.pseudo_element.iter().count();
+    self
+      .pseudo_classes
+      .iter()
+      .map(PseudoClass::specificity)
+      .fold((a, b, c), |(a, b, c): Specificity, (d, e, f): Specificity| (a + d, b + e, c + f))
+  }
+}
+
+// How two simple selectors of a complex selector relate
+// https://drafts.csswg.org/selectors/#combinators
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Combinator {
+  Descendant,        // 'nav a'
+  Child,             // 'ul > li'
+  NextSibling,       // 'h1 + p'
+  SubsequentSibling, // 'h1 ~ p'
 }
 
 #[derive(Clone, Debug)]
 pub enum Selector {
   Simple(SimpleSelector),
+  // A selector, a combinator, and the simple selector the matched element must match, e.g.
+  // 'ul > li a' is Complex(Complex(Simple(ul), Child, li), Descendant, a)
+  Complex(Box<Selector>, Combinator, SimpleSelector),
+  // The element a relative selector of ':has()' is anchored to, which starts the selector: e.g.
+  // '> img' is Complex(Anchor, Child, img)
+  Anchor,
 }
 
 impl PartialEq for Selector {
   fn eq(&self, other: &Self) -> bool {
     match (self, other) {
       (Selector::Simple(a), Selector::Simple(b)) => a == b,
+      (Selector::Complex(a, b, c), Selector::Complex(d, e, f)) => a == d && b == e && c == f,
+      (Selector::Anchor, Selector::Anchor) => true,
+      _ => false,
     }
   }
 }
@@ -66,17 +335,43 @@ impl Selector {
   // Specificity is one of the ways a rendering engine decides which style overrides the other in a conflict
   pub fn specificity(&self) -> Specificity {
     // http://www.w3.org/TR/selectors/#specificity
-    let Selector::Simple(ref simple) = *self;
-    let a: usize = simple.id.iter().count();
-    let b: usize = simple.classes.len();
-    let c: usize = simple.tag_name.iter().count();
-    (a, b, c)
+    match *self {
+      Selector::Simple(ref simple) => simple.specificity(),
+      // The specificity of a complex selector is the sum of the specificities of its simple
+      // selectors
+      Selector::Complex(ref selector, _, ref simple) => {
+        let (a, b, c): Specificity = selector.specificity();
+        let (d, e, f): Specificity = simple.specificity();
+        (a + d, b + e, c + f)
+      }
+      Selector::Anchor => (0, 0, 0),
+    }
+  }
+
+  // The pseudo-element the selector selects, which can only be in its last simple selector
+  pub fn pseudo_element(&self) -> Option<PseudoElement> {
+    match *self {
+      Selector::Simple(ref simple) | Selector::Complex(_, _, ref simple) => {
+        simple.pseudo_element()
+      }
+      Selector::Anchor => None,
+    }
   }
 }
 
+// Units of lengths. Absolute units are a fixed number of px, font-relative units are resolved
+// when values are computed, and viewport units during layout
+// https://drafts.csswg.org/css-values/#lengths
 #[derive(Copy, Clone, Debug)]
 pub enum Unit {
   Px,
+  Pt,      // 1/72 of an inch
+  Cm,      // 96px / 2.54
+  In,      // 96px
+  Em,      // the font size of the element, or of its parent for 'font-size'
+  Rem,     // the font size of the root element
+  Vw,      // 1% of the width of the viewport
+  Vh,      // 1% of the height of the viewport
   // insert more units here
 }
 
@@ -90,6 +385,13 @@ impl fmt::Display for Unit {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       Unit::Px => write!(f, "px"),
+      Unit::Pt => write!(f, "pt"),
+      Unit::Cm => write!(f, "cm"),
+      Unit::In => write!(f, "in"),
+      Unit::Em => write!(f, "em"),
+      Unit::Rem => write!(f, "rem"),
+      Unit::Vw => write!(f, "vw"),
+      Unit::Vh => write!(f, "vh"),
       // handle more variants here
     }
   }
@@ -123,7 +425,7 @@ impl fmt::Display for Color {
 }
 
 impl Color {
-  pub fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+  pub const fn new(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
     Self {
       red,
       green,
@@ -147,13 +449,105 @@ impl Color {
   pub fn alpha(&self) -> u8 {
     self.alpha
   }
+
+  // Return the named color, e.g. 'rebeccapurple', ignoring ASCII case. 'transparent' is
+  // transparent black
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name: String = name.to_ascii_lowercase();
+    if name == "transparent" {
+      return Some(Color::new(0, 0, 0, 0));
+    }
+    named_colors::NAMED_COLORS
+      .binary_search_by_key(&name.as_str(), |&(color_name, _): &(&str, [u8; 3])| color_name)
+      .ok()
+      .map(|index: usize| {
+        let [red, green, blue]: [u8; 3] = named_colors::NAMED_COLORS[index].1;
+        Color::new(red, green, blue, 255)
+      })
+  }
+
+  // Return the color of a hue in degrees, and a saturation, a lightness and an alpha from 0 to 1
+  // https://drafts.csswg.org/css-color/#hsl-to-rgb
+  pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+    let [red, green, blue]: [f32; 3] =
+      hsl_to_rgb(hue, saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    Color::from_fractions(red, green, blue, alpha)
+  }
+
+  // Return the color of a hue in degrees, and a whiteness, a blackness and an alpha from 0 to 1
+  // https://drafts.csswg.org/css-color/#hwb-to-rgb
+  pub fn from_hwb(hue: f32, whiteness: f32, blackness: f32, alpha: f32) -> Self {
+    let whiteness: f32 = whiteness.clamp(0.0, 1.0);
+    let blackness: f32 = blackness.clamp(0.0, 1.0);
+    // Whiteness and blackness that add up to more than 1 are scaled down to a gray
+    if whiteness + blackness >= 1.0 {
+      let gray: f32 = whiteness / (whiteness + blackness);
+      return Color::from_fractions(gray, gray, gray, alpha);
+    }
+    let [red, green, blue]: [f32; 3] = hsl_to_rgb(hue, 1.0, 0.5)
+      .map(|channel: f32| channel * (1.0 - whiteness - blackness) + whiteness);
+    Color::from_fractions(red, green, blue, alpha)
+  }
+
+  // Return the color of channels from 0 to 1, clamped and rounded to the nearest u8
+  pub fn from_fractions(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+    let channel = |fraction: f32| -> u8 { (fraction.clamp(0.0, 1.0) * 255.0).round() as u8 };
+    Color::new(channel(red), channel(green), channel(blue), channel(alpha))
+  }
+}
+
+// Convert a hue in degrees, and a saturation and a lightness from 0 to 1, to red, green and blue
+// channels from 0 to 1
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+  let hue: f32 = hue.rem_euclid(360.0);
+  let channel = |n: f32| -> f32 {
+    let k: f32 = (n + hue / 30.0) % 12.0;
+    let a: f32 = saturation * lightness.min(1.0 - lightness);
+    lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+  };
+  [channel(0.0), channel(8.0), channel(4.0)]
+}
+
+// A part of the value of the 'content' property, which generates the text of a pseudo-element
+// https://drafts.csswg.org/css-content/#content-property
+#[derive(Clone, PartialEq, Debug)]
+pub enum ContentItem {
+  String(String),
+  // 'attr(name)', the value of an attribute of the element, or nothing if it doesn't have it
+  Attr(String),
+  // 'counter(name)', the value of a counter in decimal
+  Counter(String),
+}
+
+impl fmt::Display for ContentItem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ContentItem::String(s) => write!(f, "{:?}", s),
+      ContentItem::Attr(name) => write!(f, "attr({})", name),
+      ContentItem::Counter(name) => write!(f, "counter({})", name),
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
 pub enum Value {
   Keyword(String),
   Length(f32, Unit),
+  // A number without a unit, e.g. 'line-height: 1.4', 'z-index: 3', or a zero length
+  Number(f32),
+  // A percentage of a reference value, which depends on the property, e.g. the width of the
+  // containing block for 'width'
+  Percentage(f32),
   ColorValue(Color),
+  // The value of 'content' when it generates content
+  Content(Vec<ContentItem>),
+  // The value of 'counter-reset' and 'counter-increment': counter names and integers
+  Counters(Vec<(String, i32)>),
+  // A quoted string, e.g. a font family name
+  String(String),
+  // Several component values, e.g. the value of a shorthand property such as 'margin: 0 auto'.
+  // The '/' and ',' separators between them are kept as keywords
+  List(Vec<Value>),
   // insert more values here
 }
 
@@ -162,7 +556,13 @@ impl PartialEq for Value {
     match (self, other) {
       (Value::Keyword(a), Value::Keyword(b)) => a == b,
       (Value::Length(a, b), Value::Length(c, d)) => a == c && b == d,
+      (Value::Number(a), Value::Number(b)) => a == b,
+      (Value::Percentage(a), Value::Percentage(b)) => a == b,
       (Value::ColorValue(a), Value::ColorValue(b)) => a == b,
+      (Value::Content(a), Value::Content(b)) => a == b,
+      (Value::Counters(a), Value::Counters(b)) => a == b,
+      (Value::String(a), Value::String(b)) => a == b,
+      (Value::List(a), Value::List(b)) => a == b,
       _ => false,
     }
   }
@@ -173,17 +573,45 @@ impl fmt::Display for Value {
     match self {
       Value::Keyword(s) => write!(f, "{}", s),
       Value::Length(value, unit) => write!(f, "{}{}", value, unit),
+      Value::Number(value) => write!(f, "{}", value),
+      Value::Percentage(value) => write!(f, "{}%", value),
       Value::ColorValue(color) => write!(f, "{}", color),
+      Value::Content(items) => {
+        let items: Vec<String> = items.iter().map(ContentItem::to_string).collect();
+        write!(f, "{}", items.join(" "))
+      }
+      Value::Counters(counters) => {
+        let counters: Vec<String> = counters
+          .iter()
+          .map(|(name, value): &(String, i32)| format!("{} {}", name, value))
+          .collect();
+        write!(f, "{}", counters.join(" "))
+      }
+      Value::String(s) => write!(f, "{:?}", s),
+      Value::List(values) => {
+        for (index, value) in values.iter().enumerate() {
+          // Commas follow the previous value directly, e.g. 'Georgia, serif'
+          if index > 0 && *value != Value::Keyword(",".to_string()) {
+            write!(f, " ")?;
+          }
+          write!(f, "{}", value)?;
+        }
+        Ok(())
+      }
       // handle more variants here
     }
   }
 }
 
 impl Value {
-  // Return the size of a length in px, or zero for non-lengths.
+  // Return the size of a length in absolute units in px, or zero for other values, including
+  // relative lengths that have not been resolved
   pub fn to_px(&self) -> f32 {
     match *self {
       Value::Length(f, Unit::Px) => f,
+      Value::Length(f, Unit::Pt) => f * 96.0 / 72.0,
+      Value::Length(f, Unit::Cm) => f * 96.0 / 2.54,
+      Value::Length(f, Unit::In) => f * 96.0,
       _ => 0.0,
     }
   }
@@ -207,7 +635,7 @@ impl Declaration {
   }
 
   pub fn name(&self) -> &str {
-    &&self.name
+    &self.name
   }
 
   pub fn value(&self) -> &Value {
@@ -0,0 +1,107 @@
+// The attributes of an element, in source order. Elements have few attributes, so they are
+// kept in a list and looked up by scanning it
+#[derive(Clone, Debug, Default)]
+pub struct AttributeMap {
+  attributes: Vec<(String, String)>,
+}
+
+// Attribute lists are equal when they have the same attributes, in any order
+// https://dom.spec.whatwg.org/#concept-node-equals
+impl PartialEq for AttributeMap {
+  fn eq(&self, other: &Self) -> bool {
+    self.len() == other.len()
+      && self
+        .iter()
+        .all(|(name, value): (&String, &String)| other.get(name) == Some(value))
+  }
+}
+
+impl AttributeMap {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn len(&self) -> usize {
+    self.attributes.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.attributes.is_empty()
+  }
+
+  pub fn get(&self, name: &str) -> Option<&String> {
+    self
+      .attributes
+      .iter()
+      .find(|(key, _): &&(String, String)| key == name)
+      .map(|(_, value): &(String, String)| value)
+  }
+
+  pub fn contains_key(&self, name: &str) -> bool {
+    self.get(name).is_some()
+  }
+
+  // Set the value of an attribute and return its previous value. An existing attribute keeps
+  // its place; a new one is added at the end
+  pub fn insert(&mut self, name: String, value: String) -> Option<String> {
+    match self
+      .attributes
+      .iter_mut()
+      .find(|(key, _): &&mut (String, String)| *key == name)
+    {
+      Some((_, old_value)) => Some(std::mem::replace(old_value, value)),
+      None => {
+        self.attributes.push((name, value));
+        None
+      }
+    }
+  }
+
+  // Remove an attribute and return its value, if it existed
+  pub fn remove(&mut self, name: &str) -> Option<String> {
+    let index: usize = self
+      .attributes
+      .iter()
+      .position(|(key, _): &(String, String)| key == name)?;
+    Some(self.attributes.remove(index).1)
+  }
+
+  // Iterate over the names and values of the attributes, in order
+  pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+    self.into_iter()
+  }
+}
+
+impl FromIterator<(String, String)> for AttributeMap {
+  fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+    let mut attributes: AttributeMap = AttributeMap::new();
+    for (name, value) in iter {
+      attributes.insert(name, value);
+    }
+    attributes
+  }
+}
+
+impl IntoIterator for AttributeMap {
+  type Item = (String, String);
+  type IntoIter = std::vec::IntoIter<(String, String)>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.attributes.into_iter()
+  }
+}
+
+impl<'a> IntoIterator for &'a AttributeMap {
+  type Item = (&'a String, &'a String);
+  type IntoIter = std::iter::Map<
+    std::slice::Iter<'a, (String, String)>,
+    fn(&'a (String, String)) -> (&'a String, &'a String),
+  >;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self
+      .attributes
+      .iter()
+      .map(|(name, value): &(String, String)| (name, value))
+  }
+}
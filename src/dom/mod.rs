@@ -0,0 +1,519 @@
+use std::{
+  collections::HashSet,
+  fmt::{self, Formatter, Result},
+};
+
+use crate::css;
+
+pub mod attributes;
+pub mod query;
+pub mod serialize;
+pub mod tree;
+
+pub use attributes::AttributeMap;
+pub use query::Descendants;
+pub use tree::{NodeId, Tree};
+
+#[cfg(test)]
+mod tests;
+
+// The namespace of an element. HTML documents embed SVG and MathML elements, whose names are
+// case-sensitive (e.g. 'viewBox' or 'linearGradient')
+// https://infra.spec.whatwg.org/#namespaces
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Namespace {
+  #[default]
+  Html,
+  Svg,
+  MathMl,
+}
+
+impl Namespace {
+  pub fn url(self) -> &'static str {
+    match self {
+      Namespace::Html => "http://www.w3.org/1999/xhtml",
+      Namespace::Svg => "http://www.w3.org/2000/svg",
+      Namespace::MathMl => "http://www.w3.org/1998/Math/MathML",
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct ElementData {
+  namespace: Namespace,
+  tag_name: String,
+  attributes: AttributeMap,
+}
+
+impl PartialEq for ElementData {
+  fn eq(&self, other: &Self) -> bool {
+    self.namespace == other.namespace
+      && *self.tag_name == *other.tag_name
+      && self.attributes == other.attributes
+  }
+}
+
+impl fmt::Display for ElementData {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "<{}", self.tag_name)?;
+    for (key, value) in &self.attributes {
+      write!(f, " {}='{}'", key, value)?;
+    }
+    write!(f, ">")
+  }
+}
+
+impl ElementData {
+  pub fn new(tag_name: String, attributes: AttributeMap) -> Self {
+    Self::new_in_namespace(Namespace::Html, tag_name, attributes)
+  }
+
+  pub fn new_in_namespace(
+    namespace: Namespace,
+    tag_name: String,
+    attributes: AttributeMap,
+  ) -> Self {
+    Self {
+      namespace,
+      tag_name,
+      attributes,
+    }
+  }
+
+  pub fn namespace(&self) -> Namespace {
+    self.namespace
+  }
+
+  pub fn tag_name(&self) -> &str {
+    &self.tag_name
+  }
+
+  pub fn attributes(&self) -> &AttributeMap {
+    &self.attributes
+  }
+
+  // Set the value of an attribute, adding the attribute if it doesn't exist
+  pub fn set_attribute(&mut self, name: String, value: String) {
+    self.attributes.insert(name, value);
+  }
+
+  // Remove an attribute, returning its value if it existed
+  pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+    self.attributes.remove(name)
+  }
+
+  pub fn id(&self) -> Option<&String> {
+    self.attributes.get("id")
+  }
+
+  pub fn classes(&self) -> HashSet<&str> {
+    match self.attributes.get("class") {
+      Some(classlist) => classlist.split(' ').collect(),
+      None => HashSet::new(),
+    }
+  }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DoctypeData {
+  name: String,
+  public_id: Option<String>,
+  system_id: Option<String>,
+}
+
+impl fmt::Display for DoctypeData {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "<!DOCTYPE {}", self.name)?;
+    match (&self.public_id, &self.system_id) {
+      (Some(public_id), Some(system_id)) => write!(f, " PUBLIC \"{}\" \"{}\"", public_id, system_id)?,
+      (Some(public_id), None) => write!(f, " PUBLIC \"{}\"", public_id)?,
+      (None, Some(system_id)) => write!(f, " SYSTEM \"{}\"", system_id)?,
+      (None, None) => {}
+    }
+    write!(f, ">")
+  }
+}
+
+impl DoctypeData {
+  pub fn new(name: String, public_id: Option<String>, system_id: Option<String>) -> Self {
+    Self {
+      name,
+      public_id,
+      system_id,
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn public_id(&self) -> Option<&str> {
+    self.public_id.as_deref()
+  }
+
+  pub fn system_id(&self) -> Option<&str> {
+    self.system_id.as_deref()
+  }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum NodeType {
+  Text(String),
+  Comment(String),
+  Element(ElementData),
+  Doctype(DoctypeData),
+}
+
+// How closely the document asks to follow the standards, decided by its doctype
+// https://dom.spec.whatwg.org/#concept-document-quirks
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum QuirksMode {
+  #[default]
+  NoQuirks,
+  LimitedQuirks,
+  Quirks,
+}
+
+// A range of bytes in the source text, with the line and column (both starting at 1) of its
+// first character
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Span {
+  start: usize,
+  end: usize,
+  line: usize,
+  column: usize,
+}
+
+impl fmt::Display for Span {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}:{}", self.line, self.column)
+  }
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+    Self {
+      start,
+      end,
+      line,
+      column,
+    }
+  }
+
+  pub fn start(&self) -> usize {
+    self.start
+  }
+
+  pub fn end(&self) -> usize {
+    self.end
+  }
+
+  pub fn line(&self) -> usize {
+    self.line
+  }
+
+  pub fn column(&self) -> usize {
+    self.column
+  }
+}
+
+// The kinds of errors the HTML parser recovers from
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DiagnosticKind {
+  // An end tag that doesn't close any open element, e.g. '<div></span></div>'
+  UnexpectedEndTag,
+  // An element closed without its end tag, by EOF or by the end tag of an ancestor, when its
+  // end tag is not optional
+  UnclosedElement,
+  // An attribute without '=value', e.g. '<input disabled>', parsed as an empty value
+  AttributeWithoutValue,
+  // An attribute value without quotes, e.g. '<div class=main>'
+  UnquotedAttributeValue,
+  // An attribute repeated in the same tag, e.g. '<p id=a id=b>', ignored
+  DuplicateAttribute,
+  // A '<' that doesn't start a tag, e.g. 'a < b', kept as text
+  UnescapedLessThan,
+  // A comment that the end of the input closes, e.g. '<!-- note'
+  UnterminatedComment,
+  // A CDATA section in SVG or MathML content that the end of the input closes, e.g.
+  // '<svg><![CDATA[x'
+  UnterminatedCdata,
+  // A markup declaration other than a comment or the doctype, e.g. '<!ELEMENT br EMPTY>',
+  // kept as a comment
+  BogusComment,
+  // A doctype with a missing name or identifier, or with unexpected contents
+  MalformedDoctype,
+  // A doctype anywhere but at the start of the document, ignored
+  MisplacedDoctype,
+  // An <html>, <head> or <body> start tag after the element it starts already exists
+  MisplacedTag,
+  // An element nested deeper than the parser allows, parsed as a sibling instead
+  NestingTooDeep,
+}
+
+// An error found while parsing, with the place in the source where it was found
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+  kind: DiagnosticKind,
+  span: Span,
+  message: String,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut Formatter) -> Result {
+    write!(f, "{}: {}", self.span, self.message)
+  }
+}
+
+impl Diagnostic {
+  pub fn new(kind: DiagnosticKind, span: Span, message: String) -> Self {
+    Self {
+      kind,
+      span,
+      message,
+    }
+  }
+
+  pub fn kind(&self) -> DiagnosticKind {
+    self.kind
+  }
+
+  pub fn span(&self) -> &Span {
+    &self.span
+  }
+
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct Node {
+  // data common to all nodes:
+  children: Vec<Node>,
+  // where the node comes from in the source, or None for nodes not parsed from a source or
+  // implied by the parser (e.g. a <tbody> inserted around table rows):
+  span: Option<Span>,
+  // data specific to each node type:
+  node_type: NodeType,
+}
+
+impl Node {
+  pub fn children(&self) -> &Vec<Node> {
+    &self.children
+  }
+
+  pub fn span(&self) -> Option<&Span> {
+    self.span.as_ref()
+  }
+
+  pub fn node_type(&self) -> &NodeType {
+    &self.node_type
+  }
+
+  // Return the node with its source span set
+  pub fn with_span(mut self, span: Span) -> Node {
+    self.span = Some(span);
+    self
+  }
+
+  // Return the concatenated data of all descendant text nodes, in document order
+  pub fn text_content(&self) -> String {
+    match self.node_type() {
+      NodeType::Text(text) | NodeType::Comment(text) => text.clone(),
+      NodeType::Doctype(_) => String::new(),
+      NodeType::Element(_) => {
+        let mut content: String = String::new();
+        for child in self.children() {
+          if let NodeType::Comment(_) = child.node_type() {
+            continue;
+          }
+          content.push_str(&child.text_content());
+        }
+        content
+      }
+    }
+  }
+}
+
+// Nodes are compared by contents only: the same tree parsed from differently formatted sources
+// is still equal
+impl PartialEq for Node {
+  fn eq(&self, other: &Self) -> bool {
+    *self.children == *other.children && self.node_type == other.node_type
+  }
+}
+
+impl Node {
+  pub fn text(data: String) -> Node {
+    Node {
+      children: Vec::new(),
+      span: None,
+      node_type: NodeType::Text(data),
+    }
+  }
+
+  pub fn element(tag_name: String, attributes: AttributeMap, children: Vec<Node>) -> Node {
+    Node::element_in_namespace(Namespace::Html, tag_name, attributes, children)
+  }
+
+  // An SVG or MathML element, or an HTML element for Namespace::Html
+  pub fn element_in_namespace(
+    namespace: Namespace,
+    tag_name: String,
+    attributes: AttributeMap,
+    children: Vec<Node>,
+  ) -> Node {
+    Node {
+      children,
+      span: None,
+      node_type: NodeType::Element(ElementData::new_in_namespace(namespace, tag_name, attributes)),
+    }
+  }
+
+  pub fn comment(data: String) -> Node {
+    Node {
+      children: Vec::new(),
+      span: None,
+      node_type: NodeType::Comment(data),
+    }
+  }
+
+  pub fn doctype(name: String, public_id: Option<String>, system_id: Option<String>) -> Node {
+    Node {
+      children: Vec::new(),
+      span: None,
+      node_type: NodeType::Doctype(DoctypeData::new(name, public_id, system_id)),
+    }
+  }
+
+  pub fn print_node_tree(node: &Node, indent: usize) {
+    match node.node_type() {
+      NodeType::Text(text) => {
+        // Indentation of 'indent' spaces before the next argument
+        println!("{:spaces$}{}", "", text, spaces = indent);
+      }
+      NodeType::Comment(comment) => {
+        println!("{:spaces$}<!--{}-->", "", comment, spaces = indent);
+      }
+      NodeType::Doctype(doctype) => {
+        println!("{:spaces$}{}", "", doctype, spaces = indent);
+      }
+      NodeType::Element(element) => {
+        println!("{:spaces$}{}", "", element, spaces = indent);
+        for child in node.children() {
+          Node::print_node_tree(child, indent + 2);
+        }
+        println!("{:spaces$}</{}>", "", element.tag_name(), spaces = indent);
+      }
+    }
+  }
+
+  pub fn print_only_text_from_node_tree(node: &Node) {
+    match node.node_type() {
+      NodeType::Text(text) => {
+        println!("{}\n", text);
+      }
+      NodeType::Comment(_) | NodeType::Doctype(_) => {}
+      NodeType::Element(_) => {
+        for child in node.children() {
+          Node::print_only_text_from_node_tree(child);
+        }
+      }
+    }
+  }
+}
+
+// A parsed HTML document: its doctype, the DOM tree, and the stylesheets it references in
+// document order
+#[derive(Debug)]
+pub struct Document {
+  doctype: Option<Node>,
+  root: Node,
+  stylesheets: Vec<css::Stylesheet>,
+  quirks_mode: QuirksMode,
+  // Errors the parser recovered from, in source order
+  diagnostics: Vec<Diagnostic>,
+  // Name of the encoding the source was decoded from, e.g. 'UTF-8' or 'windows-1252'
+  encoding: &'static str,
+}
+
+impl PartialEq for Document {
+  fn eq(&self, other: &Self) -> bool {
+    self.doctype == other.doctype
+      && self.root == other.root
+      && self.stylesheets == other.stylesheets
+      && self.quirks_mode == other.quirks_mode
+      && self.diagnostics == other.diagnostics
+      && self.encoding == other.encoding
+  }
+}
+
+impl Document {
+  pub fn new(
+    doctype: Option<Node>,
+    root: Node,
+    stylesheets: Vec<css::Stylesheet>,
+    quirks_mode: QuirksMode,
+  ) -> Self {
+    Self {
+      doctype,
+      root,
+      stylesheets,
+      quirks_mode,
+      diagnostics: vec![],
+      encoding: "UTF-8",
+    }
+  }
+
+  // The doctype node, if the document has one
+  pub fn doctype(&self) -> Option<&Node> {
+    self.doctype.as_ref()
+  }
+
+  pub fn root(&self) -> &Node {
+    &self.root
+  }
+
+  // Replace the DOM tree, e.g. with one edited as a Tree
+  pub fn set_root(&mut self, root: Node) {
+    self.root = root;
+  }
+
+  pub fn quirks_mode(&self) -> QuirksMode {
+    self.quirks_mode
+  }
+
+  pub fn stylesheets(&self) -> &Vec<css::Stylesheet> {
+    &self.stylesheets
+  }
+
+  pub fn add_stylesheet(&mut self, stylesheet: css::Stylesheet) {
+    self.stylesheets.push(stylesheet);
+  }
+
+  pub fn diagnostics(&self) -> &Vec<Diagnostic> {
+    &self.diagnostics
+  }
+
+  pub fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+    self.diagnostics.push(diagnostic);
+  }
+
+  // The encoding of the source, UTF-8 for documents parsed from text
+  pub fn encoding(&self) -> &'static str {
+    self.encoding
+  }
+
+  pub fn set_encoding(&mut self, encoding: &'static str) {
+    self.encoding = encoding;
+  }
+}
+
+// Build a map from 'key => value' pairs: a HashMap, or the AttributeMap of an element
+#[macro_export]
+macro_rules! hashmap {
+  ($( $key: expr => $val: expr ),*) => {{
+       [$( ($key, $val) ),*].into_iter().collect()
+  }}
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use super::{Document, ElementData, Namespace, Node, NodeType};
+use crate::css;
+use crate::parser::css::CSSParser;
+use crate::style::matching;
+
+// Iterator over nodes in document order: each node comes before its descendants, which come
+// before its next sibling
+pub struct Descendants<'a> {
+  stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+  type Item = &'a Node;
+
+  fn next(&mut self) -> Option<&'a Node> {
+    let node: &Node = self.stack.pop()?;
+    self.stack.extend(node.children().iter().rev());
+    Some(node)
+  }
+}
+
+// Keep the elements among 'nodes' that pass 'test'
+fn filter_elements<'a, I, F>(nodes: I, test: F) -> impl Iterator<Item = &'a Node>
+where
+  I: Iterator<Item = &'a Node>,
+  F: Fn(&ElementData) -> bool,
+{
+  nodes.filter(move |node: &&Node| match node.node_type() {
+    NodeType::Element(element) => test(element),
+    _ => false,
+  })
+}
+
+fn has_id(id: &str) -> impl Fn(&ElementData) -> bool + '_ {
+  move |element: &ElementData| element.id().is_some_and(|value: &String| value == id)
+}
+
+// Match elements that have all the classes of a space-separated list. An empty list matches
+// nothing
+fn has_classes(class_names: &str) -> impl Fn(&ElementData) -> bool + '_ {
+  let class_names: Vec<&str> = class_names.split_whitespace().collect();
+  move |element: &ElementData| {
+    let classes: HashSet<&str> = element.classes();
+    !class_names.is_empty() && class_names.iter().all(|name: &&str| classes.contains(name))
+  }
+}
+
+// Match elements with a tag name, or all elements for '*'. The case of the name is ignored for
+// HTML elements only, so that 'linearGradient' doesn't match an SVG <lineargradient>
+// https://dom.spec.whatwg.org/#concept-getelementsbytagname
+fn has_tag_name(tag_name: &str) -> impl Fn(&ElementData) -> bool + '_ {
+  let lowercase_tag_name: String = tag_name.to_ascii_lowercase();
+  move |element: &ElementData| match element.namespace() {
+    _ if tag_name == "*" => true,
+    Namespace::Html => element.tag_name() == lowercase_tag_name,
+    _ => element.tag_name() == tag_name,
+  }
+}
+
+// Search 'nodes' and their descendants in document order for the elements that match any
+// selector of a list. 'ancestors' are the ancestors of 'nodes', from the root to their parent,
+// and grow with the search for selectors with combinators. With 'first_only', the search stops
+// at the first match
+fn select_elements<'a>(
+  nodes: &'a [Node],
+  ancestors: &mut Vec<&'a Node>,
+  selectors: &[css::Selector],
+  first_only: bool,
+  found: &mut Vec<&'a Node>,
+) {
+  for node in nodes {
+    if first_only && !found.is_empty() {
+      return;
+    }
+    if selectors
+      .iter()
+      .any(|selector: &css::Selector| matching::matches(node, ancestors, selector))
+    {
+      found.push(node);
+    }
+    ancestors.push(node);
+    select_elements(node.children(), ancestors, selectors, first_only, found);
+    ancestors.pop();
+  }
+}
+
+// Find the elements that match a selector list, or return the error that makes the list invalid
+fn query_selectors<'a>(
+  nodes: &'a [Node],
+  mut ancestors: Vec<&'a Node>,
+  selectors: &str,
+  first_only: bool,
+) -> Result<Vec<&'a Node>, String> {
+  let selectors: Vec<css::Selector> = CSSParser::parse_selector_list(selectors.to_string())?;
+  let mut found: Vec<&Node> = vec![];
+  select_elements(nodes, &mut ancestors, &selectors, first_only, &mut found);
+  Ok(found)
+}
+
+// The query methods search the descendants of a node, in document order
+impl Node {
+  pub fn descendants(&self) -> Descendants<'_> {
+    Descendants {
+      stack: self.children().iter().rev().collect(),
+    }
+  }
+
+  // Return the first element with the given id
+  pub fn get_element_by_id(&self, id: &str) -> Option<&Node> {
+    filter_elements(self.descendants(), has_id(id)).next()
+  }
+
+  // Return the elements that have all of a space-separated list of classes
+  pub fn get_elements_by_class_name(&self, class_names: &str) -> Vec<&Node> {
+    filter_elements(self.descendants(), has_classes(class_names)).collect()
+  }
+
+  // Return the elements with a tag name, or all elements for '*'
+  pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<&Node> {
+    filter_elements(self.descendants(), has_tag_name(tag_name)).collect()
+  }
+
+  // Return the first element that matches a CSS selector list, or an error if the list is
+  // invalid
+  pub fn query_selector(&self, selectors: &str) -> Result<Option<&Node>, String> {
+    Ok(query_selectors(self.children(), vec![self], selectors, true)?.pop())
+  }
+
+  // Return all the elements that match a CSS selector list, or an error if the list is invalid.
+  // Combinators can match the node and its descendants, but not the node's own ancestors
+  pub fn query_selector_all(&self, selectors: &str) -> Result<Vec<&Node>, String> {
+    query_selectors(self.children(), vec![self], selectors, false)
+  }
+}
+
+// The query methods of a document search the whole tree, including its root element
+impl Document {
+  fn nodes(&self) -> impl Iterator<Item = &Node> {
+    std::iter::once(self.root()).chain(self.root().descendants())
+  }
+
+  pub fn get_element_by_id(&self, id: &str) -> Option<&Node> {
+    filter_elements(self.nodes(), has_id(id)).next()
+  }
+
+  pub fn get_elements_by_class_name(&self, class_names: &str) -> Vec<&Node> {
+    filter_elements(self.nodes(), has_classes(class_names)).collect()
+  }
+
+  pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<&Node> {
+    filter_elements(self.nodes(), has_tag_name(tag_name)).collect()
+  }
+
+  pub fn query_selector(&self, selectors: &str) -> Result<Option<&Node>, String> {
+    Ok(query_selectors(std::slice::from_ref(self.root()), vec![], selectors, true)?.pop())
+  }
+
+  pub fn query_selector_all(&self, selectors: &str) -> Result<Vec<&Node>, String> {
+    query_selectors(std::slice::from_ref(self.root()), vec![], selectors, false)
+  }
+}
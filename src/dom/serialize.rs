@@ -0,0 +1,106 @@
+use super::{Document, Namespace, Node, NodeType};
+use crate::parser::html::{RAW_TEXT_ELEMENTS, is_void_element};
+
+// Escape text for use in an attribute value or, with 'attribute' false, in a text node
+// https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+fn escape(text: &str, attribute: bool) -> String {
+  let mut result: String = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => result.push_str("&amp;"),
+      '\u{A0}' => result.push_str("&nbsp;"),
+      '<' => result.push_str("&lt;"),
+      '>' => result.push_str("&gt;"),
+      '"' if attribute => result.push_str("&quot;"),
+      _ => result.push(c),
+    }
+  }
+  result
+}
+
+// Append the HTML of the children of a node to 'html'. The text of raw text elements such as
+// <script> and <style> is written verbatim, since character references are not decoded there.
+// SVG and MathML elements have no raw text: an SVG <style> is escaped like any other element
+fn serialize_children(node: &Node, html: &mut String) {
+  let raw_text: bool = match node.node_type() {
+    NodeType::Element(element) => {
+      element.namespace() == Namespace::Html && RAW_TEXT_ELEMENTS.contains(&element.tag_name())
+    }
+    _ => false,
+  };
+  for child in node.children() {
+    match child.node_type() {
+      NodeType::Text(text) if raw_text => html.push_str(text),
+      _ => serialize_node(child, html),
+    }
+  }
+}
+
+// Append the HTML of a node and its descendants to 'html'
+// https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+fn serialize_node(node: &Node, html: &mut String) {
+  match node.node_type() {
+    NodeType::Text(text) => html.push_str(&escape(text, false)),
+    NodeType::Comment(comment) => {
+      html.push_str("<!--");
+      html.push_str(comment);
+      html.push_str("-->");
+    }
+    NodeType::Doctype(doctype) => html.push_str(&doctype.to_string()),
+    NodeType::Element(element) => {
+      html.push('<');
+      html.push_str(element.tag_name());
+      for (name, value) in element.attributes() {
+        html.push_str(&format!(" {}=\"{}\"", name, escape(value, true)));
+      }
+      html.push('>');
+      // Void elements have no contents and no end tag. SVG and MathML elements always get an
+      // end tag, which parses back the same as a self-closing tag
+      if element.namespace() == Namespace::Html && is_void_element(element.tag_name()) {
+        return;
+      }
+      // The parser skips a newline right after the start tag of these elements, so one that
+      // starts their text is written after an extra newline to survive parsing back
+      let skips_leading_newline: bool = element.namespace() == Namespace::Html
+        && matches!(element.tag_name(), "pre" | "textarea" | "listing");
+      let starts_with_newline: bool = node.children().first().is_some_and(|child: &Node| {
+        matches!(child.node_type(), NodeType::Text(text) if text.starts_with('\n'))
+      });
+      if skips_leading_newline && starts_with_newline {
+        html.push('\n');
+      }
+      serialize_children(node, html);
+      html.push_str("</");
+      html.push_str(element.tag_name());
+      html.push('>');
+    }
+  }
+}
+
+impl Node {
+  // Serialize the node and its descendants to HTML that parses back to the same tree
+  pub fn outer_html(&self) -> String {
+    let mut html: String = String::new();
+    serialize_node(self, &mut html);
+    html
+  }
+
+  // Serialize the descendants of the node to HTML
+  pub fn inner_html(&self) -> String {
+    let mut html: String = String::new();
+    serialize_children(self, &mut html);
+    html
+  }
+}
+
+impl Document {
+  // Serialize the whole document, including its doctype, to HTML
+  pub fn to_html(&self) -> String {
+    let mut html: String = String::new();
+    if let Some(doctype) = self.doctype() {
+      serialize_node(doctype, &mut html);
+    }
+    serialize_node(self.root(), &mut html);
+    html
+  }
+}
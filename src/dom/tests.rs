@@ -0,0 +1,388 @@
+use crate::hashmap;
+use crate::parser::html::HTMLParser;
+use super::*;
+
+// Test the function text of the Node struct implementation
+#[test]
+fn test_text() {
+  let node: Node = Node::text("Hello World!".to_string());
+
+  // Assert that the node_type method correctly returns the text "Hello World!"
+  assert_eq!(
+    *node.node_type(),
+    NodeType::Text("Hello World!".to_string())
+  );
+  // Assert that the children method correctly returns no elements
+  assert_eq!(*node.children(), Vec::new());
+}
+
+// Test the function element of the Node struct implementation
+#[test]
+fn test_element() {
+  // Node 2: <span class='text'>
+  let tag_name_2: String = String::from("span");
+  let attributes_2: AttributeMap = hashmap![String::from("class") => String::from("text")];
+  let children_2: Vec<Node> = vec![];
+  let node_2: Node = Node::element(tag_name_2, attributes_2, children_2);
+  // Node 1: <p class='paragraph'>
+  let tag_name_1: String = String::from("p");
+  let attributes_1: AttributeMap = hashmap![String::from("class") => String::from("paragraph")];
+  let children_1: Vec<Node> = vec![node_2];
+  let node_1: Node = Node::element(tag_name_1.clone(), attributes_1.clone(), children_1.clone());
+
+  // Assert that the node_type method correctly returns the NodeType (Element) with the correct ElementData (tag name and attributes).
+  assert_eq!(
+    *node_1.node_type(),
+    NodeType::Element(ElementData::new(tag_name_1, attributes_1))
+  );
+  // Assert that the children method correctly returns the node_1 children
+  assert_eq!(*node_1.children(), children_1);
+}
+
+// Test the method text_content of the Node struct implementation
+#[test]
+fn test_text_content() {
+  // Node: <p>Hello <!-- comment --><b>World</b>!</p>
+  let bold: Node = Node::element(
+    String::from("b"),
+    hashmap![],
+    vec![Node::text("World".to_string())],
+  );
+  let node: Node = Node::element(
+    String::from("p"),
+    hashmap![],
+    vec![
+      Node::text("Hello ".to_string()),
+      Node::comment(" comment ".to_string()),
+      bold,
+      Node::text("!".to_string()),
+    ],
+  );
+
+  // Assert that the text_content method concatenates descendant text, skipping comments
+  assert_eq!(node.text_content(), "Hello World!");
+}
+
+// Build the tree: <ul id='list'><li>one</li><li>two</li></ul>
+fn list_tree() -> (Tree, NodeId, NodeId) {
+  let list: Node = Node::element(
+    String::from("ul"),
+    hashmap![String::from("id") => String::from("list")],
+    vec![
+      Node::element(String::from("li"), hashmap![], vec![Node::text("one".to_string())]),
+      Node::element(String::from("li"), hashmap![], vec![Node::text("two".to_string())]),
+    ],
+  );
+  let tree: Tree = Tree::new(list);
+  let first: NodeId = tree.children(tree.root())[0];
+  let second: NodeId = tree.children(tree.root())[1];
+  (tree, first, second)
+}
+
+// Test the navigation methods of the Tree struct implementation
+#[test]
+fn test_tree_navigation() {
+  let (tree, first, second): (Tree, NodeId, NodeId) = list_tree();
+  let root: NodeId = tree.root();
+
+  // Assert that parent links and sibling navigation follow the tree
+  assert_eq!(tree.parent(root), None);
+  assert_eq!(tree.parent(first), Some(root));
+  assert_eq!(tree.first_child(root), Some(first));
+  assert_eq!(tree.last_child(root), Some(second));
+  assert_eq!(tree.next_sibling(first), Some(second));
+  assert_eq!(tree.previous_sibling(second), Some(first));
+  assert_eq!(tree.previous_sibling(first), None);
+  assert_eq!(tree.next_sibling(second), None);
+  assert_eq!(tree.next_sibling(root), None);
+  // Assert that the tree converts back to the node it was built from
+  let (original, _, _): (Tree, NodeId, NodeId) = list_tree();
+  assert_eq!(tree.to_node(root), original.to_node(original.root()));
+  assert_eq!(tree.text_content(root), "onetwo");
+}
+
+// Test the methods append_child, insert_before, remove_child and replace_child of the Tree
+// struct implementation
+#[test]
+fn test_tree_insertion_and_removal() {
+  let (mut tree, first, second): (Tree, NodeId, NodeId) = list_tree();
+  let root: NodeId = tree.root();
+  let third: NodeId = tree.create_node(Node::element(String::from("li"), hashmap![], vec![]));
+
+  // Assert that a created node is detached until it is inserted
+  assert_eq!(tree.parent(third), None);
+  tree.append_child(root, third).unwrap();
+  assert_eq!(tree.children(root), &[first, second, third]);
+  // Assert that inserting a node that has a parent moves it
+  tree.insert_before(root, third, first).unwrap();
+  assert_eq!(tree.children(root), &[third, first, second]);
+  tree.insert_before(root, second, second).unwrap();
+  assert_eq!(tree.children(root), &[third, first, second]);
+  // Assert that a removed node keeps its id and can be inserted again
+  tree.remove_child(root, first).unwrap();
+  assert_eq!(tree.children(root), &[third, second]);
+  assert_eq!(tree.parent(first), None);
+  tree.replace_child(root, first, third).unwrap();
+  assert_eq!(tree.children(root), &[first, second]);
+  assert_eq!(tree.parent(third), None);
+  assert_eq!(tree.parent(first), Some(root));
+  assert_eq!(tree.text_content(root), "onetwo");
+}
+
+// Test that the editing methods of the Tree struct implementation reject invalid changes
+#[test]
+fn test_tree_invalid_changes() {
+  let (mut tree, first, second): (Tree, NodeId, NodeId) = list_tree();
+  let root: NodeId = tree.root();
+  let text: NodeId = tree.first_child(first).unwrap();
+  let doctype: NodeId = tree.create_node(Node::doctype("html".to_string(), None, None));
+  // A node id from a bigger tree, out of the bounds of this one
+  let mut other_tree: Tree = Tree::new(Node::element(String::from("div"), hashmap![], vec![]));
+  let foreign: NodeId = other_tree.create_node(list_tree().0.to_node(root));
+  let foreign: NodeId = other_tree.create_node(other_tree.to_node(foreign));
+
+  // Assert that a node can't be inserted into itself, its descendants or a text node
+  assert!(tree.append_child(first, root).is_err());
+  assert!(tree.append_child(first, first).is_err());
+  assert!(tree.append_child(text, second).is_err());
+  assert!(tree.append_child(root, doctype).is_err());
+  // Assert that the reference and removed nodes must be children of the parent
+  assert!(tree.insert_before(first, second, root).is_err());
+  assert!(tree.remove_child(first, second).is_err());
+  assert!(tree.replace_child(first, doctype, second).is_err());
+  assert!(tree.set_attribute(text, "id".to_string(), "x".to_string()).is_err());
+  assert!(tree.remove_child(root, foreign).is_err());
+  assert!(tree.append_child(root, foreign).is_err());
+  // Assert that rejected changes leave the tree unchanged
+  assert_eq!(tree.children(root), &[first, second]);
+  assert_eq!(tree.parent(second), Some(root));
+}
+
+// Test the methods set_attribute, remove_attribute and set_text_content of the Tree struct
+// implementation
+#[test]
+fn test_tree_attributes_and_text() {
+  let (mut tree, first, second): (Tree, NodeId, NodeId) = list_tree();
+  let root: NodeId = tree.root();
+  let text: NodeId = tree.first_child(second).unwrap();
+
+  tree.set_attribute(root, "class".to_string(), "menu".to_string()).unwrap();
+  tree.set_attribute(root, "ID".to_string(), "nav".to_string()).unwrap();
+  // Assert that attributes are added, replaced and removed
+  assert_eq!(
+    tree.remove_attribute(root, "class").unwrap(),
+    Some("menu".to_string())
+  );
+  assert_eq!(tree.remove_attribute(root, "class").unwrap(), None);
+  assert_eq!(
+    *tree.node_type(root),
+    NodeType::Element(ElementData::new(
+      "ul".to_string(),
+      hashmap![String::from("id") => String::from("nav")]
+    ))
+  );
+  // Assert that setting the text of an element replaces its children with one text node
+  tree.set_text_content(first, "first".to_string()).unwrap();
+  assert_eq!(tree.children(first).len(), 1);
+  assert_eq!(tree.text_content(root), "firsttwo");
+  tree.set_text_content(first, String::new()).unwrap();
+  assert_eq!(tree.children(first).len(), 0);
+  // Assert that setting the text of a text node changes its data in place
+  tree.set_text_content(text, "second".to_string()).unwrap();
+  assert_eq!(tree.first_child(second), Some(text));
+  assert_eq!(tree.text_content(root), "second");
+}
+
+// Parse the document used by the query tests
+fn query_document() -> Document {
+  HTMLParser::parse_document(
+    "<div id='main' class='page wide'>\
+      <p class='intro'>one</p>\
+      <p id='second' class='wide'>two</p>\
+      <section><P class='page'>three</P></section>\
+    </div>"
+      .to_string(),
+  )
+  .unwrap()
+}
+
+// Test the methods descendants, get_element_by_id, get_elements_by_class_name and
+// get_elements_by_tag_name of the Node and Document struct implementations
+#[test]
+fn test_get_elements() {
+  let document: Document = query_document();
+  let main: &Node = document.get_element_by_id("main").unwrap();
+  let texts = |nodes: Vec<&Node>| -> Vec<String> {
+    nodes.iter().map(|node: &&Node| node.text_content()).collect()
+  };
+
+  // Assert that descendants come in document order, without the node itself
+  assert_eq!(main.descendants().count(), 7);
+  assert_eq!(main.descendants().next(), main.children().first());
+  // Assert that an element is found by id, and that a node doesn't find itself
+  assert_eq!(document.get_element_by_id("second").unwrap().text_content(), "two");
+  assert_eq!(main.get_element_by_id("main"), None);
+  assert_eq!(document.get_element_by_id("missing"), None);
+  // Assert that elements are found by all of their classes, in document order
+  assert_eq!(texts(main.get_elements_by_class_name("wide")), vec!["two"]);
+  assert_eq!(document.get_elements_by_class_name(" wide  page ").len(), 1);
+  assert_eq!(texts(document.get_elements_by_class_name("page")).len(), 2);
+  assert!(document.get_elements_by_class_name(" ").is_empty());
+  // Assert that tag names are matched ignoring case, and that '*' matches every element
+  assert_eq!(
+    texts(main.get_elements_by_tag_name("P")),
+    vec!["one", "two", "three"]
+  );
+  assert_eq!(main.get_elements_by_tag_name("*").len(), 4);
+  assert_eq!(document.get_elements_by_tag_name("html").len(), 1);
+}
+
+// Test the methods query_selector and query_selector_all of the Node and Document struct
+// implementations
+#[test]
+fn test_query_selector() {
+  let document: Document = query_document();
+  let main: &Node = document.query_selector("#main").unwrap().unwrap();
+  let texts = |nodes: Vec<&Node>| -> Vec<String> {
+    nodes.iter().map(|node: &&Node| node.text_content()).collect()
+  };
+
+  // Assert that elements matching any selector of the list are returned in document order
+  assert_eq!(
+    texts(main.query_selector_all("p.page, .intro").unwrap()),
+    vec!["one", "three"]
+  );
+  assert_eq!(
+    main.query_selector("p.wide").unwrap().unwrap().text_content(),
+    "two"
+  );
+  assert_eq!(document.query_selector_all(".page").unwrap().len(), 2);
+  assert_eq!(main.query_selector("div").unwrap(), None);
+  // Assert that combinators match the ancestors and previous siblings of elements
+  assert_eq!(texts(document.query_selector_all("body > div > p").unwrap()), vec!["one", "two"]);
+  assert_eq!(texts(main.query_selector_all("div section p").unwrap()), vec!["three"]);
+  assert_eq!(texts(main.query_selector_all(".intro + p, .intro ~ *").unwrap()).len(), 2);
+  assert_eq!(main.query_selector("#second + p").unwrap(), None);
+  assert_eq!(main.query_selector("body p").unwrap(), None);
+  // Assert that an invalid selector list is an error
+  assert!(main.query_selector_all("p,").is_err());
+  assert!(document.query_selector("").is_err());
+}
+
+// Test the methods outer_html and inner_html of the Node struct implementation
+#[test]
+fn test_outer_html() {
+  // Node: <p title='a "b" & c'>1 < 2 &amp; 3<br><!-- note --></p>
+  let node: Node = Node::element(
+    String::from("p"),
+    hashmap![String::from("title") => String::from("a \"b\" & c")],
+    vec![
+      Node::text("1 < 2 &amp;\u{A0}3".to_string()),
+      Node::element(String::from("br"), hashmap![], vec![]),
+      Node::comment(" note ".to_string()),
+    ],
+  );
+  let script: Node = Node::element(
+    String::from("script"),
+    hashmap![],
+    vec![Node::text("if (a < b && c) {}".to_string())],
+  );
+
+  // Assert that text and attribute values are escaped, and that void elements have no end tag
+  assert_eq!(
+    node.outer_html(),
+    "<p title=\"a &quot;b&quot; &amp; c\">1 &lt; 2 &amp;amp;&nbsp;3<br><!-- note --></p>"
+  );
+  assert_eq!(node.inner_html(), "1 &lt; 2 &amp;amp;&nbsp;3<br><!-- note -->");
+  // Assert that the text of raw text elements is written verbatim
+  assert_eq!(script.outer_html(), "<script>if (a < b && c) {}</script>");
+  assert_eq!(Node::text("a < b".to_string()).inner_html(), "");
+}
+
+// Test that the method to_html of the Document struct implementation round-trips through the
+// parser
+#[test]
+fn test_to_html() {
+  let source: &str = "<!DOCTYPE html><title>Q&amp;A</title>\
+    <style>p > a { color: #ff0000; }</style>\
+    <p class='intro' hidden>caf&eacute; &lt;3<img src=\"a.png\" alt='&quot;'></p>\
+    <table><tr><td>1<td>2</table><textarea>&lt;b&gt;</textarea><!-- end -->\
+    <pre>\n\nfoo</pre><textarea>\n\nbar</textarea><listing>\nbaz</listing>";
+  let document: Document = HTMLParser::parse_document(source.to_string()).unwrap();
+  let html: String = document.to_html();
+
+  // Assert that the serialized document has the implied elements and end tags
+  assert!(html.starts_with("<!DOCTYPE html><html><head><title>Q&amp;A</title>"));
+  assert!(html.contains("<td>1</td><td>2</td></tr></tbody></table>"));
+  // Assert that a newline starting the text of a <pre>, <textarea> or <listing> is written after
+  // an extra one, which the parser skips
+  assert!(html.contains("<pre>\n\nfoo</pre><textarea>\n\nbar</textarea><listing>baz</listing>"));
+  // Assert that parsing the serialized document gives back the same tree
+  let reparsed: Document = HTMLParser::parse_document(html.clone()).unwrap();
+  assert_eq!(reparsed.doctype(), document.doctype());
+  assert_eq!(reparsed.root(), document.root());
+  // Assert that attributes are written in source order, so serializing again gives the same HTML
+  assert_eq!(reparsed.to_html(), html);
+}
+
+// Test queries, attribute changes and serialization of SVG elements
+#[test]
+fn test_foreign_elements() {
+  let source: &str = "<p>a</p><svg viewBox='0 0 2 2'><linearGradient id=g />\
+    <style>rect { fill: red; }</style><rect width=2 /></svg>";
+  let document: Document = HTMLParser::parse_document(source.to_string()).unwrap();
+
+  // Assert that SVG tag names are matched with their case
+  assert_eq!(document.get_elements_by_tag_name("linearGradient").len(), 1);
+  assert!(document.get_elements_by_tag_name("LINEARGRADIENT").is_empty());
+  assert_eq!(document.get_elements_by_tag_name("P").len(), 1);
+  assert_eq!(document.query_selector_all("*|rect, *|p").unwrap().len(), 2);
+  // Assert that the attribute names of SVG elements keep their case
+  let mut tree: Tree = Tree::new(document.root().clone());
+  let body: NodeId = tree.last_child(tree.root()).unwrap();
+  let svg: NodeId = tree.last_child(body).unwrap();
+  tree.set_attribute(svg, "preserveAspectRatio".to_string(), "none".to_string()).unwrap();
+  assert_eq!(tree.remove_attribute(svg, "viewbox").unwrap(), None);
+  assert_eq!(
+    tree.remove_attribute(svg, "viewBox").unwrap(),
+    Some("0 0 2 2".to_string())
+  );
+  assert_eq!(
+    tree.to_node(svg).outer_html(),
+    "<svg preserveAspectRatio=\"none\"><linearGradient id=\"g\"></linearGradient>\
+    <style>rect { fill: red; }</style><rect width=\"2\"></rect></svg>"
+  );
+  // Assert that parsing the serialized document gives back the same tree
+  let reparsed: Document = HTMLParser::parse_document(document.to_html()).unwrap();
+  assert_eq!(reparsed.root(), document.root());
+}
+
+// Test the methods of the AttributeMap struct implementation
+#[test]
+fn test_attribute_map() {
+  let mut attributes: AttributeMap = hashmap![
+    String::from("id") => String::from("main"),
+    String::from("class") => String::from("page")
+  ];
+  let names = |attributes: &AttributeMap| -> Vec<String> {
+    attributes.iter().map(|(name, _): (&String, &String)| name.clone()).collect()
+  };
+
+  // Assert that setting an existing attribute keeps its place, and a new one goes last
+  assert_eq!(attributes.insert("id".to_string(), "top".to_string()), Some("main".to_string()));
+  assert_eq!(attributes.insert("title".to_string(), "t".to_string()), None);
+  assert_eq!(names(&attributes), vec!["id", "class", "title"]);
+  assert_eq!(attributes.get("id"), Some(&"top".to_string()));
+  // Assert that removing an attribute keeps the order of the others
+  assert_eq!(attributes.remove("id"), Some("top".to_string()));
+  assert_eq!(attributes.remove("id"), None);
+  assert_eq!(names(&attributes), vec!["class", "title"]);
+  assert!(!attributes.contains_key("id"));
+  // Assert that attribute lists with the same attributes in another order are equal
+  let reordered: AttributeMap = hashmap![
+    String::from("title") => String::from("t"),
+    String::from("class") => String::from("page")
+  ];
+  assert_eq!(attributes, reordered);
+  assert_ne!(attributes, AttributeMap::new());
+}
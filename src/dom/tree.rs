@@ -0,0 +1,286 @@
+use super::{ElementData, Namespace, Node, NodeType, Span};
+
+// Identifies a node of a Tree. Ids stay valid while the tree is edited: a removed node keeps its
+// id, and can be inserted again
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NodeId(usize);
+
+// Attribute names are case-insensitive on HTML elements only: SVG and MathML keep their case
+// (e.g. 'viewBox')
+fn attribute_name(element: &ElementData, name: String) -> String {
+  match element.namespace() {
+    Namespace::Html => name.to_ascii_lowercase(),
+    _ => name,
+  }
+}
+
+#[derive(Clone, Debug)]
+struct TreeNode {
+  parent: Option<NodeId>,
+  children: Vec<NodeId>,
+  span: Option<Span>,
+  node_type: NodeType,
+}
+
+// A DOM tree stored in an arena, with links from each node to its parent, for editing a
+// document before rendering. A parsed Node is converted into a Tree to be edited, and back into
+// a Node with to_node for the style and layout stages. Nodes removed from the tree stay in the
+// arena, detached, until the Tree is dropped
+#[derive(Clone, Debug)]
+pub struct Tree {
+  nodes: Vec<TreeNode>,
+  root: NodeId,
+}
+
+impl Tree {
+  pub fn new(root: Node) -> Self {
+    let mut tree: Tree = Tree {
+      nodes: vec![],
+      root: NodeId(0),
+    };
+    tree.root = tree.create_node(root);
+    tree
+  }
+
+  pub fn root(&self) -> NodeId {
+    self.root
+  }
+
+  // The methods reading a node panic if its id comes from another tree, like indexing a Vec
+  // out of bounds; the methods editing the tree return an error instead
+  fn node(&self, id: NodeId) -> &TreeNode {
+    &self.nodes[id.0]
+  }
+
+  pub fn node_type(&self, id: NodeId) -> &NodeType {
+    &self.node(id).node_type
+  }
+
+  pub fn span(&self, id: NodeId) -> Option<&Span> {
+    self.node(id).span.as_ref()
+  }
+
+  // The parent of a node, or None for the root and for detached nodes
+  pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+    self.node(id).parent
+  }
+
+  pub fn children(&self, id: NodeId) -> &[NodeId] {
+    &self.node(id).children
+  }
+
+  pub fn first_child(&self, id: NodeId) -> Option<NodeId> {
+    self.children(id).first().copied()
+  }
+
+  pub fn last_child(&self, id: NodeId) -> Option<NodeId> {
+    self.children(id).last().copied()
+  }
+
+  pub fn previous_sibling(&self, id: NodeId) -> Option<NodeId> {
+    let siblings: &[NodeId] = self.children(self.parent(id)?);
+    let index: usize = siblings.iter().position(|&sibling: &NodeId| sibling == id)?;
+    siblings.get(index.checked_sub(1)?).copied()
+  }
+
+  pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+    let siblings: &[NodeId] = self.children(self.parent(id)?);
+    let index: usize = siblings.iter().position(|&sibling: &NodeId| sibling == id)?;
+    siblings.get(index + 1).copied()
+  }
+
+  // Add a node and its descendants to the tree, detached, and return its id. It becomes part
+  // of the document once inserted with append_child, insert_before or replace_child
+  pub fn create_node(&mut self, node: Node) -> NodeId {
+    let id: NodeId = NodeId(self.nodes.len());
+    self.nodes.push(TreeNode {
+      parent: None,
+      children: vec![],
+      span: node.span,
+      node_type: node.node_type,
+    });
+    for child in node.children {
+      let child_id: NodeId = self.create_node(child);
+      self.nodes[child_id.0].parent = Some(id);
+      self.nodes[id.0].children.push(child_id);
+    }
+    id
+  }
+
+  // Build a Node from a node of the tree and its descendants
+  pub fn to_node(&self, id: NodeId) -> Node {
+    let node: &TreeNode = self.node(id);
+    Node {
+      children: node
+        .children
+        .iter()
+        .map(|&child: &NodeId| self.to_node(child))
+        .collect(),
+      span: node.span,
+      node_type: node.node_type.clone(),
+    }
+  }
+
+  // Return the concatenated data of all descendant text nodes, in document order
+  pub fn text_content(&self, id: NodeId) -> String {
+    self.to_node(id).text_content()
+  }
+
+  fn check_id(&self, id: NodeId) -> Result<(), String> {
+    if id.0 < self.nodes.len() {
+      Ok(())
+    } else {
+      Err(format!("Node {} doesn't belong to this tree", id.0))
+    }
+  }
+
+  fn is_inclusive_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+    let mut node: Option<NodeId> = Some(id);
+    while let Some(current) = node {
+      if current == ancestor {
+        return true;
+      }
+      node = self.parent(current);
+    }
+    false
+  }
+
+  // Check that 'child' can be inserted into 'parent'
+  // https://dom.spec.whatwg.org/#concept-node-ensure-pre-insertion-validity
+  fn check_insertion(&self, parent: NodeId, child: NodeId) -> Result<(), String> {
+    self.check_id(parent)?;
+    self.check_id(child)?;
+    if !matches!(self.node_type(parent), NodeType::Element(_)) {
+      return Err("Only elements can have children".to_string());
+    }
+    if matches!(self.node_type(child), NodeType::Doctype(_)) {
+      return Err("A doctype can't be inserted into an element".to_string());
+    }
+    if child == self.root || self.is_inclusive_ancestor(child, parent) {
+      return Err("A node can't be inserted into itself or its descendants".to_string());
+    }
+    Ok(())
+  }
+
+  // The index of 'child' among the children of 'parent'
+  fn child_index(&self, parent: NodeId, child: NodeId) -> Result<usize, String> {
+    self.check_id(parent)?;
+    self.check_id(child)?;
+    self
+      .children(parent)
+      .iter()
+      .position(|&id: &NodeId| id == child)
+      .ok_or_else(|| format!("Node {} is not a child of node {}", child.0, parent.0))
+  }
+
+  // Remove a node from its parent, if it has one
+  fn detach(&mut self, id: NodeId) {
+    if let Some(parent) = self.nodes[id.0].parent.take() {
+      self.nodes[parent.0].children.retain(|&child: &NodeId| child != id);
+    }
+  }
+
+  // Insert 'child' as the last child of 'parent', moving it from its current parent if it has
+  // one
+  pub fn append_child(&mut self, parent: NodeId, child: NodeId) -> Result<(), String> {
+    self.check_insertion(parent, child)?;
+    self.detach(child);
+    self.nodes[child.0].parent = Some(parent);
+    self.nodes[parent.0].children.push(child);
+    Ok(())
+  }
+
+  // Insert 'child' into 'parent' right before its child 'reference', moving it from its current
+  // parent if it has one
+  pub fn insert_before(
+    &mut self,
+    parent: NodeId,
+    child: NodeId,
+    reference: NodeId,
+  ) -> Result<(), String> {
+    self.check_insertion(parent, child)?;
+    self.child_index(parent, reference)?;
+    if child == reference {
+      return Ok(());
+    }
+    self.detach(child);
+    let index: usize = self.child_index(parent, reference)?;
+    self.nodes[child.0].parent = Some(parent);
+    self.nodes[parent.0].children.insert(index, child);
+    Ok(())
+  }
+
+  // Detach 'child' from 'parent'. The removed node keeps its id and descendants
+  pub fn remove_child(&mut self, parent: NodeId, child: NodeId) -> Result<(), String> {
+    let index: usize = self.child_index(parent, child)?;
+    self.nodes[parent.0].children.remove(index);
+    self.nodes[child.0].parent = None;
+    Ok(())
+  }
+
+  // Put 'new_child' in the place of the child 'old_child' of 'parent', which is detached
+  pub fn replace_child(
+    &mut self,
+    parent: NodeId,
+    new_child: NodeId,
+    old_child: NodeId,
+  ) -> Result<(), String> {
+    self.check_insertion(parent, new_child)?;
+    self.child_index(parent, old_child)?;
+    if new_child == old_child {
+      return Ok(());
+    }
+    self.detach(new_child);
+    let index: usize = self.child_index(parent, old_child)?;
+    self.nodes[parent.0].children[index] = new_child;
+    self.nodes[new_child.0].parent = Some(parent);
+    self.nodes[old_child.0].parent = None;
+    Ok(())
+  }
+
+  // Set the value of an attribute. As in the parser, the name is lowercased on HTML elements
+  pub fn set_attribute(&mut self, id: NodeId, name: String, value: String) -> Result<(), String> {
+    self.check_id(id)?;
+    match &mut self.nodes[id.0].node_type {
+      NodeType::Element(element) => {
+        let name: String = attribute_name(element, name);
+        element.set_attribute(name, value);
+        Ok(())
+      }
+      _ => Err("Only elements have attributes".to_string()),
+    }
+  }
+
+  // Remove an attribute, returning its value if it existed
+  pub fn remove_attribute(&mut self, id: NodeId, name: &str) -> Result<Option<String>, String> {
+    self.check_id(id)?;
+    match &mut self.nodes[id.0].node_type {
+      NodeType::Element(element) => {
+        let name: String = attribute_name(element, name.to_string());
+        Ok(element.remove_attribute(&name))
+      }
+      _ => Err("Only elements have attributes".to_string()),
+    }
+  }
+
+  // Set the data of a text or comment node, or replace the children of an element with a single
+  // text node (or none, for an empty string). A doctype has no text content, so it is unchanged
+  // https://dom.spec.whatwg.org/#dom-node-textcontent
+  pub fn set_text_content(&mut self, id: NodeId, text: String) -> Result<(), String> {
+    self.check_id(id)?;
+    match &mut self.nodes[id.0].node_type {
+      NodeType::Text(data) | NodeType::Comment(data) => *data = text,
+      NodeType::Doctype(_) => {}
+      NodeType::Element(_) => {
+        for child in std::mem::take(&mut self.nodes[id.0].children) {
+          self.nodes[child.0].parent = None;
+        }
+        if !text.is_empty() {
+          let child: NodeId = self.create_node(Node::text(text));
+          self.append_child(id, child)?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
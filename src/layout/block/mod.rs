@@ -1,6 +1,7 @@
 use crate::css;
 use crate::style::StyledNode;
-use super::{Dimensions, LayoutBox};
+use super::{Dimensions, LayoutBox, Rectangle};
+use super::types::to_px;
 
 fn sum<I>(iter: I) -> f32
 where
@@ -12,9 +13,15 @@ where
 impl<'a> LayoutBox<'a> {
   // Calculate the width of a block-level non-replaced element in normal flow
   // http://www.w3.org/TR/CSS2/visudet.html#blockwidth
-  // Sets the horizontal margin/padding/border dimensions, and the "width"
-  pub(super) fn calculate_block_width(&mut self, containing_block: Dimensions) {
+  // Sets the horizontal margin/padding/border dimensions, and the "width". Percentages are of
+  // the width of the containing block
+  pub(super) fn calculate_block_width(
+    &mut self,
+    containing_block: Dimensions,
+    viewport: Rectangle,
+  ) {
     let style: &StyledNode = self.get_style_node();
+    let px = |value: &css::Value| -> f32 { to_px(value, containing_block.content.width, viewport) };
 
     // "width" has initial value "auto"
     let auto: css::Value = css::Value::Keyword("auto".to_string());
@@ -23,14 +30,14 @@ impl<'a> LayoutBox<'a> {
     // margin, border, and padding have initial value 0
     let zero: css::Value = css::Value::Length(0.0, css::Unit::Px);
 
-    let mut margin_left: css::Value = style.lookup("margin-left", "margin", &zero);
-    let mut margin_right: css::Value = style.lookup("margin-right", "margin", &zero);
+    let mut margin_left: css::Value = style.value_or("margin-left", &zero);
+    let mut margin_right: css::Value = style.value_or("margin-right", &zero);
 
-    let border_left: css::Value = style.lookup("border-left-width", "border-width", &zero);
-    let border_right: css::Value = style.lookup("border-right-width", "border-width", &zero);
+    let border_left: css::Value = style.value_or("border-left-width", &zero);
+    let border_right: css::Value = style.value_or("border-right-width", &zero);
 
-    let padding_left: css::Value = style.lookup("padding-left", "padding", &zero);
-    let padding_right: css::Value = style.lookup("padding-right", "padding", &zero);
+    let padding_left: css::Value = style.value_or("padding-left", &zero);
+    let padding_right: css::Value = style.value_or("padding-right", &zero);
 
     let total: f32 = sum(
       [
@@ -43,7 +50,7 @@ impl<'a> LayoutBox<'a> {
         &width,
       ]
       .iter()
-      .map(|v: &&css::Value| v.to_px()),
+      .map(|v: &&css::Value| px(v)),
     );
 
     // If width is not auto and the total is wider than the container, treat auto margins as 0
@@ -64,7 +71,7 @@ impl<'a> LayoutBox<'a> {
     match (width == auto, margin_left == auto, margin_right == auto) {
       // If the values are overconstrained, calculate margin_right.
       (false, false, false) => {
-        margin_right = css::Value::Length(margin_right.to_px() + underflow, css::Unit::Px);
+        margin_right = css::Value::Length(px(&margin_right) + underflow, css::Unit::Px);
       }
 
       // If exactly one size is auto, its used value follows from the equality
@@ -90,7 +97,7 @@ impl<'a> LayoutBox<'a> {
         } else {
           // Width can't be negative. Adjust the right margin instead
           width = css::Value::Length(0.0, css::Unit::Px);
-          margin_right = css::Value::Length(margin_right.to_px() + underflow, css::Unit::Px);
+          margin_right = css::Value::Length(px(&margin_right) + underflow, css::Unit::Px);
         }
       }
 
@@ -101,40 +108,42 @@ impl<'a> LayoutBox<'a> {
       }
     }
 
-    self.dimensions.content.width = width.to_px();
+    self.dimensions.content.width = px(&width);
 
-    self.dimensions.padding.left = padding_left.to_px();
-    self.dimensions.padding.right = padding_right.to_px();
+    self.dimensions.padding.left = px(&padding_left);
+    self.dimensions.padding.right = px(&padding_right);
 
-    self.dimensions.border.left = border_left.to_px();
-    self.dimensions.border.right = border_right.to_px();
+    self.dimensions.border.left = px(&border_left);
+    self.dimensions.border.right = px(&border_right);
 
-    self.dimensions.margin.left = margin_left.to_px();
-    self.dimensions.margin.right = margin_right.to_px();
+    self.dimensions.margin.left = px(&margin_left);
+    self.dimensions.margin.right = px(&margin_right);
   }
 
   // Finish calculating the block's edge sizes, and position it within its containing block
   // http://www.w3.org/TR/CSS2/visudet.html#normal-block
-  // Sets the vertical margin/padding/border dimensions, and the "x", "y" values
-  pub(super) fn calculate_block_position(&mut self, containing_block: Dimensions) {
+  // Sets the vertical margin/padding/border dimensions, and the "x", "y" values. Percentages
+  // of vertical margins and padding are of the width of the containing block too
+  pub(super) fn calculate_block_position(
+    &mut self,
+    containing_block: Dimensions,
+    viewport: Rectangle,
+  ) {
     let style: &StyledNode = self.get_style_node();
+    let px = |value: &css::Value| -> f32 { to_px(value, containing_block.content.width, viewport) };
 
     // margin, border, and padding have initial value 0
     let zero: css::Value = css::Value::Length(0.0, css::Unit::Px);
 
     // If margin-top or margin-bottom is "auto", the used value is zero
-    self.dimensions.margin.top = style.lookup("margin-top", "margin", &zero).to_px();
-    self.dimensions.margin.bottom = style.lookup("margin-bottom", "margin", &zero).to_px();
+    self.dimensions.margin.top = px(&style.value_or("margin-top", &zero));
+    self.dimensions.margin.bottom = px(&style.value_or("margin-bottom", &zero));
 
-    self.dimensions.border.top = style
-      .lookup("border-top-width", "border-width", &zero)
-      .to_px();
-    self.dimensions.border.bottom = style
-      .lookup("border-bottom-width", "border-width", &zero)
-      .to_px();
+    self.dimensions.border.top = px(&style.value_or("border-top-width", &zero));
+    self.dimensions.border.bottom = px(&style.value_or("border-bottom-width", &zero));
 
-    self.dimensions.padding.top = style.lookup("padding-top", "padding", &zero).to_px();
-    self.dimensions.padding.bottom = style.lookup("padding-bottom", "padding", &zero).to_px();
+    self.dimensions.padding.top = px(&style.value_or("padding-top", &zero));
+    self.dimensions.padding.bottom = px(&style.value_or("padding-bottom", &zero));
 
     self.dimensions.content.x = containing_block.content.x
       + self.dimensions.margin.left
@@ -151,68 +160,70 @@ impl<'a> LayoutBox<'a> {
 
   // Lay out the block's children within its content area
   // Sets "self.dimensions.height" to the total content height
-  pub(super) fn layout_block_children(&mut self) {
+  pub(super) fn layout_block_children(&mut self, viewport: Rectangle) {
     for child in &mut self.children {
-      child.layout(self.dimensions);
+      child.layout(self.dimensions, viewport);
       // Increment the height so each child is laid out below the previous one
-      self.dimensions.content.height =
-        self.dimensions.content.height + child.dimensions.margin_box().height();
+      self.dimensions.content.height += child.dimensions.margin_box().height();
     }
   }
 
   // Height of a block-level non-replaced element in normal flow with overflow visible
-  pub(super) fn calculate_block_height(&mut self) {
+  pub(super) fn calculate_block_height(&mut self, viewport: Rectangle) {
     // If the height is set to an explicit length, use that exact length
-    // Otherwise, just keep the value set by "layout_block_children"
-    if let Some(css::Value::Length(height, css::Unit::Px)) = self.get_style_node().value("height") {
-      self.dimensions.content.height = height;
+    // Otherwise, just keep the value set by "layout_block_children". The height of the
+    // containing block depends on its content, so a percentage height behaves as 'auto'
+    if let Some(height @ css::Value::Length(..)) = self.get_style_node().value("height") {
+      self.dimensions.content.height = to_px(&height, 0.0, viewport);
     }
   }
 
   // Lay out an inline-block element: reads box model properties directly from CSS
   // (no auto margin filling), lays out children vertically, then computes height.
   // x/y are not set here — the parent anonymous block positions the box horizontally.
-  pub(super) fn layout_inline_block(&mut self, _containing_block: Dimensions) {
+  pub(super) fn layout_inline_block(&mut self, containing_block: Dimensions, viewport: Rectangle) {
     let style: &StyledNode = self.get_style_node();
     let zero: css::Value = css::Value::Length(0.0, css::Unit::Px);
+    let px = |value: css::Value| -> f32 { to_px(&value, containing_block.content.width, viewport) };
 
-    self.dimensions.padding.left = style.lookup("padding-left", "padding", &zero).to_px();
-    self.dimensions.padding.right = style.lookup("padding-right", "padding", &zero).to_px();
-    self.dimensions.padding.top = style.lookup("padding-top", "padding", &zero).to_px();
-    self.dimensions.padding.bottom = style.lookup("padding-bottom", "padding", &zero).to_px();
+    self.dimensions.padding.left = px(style.value_or("padding-left", &zero));
+    self.dimensions.padding.right = px(style.value_or("padding-right", &zero));
+    self.dimensions.padding.top = px(style.value_or("padding-top", &zero));
+    self.dimensions.padding.bottom = px(style.value_or("padding-bottom", &zero));
 
-    self.dimensions.border.left = style.lookup("border-left-width", "border-width", &zero).to_px();
-    self.dimensions.border.right = style.lookup("border-right-width", "border-width", &zero).to_px();
-    self.dimensions.border.top = style.lookup("border-top-width", "border-width", &zero).to_px();
-    self.dimensions.border.bottom = style.lookup("border-bottom-width", "border-width", &zero).to_px();
+    self.dimensions.border.left = px(style.value_or("border-left-width", &zero));
+    self.dimensions.border.right = px(style.value_or("border-right-width", &zero));
+    self.dimensions.border.top = px(style.value_or("border-top-width", &zero));
+    self.dimensions.border.bottom = px(style.value_or("border-bottom-width", &zero));
 
-    self.dimensions.margin.left = style.lookup("margin-left", "margin", &zero).to_px();
-    self.dimensions.margin.right = style.lookup("margin-right", "margin", &zero).to_px();
-    self.dimensions.margin.top = style.lookup("margin-top", "margin", &zero).to_px();
-    self.dimensions.margin.bottom = style.lookup("margin-bottom", "margin", &zero).to_px();
+    self.dimensions.margin.left = px(style.value_or("margin-left", &zero));
+    self.dimensions.margin.right = px(style.value_or("margin-right", &zero));
+    self.dimensions.margin.top = px(style.value_or("margin-top", &zero));
+    self.dimensions.margin.bottom = px(style.value_or("margin-bottom", &zero));
 
     // Use explicit CSS width; no auto-fill since the box sits in an inline flow
-    self.dimensions.content.width = style.value("width").map(|v: css::Value| v.to_px()).unwrap_or(0.0);
+    self.dimensions.content.width = style.value("width").map(px).unwrap_or(0.0);
 
-    self.layout_block_children();
-    self.calculate_block_height();
+    self.layout_block_children(viewport);
+    self.calculate_block_height(viewport);
   }
 
-  // Lay out a block-level element and its descendants
-  pub fn layout_block(&mut self, containing_block: Dimensions) {
+  // Lay out a block-level element and its descendants. 'viewport' is the size of the viewport,
+  // for viewport units
+  pub fn layout_block(&mut self, containing_block: Dimensions, viewport: Rectangle) {
     // Child width can depend on parent width, so we need to calculate this box's width before
     // laying out its children.
-    self.calculate_block_width(containing_block);
+    self.calculate_block_width(containing_block, viewport);
 
     // Determine where the box is located within its container
-    self.calculate_block_position(containing_block);
+    self.calculate_block_position(containing_block, viewport);
 
     // Recursively lay out the children of this box
-    self.layout_block_children();
+    self.layout_block_children(viewport);
 
     // Parent height can depend on child height, so "calculate_height" must be called after the
     // children are laid out
-    self.calculate_block_height();
+    self.calculate_block_height(viewport);
   }
 }
 
@@ -2,6 +2,8 @@ use crate::css;
 use crate::dom;
 use crate::hashmap;
 use crate::layout::*;
+use crate::layout::tree::build_layout_tree;
+use crate::parser::css::CSSParser;
 use crate::style;
 
 // Test the method calculate_block_width of the LayoutBox struct implementation
@@ -63,15 +65,12 @@ fn test_calculate_block_width() {
     ],
   );
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -86,7 +85,7 @@ fn test_calculate_block_width() {
   let containing_block: Dimensions = Dimensions::new(content, padding, border, margin);
 
   // Assert that the calculate_block_width method correctly calculates the width of the layout box given the containing block
-  layout_box.calculate_block_width(containing_block);
+  layout_box.calculate_block_width(containing_block, containing_block.content);
 
   // Assert that the resulting content width is as expected
   assert_eq!(layout_box.dimensions().content().width(), 50.0);
@@ -109,6 +108,78 @@ fn test_calculate_block_width() {
   assert_eq!(layout_box.dimensions().margin().right(), 28.0);
 }
 
+// Test the methods calculate_block_width and calculate_block_height with relative lengths
+#[test]
+fn test_calculate_block_width_relative_units() {
+  let node: dom::Node = dom::Node::element(String::from("div"), hashmap![], vec![]);
+  let length = |name: &str, length: f32, unit: css::Unit| -> (String, css::Value) {
+    (name.to_string(), css::Value::Length(length, unit))
+  };
+  let percentage = |name: &str, percentage: f32| -> (String, css::Value) {
+    (name.to_string(), css::Value::Percentage(percentage))
+  };
+  let values: style::PropertyMap = [
+    percentage("width", 50.0),
+    percentage("padding-left", 10.0),
+    length("padding-right", 3.0, css::Unit::Pt),
+    length("margin-left", 5.0, css::Unit::Vw),
+    length("height", 10.0, css::Unit::Vh),
+  ]
+  .into_iter()
+  .collect();
+  let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
+  let mut layout_box: LayoutBox = LayoutBox::new(BoxType::BlockNode(&style_node));
+  let content: Rectangle = Rectangle::new(0.0, 0.0, 200.0, 0.0);
+  let edges: EdgeSizes = EdgeSizes::new(0.0, 0.0, 0.0, 0.0);
+  let containing_block: Dimensions = Dimensions::new(content, edges, edges, edges);
+  let viewport: Rectangle = Rectangle::new(0.0, 0.0, 400.0, 300.0);
+
+  layout_box.calculate_block_width(containing_block, viewport);
+  layout_box.calculate_block_height(viewport);
+
+  // Assert that percentages are of the width of the containing block, viewport units of the
+  // viewport, and points are 4/3 px
+  assert_eq!(layout_box.dimensions().content().width(), 100.0);
+  assert_eq!(layout_box.dimensions().padding().left(), 20.0);
+  assert_eq!(layout_box.dimensions().padding().right(), 4.0);
+  assert_eq!(layout_box.dimensions().margin().left(), 20.0);
+  // Assert that the right margin takes the rest of the width of the containing block
+  assert_eq!(layout_box.dimensions().margin().right(), 56.0);
+  assert_eq!(layout_box.dimensions().content().height(), 30.0);
+}
+
+// Test the method calculate_block_height with a zero height written without a unit
+#[test]
+fn test_calculate_block_height_unitless_zero() {
+  // <div class='parent'><div class='child'></div></div>
+  let child: dom::Node = dom::Node::element(
+    String::from("div"),
+    hashmap![String::from("class") => String::from("child")],
+    vec![],
+  );
+  let parent: dom::Node = dom::Node::element(
+    String::from("div"),
+    hashmap![String::from("class") => String::from("parent")],
+    vec![child],
+  );
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    ".parent { display: block; height: 0 } .child { display: block; height: 40px }".to_string(),
+  )
+  .unwrap()];
+  let parent_style: style::StyledNode =
+    style::style_tree(&parent, &stylesheets, dom::QuirksMode::NoQuirks, &style::StateMap::new());
+  let mut layout_box: LayoutBox = build_layout_tree(&parent_style);
+  let content: Rectangle = Rectangle::new(0.0, 0.0, 200.0, 0.0);
+  let edges: EdgeSizes = EdgeSizes::new(0.0, 0.0, 0.0, 0.0);
+  let containing_block: Dimensions = Dimensions::new(content, edges, edges, edges);
+
+  layout_box.layout_block(containing_block, containing_block.content);
+
+  // Assert that 'height: 0' is a zero length, not 'auto', so the box doesn't grow to fit its
+  // 40px child
+  assert_eq!(layout_box.dimensions().content().height(), 0.0);
+}
+
 // Test the method calculate_block_position of the LayoutBox struct implementation
 #[test]
 fn test_calculate_block_position() {
@@ -183,15 +254,12 @@ fn test_calculate_block_position() {
     ],
   );
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -205,9 +273,9 @@ fn test_calculate_block_position() {
 
   let containing_block: Dimensions = Dimensions::new(content, padding, border, margin);
 
-  layout_box.calculate_block_width(containing_block);
+  layout_box.calculate_block_width(containing_block, containing_block.content);
   // Assert that the calculate_block_position method correctly calculates the position of the layout box given the containing block
-  layout_box.calculate_block_position(containing_block);
+  layout_box.calculate_block_position(containing_block, containing_block.content);
 
   // Assert that the resulting content x position is as expected
   assert_eq!(layout_box.dimensions().content().x(), 16.0);
@@ -326,22 +394,16 @@ fn test_layout_block_children() {
     ],
   );
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule_1, rule_2]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule_1, rule_2])];
   // Values
   let mut values_2: style::PropertyMap = hashmap![];
   let mut values_1: style::PropertyMap = hashmap![];
 
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => {
-      values_2 = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => {
-      values_1 = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
   // Style nodes
   let style_node_3: style::StyledNode = style::StyledNode::new(&text_node, hashmap![], vec![]);
@@ -355,9 +417,10 @@ fn test_layout_block_children() {
   let text_box: LayoutBox = LayoutBox::new(BoxType::InlineNode(&style_node_3));
 
   child_box.add_child(text_box.clone());
-  child_box.calculate_block_width(*root_box.dimensions());
-  child_box.calculate_block_position(*root_box.dimensions());
-  child_box.calculate_block_height();
+  let viewport: Rectangle = root_box.dimensions().content;
+  child_box.calculate_block_width(*root_box.dimensions(), viewport);
+  child_box.calculate_block_position(*root_box.dimensions(), viewport);
+  child_box.calculate_block_height(viewport);
   root_box.add_child(child_box);
 
   // Containing block
@@ -368,10 +431,10 @@ fn test_layout_block_children() {
 
   let containing_block: Dimensions = Dimensions::new(content, padding, border, margin);
 
-  root_box.calculate_block_width(containing_block);
-  root_box.calculate_block_position(containing_block);
+  root_box.calculate_block_width(containing_block, containing_block.content);
+  root_box.calculate_block_position(containing_block, containing_block.content);
   // Assert that the layout_block_children method correctly calculates the height of the layout box by its children height
-  root_box.layout_block_children();
+  root_box.layout_block_children(containing_block.content);
 
   // Assert that the resulting content height is as expected
   assert_eq!(root_box.dimensions().content().height(), 82.0);
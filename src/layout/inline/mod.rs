@@ -1,37 +1,47 @@
 use crate::css;
 use crate::style::StyledNode;
-use super::{BoxType, Dimensions, LayoutBox};
+use super::{BoxType, Dimensions, LayoutBox, Rectangle};
+use super::types::to_px;
 
 impl<'a> LayoutBox<'a> {
   // Compute the dimensions of an inline-level element from its CSS properties.
   // Position (x, y) is not set here — the parent anonymous block is responsible for that.
-  pub(super) fn layout_inline(&mut self, _containing_block: Dimensions) {
+  pub(super) fn layout_inline(&mut self, containing_block: Dimensions, viewport: Rectangle) {
     let style: &StyledNode = self.get_style_node();
     let zero: css::Value = css::Value::Length(0.0, css::Unit::Px);
+    let px = |value: css::Value| -> f32 { to_px(&value, containing_block.content.width, viewport) };
 
-    self.dimensions.padding.left = style.lookup("padding-left", "padding", &zero).to_px();
-    self.dimensions.padding.right = style.lookup("padding-right", "padding", &zero).to_px();
-    self.dimensions.padding.top = style.lookup("padding-top", "padding", &zero).to_px();
-    self.dimensions.padding.bottom = style.lookup("padding-bottom", "padding", &zero).to_px();
+    self.dimensions.padding.left = px(style.value_or("padding-left", &zero));
+    self.dimensions.padding.right = px(style.value_or("padding-right", &zero));
+    self.dimensions.padding.top = px(style.value_or("padding-top", &zero));
+    self.dimensions.padding.bottom = px(style.value_or("padding-bottom", &zero));
 
-    self.dimensions.border.left = style.lookup("border-left-width", "border-width", &zero).to_px();
-    self.dimensions.border.right = style.lookup("border-right-width", "border-width", &zero).to_px();
-    self.dimensions.border.top = style.lookup("border-top-width", "border-width", &zero).to_px();
-    self.dimensions.border.bottom = style.lookup("border-bottom-width", "border-width", &zero).to_px();
+    self.dimensions.border.left = px(style.value_or("border-left-width", &zero));
+    self.dimensions.border.right = px(style.value_or("border-right-width", &zero));
+    self.dimensions.border.top = px(style.value_or("border-top-width", &zero));
+    self.dimensions.border.bottom = px(style.value_or("border-bottom-width", &zero));
 
-    self.dimensions.margin.left = style.lookup("margin-left", "margin", &zero).to_px();
-    self.dimensions.margin.right = style.lookup("margin-right", "margin", &zero).to_px();
-    self.dimensions.margin.top = style.lookup("margin-top", "margin", &zero).to_px();
-    self.dimensions.margin.bottom = style.lookup("margin-bottom", "margin", &zero).to_px();
+    self.dimensions.margin.left = px(style.value_or("margin-left", &zero));
+    self.dimensions.margin.right = px(style.value_or("margin-right", &zero));
+    self.dimensions.margin.top = px(style.value_or("margin-top", &zero));
+    self.dimensions.margin.bottom = px(style.value_or("margin-bottom", &zero));
 
-    // Use the explicit CSS width/height, or 0 if not specified (no text measurement yet)
-    self.dimensions.content.width = style.value("width").map(|v: css::Value| v.to_px()).unwrap_or(0.0);
-    self.dimensions.content.height = style.value("height").map(|v: css::Value| v.to_px()).unwrap_or(0.0);
+    // Use the explicit CSS width/height, or 0 if not specified (no text measurement yet). As for
+    // blocks, a percentage height behaves as 'auto'
+    self.dimensions.content.width = style.value("width").map(px).unwrap_or(0.0);
+    self.dimensions.content.height = match style.value("height") {
+      Some(css::Value::Percentage(_)) | None => 0.0,
+      Some(height) => px(height),
+    };
   }
 
   // Place inline children left-to-right inside an anonymous block, wrapping to the next
   // line when a child no longer fits within the container width.
-  pub(super) fn layout_anonymous_block(&mut self, containing_block: Dimensions) {
+  pub(super) fn layout_anonymous_block(
+    &mut self,
+    containing_block: Dimensions,
+    viewport: Rectangle,
+  ) {
     self.dimensions.content.x = containing_block.content.x;
     self.dimensions.content.y = containing_block.content.y + containing_block.content.height;
     self.dimensions.content.width = containing_block.content.width;
@@ -41,7 +51,7 @@ impl<'a> LayoutBox<'a> {
     let mut line_height: f32 = 0.0;
 
     for child in &mut self.children {
-      child.layout(self.dimensions);
+      child.layout(self.dimensions, viewport);
 
       let child_margin_width: f32 = child.dimensions.margin_box().width();
       let child_margin_height: f32 = child.dimensions.margin_box().height();
@@ -17,7 +17,7 @@ fn test_layout_inline_block() {
     hashmap![String::from("class") => String::from("b")],
     vec![],
   );
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![
     css::Rule::new(
       vec![css::Selector::Simple(css::SimpleSelector::new(None, None, vec!["a".to_string()]))],
       vec![
@@ -34,16 +34,14 @@ fn test_layout_inline_block() {
         css::Declaration::new("height".to_string(), css::Value::Length(30.0, css::Unit::Px)),
       ],
     ),
-  ]);
+  ])];
   let mut values_1: style::PropertyMap = hashmap![];
   let mut values_2: style::PropertyMap = hashmap![];
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => values_1 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => values_2 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
   let style_node_1: style::StyledNode = style::StyledNode::new(&node_1, values_1, vec![]);
   let style_node_2: style::StyledNode = style::StyledNode::new(&node_2, values_2, vec![]);
@@ -60,7 +58,7 @@ fn test_layout_inline_block() {
     EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
   );
 
-  anon_box.layout_anonymous_block(containing_block);
+  anon_box.layout_anonymous_block(containing_block, containing_block.content);
 
   // Both children fit on one line: child 1 at x=0, child 2 at x=60
   assert_eq!(anon_box.children()[0].dimensions().content().x(), 0.0);
@@ -96,7 +94,7 @@ fn test_inline_block_descendant_positions() {
     hashmap![String::from("class") => String::from("b")],
     vec![inner_node.clone()],
   );
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![
     css::Rule::new(
       vec![css::Selector::Simple(css::SimpleSelector::new(None, None, vec!["a".to_string()]))],
       vec![
@@ -121,21 +119,18 @@ fn test_inline_block_descendant_positions() {
         css::Declaration::new("height".to_string(), css::Value::Length(10.0, css::Unit::Px)),
       ],
     ),
-  ]);
+  ])];
   let mut values_1: style::PropertyMap = hashmap![];
   let mut values_2: style::PropertyMap = hashmap![];
   let mut values_inner: style::PropertyMap = hashmap![];
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => values_1 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => values_2 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
-  match inner_node.node_type() {
-    dom::NodeType::Element(element) => values_inner = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = inner_node.node_type() {
+    values_inner = style::specified_values(&inner_node, &[], &stylesheets);
   }
   let style_inner: style::StyledNode = style::StyledNode::new(&inner_node, values_inner, vec![]);
   let style_node_1: style::StyledNode = style::StyledNode::new(&node_1, values_1, vec![]);
@@ -161,7 +156,7 @@ fn test_inline_block_descendant_positions() {
     EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
   );
 
-  anon_box.layout_anonymous_block(containing_block);
+  anon_box.layout_anonymous_block(containing_block, containing_block.content);
 
   // node_2 is positioned at x=60; its anonymous block child must reflect that offset
   let node_2_box = &anon_box.children()[1];
@@ -194,13 +189,10 @@ fn test_layout_inline() {
       css::Declaration::new("margin".to_string(), css::Value::Length(3.0, css::Unit::Px)),
     ],
   );
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule])];
   let mut values: style::PropertyMap = hashmap![];
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
   let mut layout_box: LayoutBox = LayoutBox::new(BoxType::InlineNode(&style_node));
@@ -211,7 +203,7 @@ fn test_layout_inline() {
     EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
   );
 
-  layout_box.layout_inline(containing_block);
+  layout_box.layout_inline(containing_block, containing_block.content);
 
   assert_eq!(layout_box.dimensions().content().width(), 80.0);
   assert_eq!(layout_box.dimensions().content().height(), 20.0);
@@ -223,6 +215,31 @@ fn test_layout_inline() {
   assert_eq!(layout_box.dimensions().margin().left(), 3.0);
 }
 
+// Test the method layout_inline with percentages
+#[test]
+fn test_layout_inline_percentages() {
+  let node: dom::Node = dom::Node::element(String::from("span"), hashmap![], vec![]);
+  let values: style::PropertyMap = hashmap![
+    String::from("width") => css::Value::Percentage(50.0),
+    String::from("height") => css::Value::Percentage(50.0)
+  ];
+  let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
+  let mut layout_box: LayoutBox = LayoutBox::new(BoxType::InlineNode(&style_node));
+  let containing_block: Dimensions = Dimensions::new(
+    Rectangle::new(0.0, 0.0, 200.0, 100.0),
+    EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
+    EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
+    EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
+  );
+
+  layout_box.layout_inline(containing_block, containing_block.content);
+
+  // Assert that a percentage width is of the width of the containing block
+  assert_eq!(layout_box.dimensions().content().width(), 100.0);
+  // Assert that a percentage height behaves as 'auto', not as a percentage of the width
+  assert_eq!(layout_box.dimensions().content().height(), 0.0);
+}
+
 // Test the method layout_anonymous_block of the LayoutBox struct implementation.
 // Two inline children fit side by side on a single line.
 #[test]
@@ -237,7 +254,7 @@ fn test_layout_anonymous_block_single_line() {
     hashmap![String::from("class") => String::from("b")],
     vec![],
   );
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![
     css::Rule::new(
       vec![css::Selector::Simple(css::SimpleSelector::new(None, None, vec!["a".to_string()]))],
       vec![
@@ -252,16 +269,14 @@ fn test_layout_anonymous_block_single_line() {
         css::Declaration::new("height".to_string(), css::Value::Length(20.0, css::Unit::Px)),
       ],
     ),
-  ]);
+  ])];
   let mut values_1: style::PropertyMap = hashmap![];
   let mut values_2: style::PropertyMap = hashmap![];
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => values_1 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => values_2 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
   let style_node_1: style::StyledNode = style::StyledNode::new(&node_1, values_1, vec![]);
   let style_node_2: style::StyledNode = style::StyledNode::new(&node_2, values_2, vec![]);
@@ -278,7 +293,7 @@ fn test_layout_anonymous_block_single_line() {
     EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
   );
 
-  anon_box.layout_anonymous_block(containing_block);
+  anon_box.layout_anonymous_block(containing_block, containing_block.content);
 
   // Both children fit on one line: child 1 at x=0, child 2 at x=50
   assert_eq!(anon_box.children()[0].dimensions().content().x(), 0.0);
@@ -302,7 +317,7 @@ fn test_layout_anonymous_block_wrapping() {
     hashmap![String::from("class") => String::from("b")],
     vec![],
   );
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![
     css::Rule::new(
       vec![css::Selector::Simple(css::SimpleSelector::new(None, None, vec!["a".to_string()]))],
       vec![
@@ -317,16 +332,14 @@ fn test_layout_anonymous_block_wrapping() {
         css::Declaration::new("height".to_string(), css::Value::Length(20.0, css::Unit::Px)),
       ],
     ),
-  ]);
+  ])];
   let mut values_1: style::PropertyMap = hashmap![];
   let mut values_2: style::PropertyMap = hashmap![];
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => values_1 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => values_2 = style::specified_values(&element, &stylesheet),
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
   let style_node_1: style::StyledNode = style::StyledNode::new(&node_1, values_1, vec![]);
   let style_node_2: style::StyledNode = style::StyledNode::new(&node_2, values_2, vec![]);
@@ -344,7 +357,7 @@ fn test_layout_anonymous_block_wrapping() {
     EdgeSizes::new(0.0, 0.0, 0.0, 0.0),
   );
 
-  anon_box.layout_anonymous_block(containing_block);
+  anon_box.layout_anonymous_block(containing_block, containing_block.content);
 
   // Child 1 stays on line 0
   assert_eq!(anon_box.children()[0].dimensions().content().x(), 0.0);
@@ -1,14 +1,15 @@
 use crate::style;
-use super::{BoxType, Dimensions, LayoutBox};
+use super::{BoxType, Dimensions, LayoutBox, Rectangle};
 
 impl<'a> LayoutBox<'a> {
-  // Lay out a box and its descendants
-  pub(super) fn layout(&mut self, containing_block: Dimensions) {
+  // Lay out a box and its descendants. 'viewport' is the size of the viewport, for viewport
+  // units
+  pub(super) fn layout(&mut self, containing_block: Dimensions, viewport: Rectangle) {
     match &self.box_type {
-      BoxType::BlockNode(_) => self.layout_block(containing_block),
-      BoxType::InlineNode(_) => self.layout_inline(containing_block),
-      BoxType::InlineBlockNode(_) => self.layout_inline_block(containing_block),
-      BoxType::AnonymousBlock => self.layout_anonymous_block(containing_block),
+      BoxType::BlockNode(_) => self.layout_block(containing_block, viewport),
+      BoxType::InlineNode(_) => self.layout_inline(containing_block, viewport),
+      BoxType::InlineBlockNode(_) => self.layout_inline_block(containing_block, viewport),
+      BoxType::AnonymousBlock => self.layout_anonymous_block(containing_block, viewport),
     }
   }
 }
@@ -34,20 +35,21 @@ pub(super) fn build_layout_tree<'a>(style_node: &'a style::StyledNode<'a>) -> La
       style::Display::None => {} // Skip nodes with "display: none;"
     }
   }
-  return root;
+  root
 }
 
-// Transform a style tree into a layout tree
+// Transform a style tree into a layout tree. The initial containing block is the viewport, whose
+// size viewport units are relative to
 pub fn layout_tree<'a>(
   node: &'a style::StyledNode<'a>,
   mut containing_block: Dimensions,
 ) -> LayoutBox<'a> {
+  let viewport: Rectangle = containing_block.content;
   // The layout algorithm expects the container height to start at 0
-  // TODO: Save the initial containing block height, for calculating percent heights
   containing_block.content.height = 0.0;
 
   let mut root_box: LayoutBox = build_layout_tree(node);
-  root_box.layout(containing_block);
+  root_box.layout(containing_block, viewport);
   root_box
 }
 
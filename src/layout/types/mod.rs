@@ -1,6 +1,19 @@
+use crate::css;
 use crate::style;
 use std::default::Default;
 
+// Resolve a length to px for layout: percentages are of 'percentage_base', which depends on the
+// property (the width of the containing block for widths, margins and padding), and viewport
+// units are of the size of the viewport
+pub(super) fn to_px(value: &css::Value, percentage_base: f32, viewport: Rectangle) -> f32 {
+  match *value {
+    css::Value::Percentage(percentage) => percentage * percentage_base / 100.0,
+    css::Value::Length(length, css::Unit::Vw) => length * viewport.width / 100.0,
+    css::Value::Length(length, css::Unit::Vh) => length * viewport.height / 100.0,
+    _ => value.to_px(),
+  }
+}
+
 #[derive(Copy, Clone, Default, Debug)]
 pub struct Rectangle {
   pub(super) x: f32,
@@ -39,22 +39,16 @@ fn test_get_inline_container() {
     hashmap![String::from("class") => String::from("container-1")];
   let node_1: dom::Node = dom::Node::element(tag_name_1, attributes_1, vec![node_2.clone()]);
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![])];
   // Values
   let mut values_2: style::PropertyMap = hashmap![];
   let mut values_1: style::PropertyMap = hashmap![];
 
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => {
-      values_2 = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = style::specified_values(&node_2, &[], &stylesheets);
   }
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => {
-      values_1 = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = style::specified_values(&node_1, &[], &stylesheets);
   }
   // Style nodes
   let style_node_3: style::StyledNode = style::StyledNode::new(&text_node, hashmap![], vec![]);
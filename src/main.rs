@@ -1,19 +1,22 @@
 use web_rendering_engine::css;
 use web_rendering_engine::parser::css::CSSParser;
 use web_rendering_engine::dom;
-use web_rendering_engine::parser::html::HTMLParser;
+use web_rendering_engine::parser::document::DocumentParser;
+use web_rendering_engine::parser::encoding;
 use web_rendering_engine::layout;
 use web_rendering_engine::painting;
 use web_rendering_engine::style;
 
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
 
-fn read_source(filename: &str) -> Result<String, String> {
-  let mut content: String = String::new();
+// Read the raw bytes of a file: they are decoded once their encoding is known
+fn read_source(filename: &str) -> Result<Vec<u8>, String> {
+  let mut content: Vec<u8> = Vec::new();
   File::open(filename)
     .map_err(|e| format!("Cannot open '{}': {}", filename, e))?
-    .read_to_string(&mut content)
+    .read_to_end(&mut content)
     .map_err(|e| format!("Cannot read '{}': {}", filename, e))?;
   Ok(content)
 }
@@ -22,9 +25,16 @@ fn run() -> Result<(), String> {
   // Parse command-line options:
   let mut options: getopts::Options = getopts::Options::new();
   options.optopt("h", "html", "HTML document", "FILENAME");
-  options.optopt("c", "css", "CSS stylesheet", "FILENAME");
+  options.optopt("c", "css", "Additional CSS stylesheet", "FILENAME");
+  options.optopt("e", "encoding", "Encoding of the HTML document", "LABEL");
   options.optopt("o", "output", "Output file", "FILENAME");
   options.optopt("f", "format", "Output file format", "png");
+  options.optmulti(
+    "s",
+    "state",
+    "Render the elements matching SELECTORS in an interaction state, e.g. 'hover:a.menu'",
+    "STATE:SELECTORS",
+  );
 
   let matches: getopts::Matches = options
     .parse(std::env::args().skip(1))
@@ -39,8 +49,8 @@ fn run() -> Result<(), String> {
   };
 
   // Read input files:
-  let html: String = read_source(&str_arg("h", "examples/test.html"))?;
-  let css: String = read_source(&str_arg("c", "examples/test.css"))?;
+  let html_filename: String = str_arg("h", "examples/test.html");
+  let html: Vec<u8> = read_source(&html_filename)?;
 
   // Since we don't have an actual window, hard-code the "viewport" size.
   let mut viewport: layout::Dimensions = Default::default();
@@ -48,12 +58,39 @@ fn run() -> Result<(), String> {
   viewport.set_content().set_height(800.0);
 
   // Parsing and rendering:
-  let root_node: dom::Node = HTMLParser::parse(html)?;
-  let stylesheet: css::Stylesheet = CSSParser::parse(css)?;
-  let style_root: style::StyledNode = style::style_tree(&root_node, &stylesheet);
+  // Stylesheets linked from the document are resolved relative to the document itself
+  let base_directory: &Path = Path::new(&html_filename).parent().unwrap_or(Path::new(""));
+  let encoding_hint: Option<String> = matches.opt_str("e");
+  let mut document: dom::Document =
+    DocumentParser::parse_bytes(&html, encoding_hint.as_deref(), base_directory)?;
+  for diagnostic in document.diagnostics() {
+    eprintln!("Warning: {}:{}", html_filename, diagnostic);
+  }
+  // A stylesheet given on the command line comes after the document's own stylesheets
+  if let Some(css_filename) = matches.opt_str("c") {
+    let css: encoding::DecodedText =
+      encoding::decode_css(&read_source(&css_filename)?, None, Some(document.encoding()));
+    document.add_stylesheet(CSSParser::parse(css.into_text())?);
+  }
+  // Interaction states given on the command line, e.g. to render a link as hovered
+  let mut states: style::StateMap = style::StateMap::new();
+  for state in matches.opt_strs("s") {
+    let (name, selectors): (&str, &str) = state
+      .split_once(':')
+      .ok_or(format!("Invalid state '{}': expected STATE:SELECTORS", state))?;
+    let element_state: style::ElementState = style::ElementState::from_name(name)
+      .ok_or(format!("Unknown element state: '{}'", name))?;
+    states.add_selector_state(selectors, element_state)?;
+  }
+  let style_root: style::StyledNode = style::style_tree(
+    document.root(),
+    document.stylesheets(),
+    document.quirks_mode(),
+    &states,
+  );
   let layout_root: layout::LayoutBox = layout::layout_tree(&style_root, viewport);
 
-  dom::Node::print_node_tree(&root_node, 0);
+  dom::Node::print_node_tree(document.root(), 0);
   // style::StyledNode::print_style_node_tree(&style_root, 0);
 
   // Create the output file:
@@ -4,16 +4,6 @@ use crate::css;
 use crate::layout;
 use super::display_list::{build_display_list, DisplayCommand};
 
-trait Clamp {
-  fn clamp(self, lower: Self, upper: Self) -> Self;
-}
-
-impl Clamp for f32 {
-  fn clamp(self, lower: f32, upper: f32) -> f32 {
-    self.max(lower).min(upper)
-  }
-}
-
 pub struct Canvas {
   pixels: Vec<css::Color>,
   width: usize,
@@ -26,12 +16,12 @@ impl Canvas {
   // Create a blank canvas
   pub(super) fn new(width: usize, height: usize) -> Canvas {
     let white: css::Color = css::Color::new(255, 255, 255, 255);
-    return Canvas {
+    Canvas {
       pixels: vec![white; width * height],
       width,
       height,
       font: None,
-    };
+    }
   }
 
   pub fn pixels(&self) -> &Vec<css::Color> {
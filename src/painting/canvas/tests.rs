@@ -43,15 +43,12 @@ fn test_paint_item() {
     ],
   );
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -65,7 +62,7 @@ fn test_paint_item() {
   // LayoutBox
   let mut layout_box: layout::LayoutBox =
     layout::LayoutBox::new(layout::BoxType::BlockNode(&style_node));
-  layout_box.layout_block(containing_block);
+  layout_box.layout_block(containing_block, *containing_block.content());
   // Display list
   let mut display_list: DisplayList = vec![];
   render_background(&mut display_list, &layout_box);
@@ -92,7 +89,7 @@ fn test_paint_item() {
   }
 
   assert_eq!(layout_box_pixels_count, 2500);
-  assert_eq!(layout_box_pixels_right_position, true);
+  assert!(layout_box_pixels_right_position);
 }
 
 // Test that painting a semi-transparent color over a white background alpha-blends correctly.
@@ -1,5 +1,4 @@
 use crate::css;
-use crate::dom;
 use crate::layout;
 
 pub(super) type DisplayList = Vec<DisplayCommand>;
@@ -39,87 +38,87 @@ pub(super) fn get_color(layout_box: &layout::LayoutBox, name: &str) -> Option<cs
 }
 
 pub(super) fn render_background(list: &mut DisplayList, layout_box: &layout::LayoutBox) {
-  get_color(layout_box, "background").map(|color: css::Color| {
+  if let Some(color) = get_color(layout_box, "background-color") {
     list.push(DisplayCommand::SolidColor(
       color,
       layout_box.dimensions().border_box(),
-    ))
-  });
+    ));
+  }
 }
 
 pub(super) fn render_borders(list: &mut DisplayList, layout_box: &layout::LayoutBox) {
-  let color: css::Color = match get_color(layout_box, "border-color") {
-    Some(color) => color,
-    _ => return, // fail out if no border-color is specified
-  };
-
   let dimensions: &layout::Dimensions = layout_box.dimensions();
   let border_box: layout::Rectangle = dimensions.border_box();
 
-  // Top border
-  list.push(DisplayCommand::SolidColor(
-    color,
-    layout::Rectangle::new(
-      border_box.x(),
-      border_box.y(),
-      border_box.width(),
-      dimensions.border().top(),
-    ),
-  ));
+  // Top border, skipped like the other sides if no color is specified for it
+  if let Some(color) = get_color(layout_box, "border-top-color") {
+    list.push(DisplayCommand::SolidColor(
+      color,
+      layout::Rectangle::new(
+        border_box.x(),
+        border_box.y(),
+        border_box.width(),
+        dimensions.border().top(),
+      ),
+    ));
+  }
 
   // Right border
-  list.push(DisplayCommand::SolidColor(
-    color,
-    layout::Rectangle::new(
-      border_box.x() + border_box.width() - dimensions.border().right(),
-      border_box.y(),
-      dimensions.border().right(),
-      border_box.height(),
-    ),
-  ));
+  if let Some(color) = get_color(layout_box, "border-right-color") {
+    list.push(DisplayCommand::SolidColor(
+      color,
+      layout::Rectangle::new(
+        border_box.x() + border_box.width() - dimensions.border().right(),
+        border_box.y(),
+        dimensions.border().right(),
+        border_box.height(),
+      ),
+    ));
+  }
 
   // Bottom border
-  list.push(DisplayCommand::SolidColor(
-    color,
-    layout::Rectangle::new(
-      border_box.x(),
-      border_box.y() + border_box.height() - dimensions.border().bottom(),
-      border_box.width(),
-      dimensions.border().bottom(),
-    ),
-  ));
+  if let Some(color) = get_color(layout_box, "border-bottom-color") {
+    list.push(DisplayCommand::SolidColor(
+      color,
+      layout::Rectangle::new(
+        border_box.x(),
+        border_box.y() + border_box.height() - dimensions.border().bottom(),
+        border_box.width(),
+        dimensions.border().bottom(),
+      ),
+    ));
+  }
 
   // Left border
-  list.push(DisplayCommand::SolidColor(
-    color,
-    layout::Rectangle::new(
-      border_box.x(),
-      border_box.y(),
-      dimensions.border().left(),
-      border_box.height(),
-    ),
-  ));
+  if let Some(color) = get_color(layout_box, "border-left-color") {
+    list.push(DisplayCommand::SolidColor(
+      color,
+      layout::Rectangle::new(
+        border_box.x(),
+        border_box.y(),
+        dimensions.border().left(),
+        border_box.height(),
+      ),
+    ));
+  }
 }
 
 // If this layout box is an inline node wrapping a DOM text node, add a DrawText command.
 pub(super) fn render_text(list: &mut DisplayList, layout_box: &layout::LayoutBox) {
   if let layout::BoxType::InlineNode(style) = layout_box.box_type() {
-    if let dom::NodeType::Text(text) = style.node().node_type() {
-      // Skip whitespace-only text nodes produced by HTML indentation
+    if let Some(text) = style.text() {
+      // Skip whitespace-only text, such as the spaces between words
       if text.trim().is_empty() {
         return;
       }
-      // Text nodes have no specified values (inheritance is not yet implemented),
-      // so default to black text at 16px.
+      // Text nodes have no specified values (inheritance is not yet implemented), so default
+      // to black text, at the font size computed for the text from its ancestors
       let color: css::Color = css::Color::new(0, 0, 0, 255);
-      let font_size: f32 = style
-        .value("font-size")
-        .map(|v: css::Value| v.to_px())
-        .unwrap_or(16.0);
+      let font_size: f32 = style.font_size();
       list.push(DisplayCommand::DrawText(
         color,
         *layout_box.dimensions().content(),
-        text.clone(),
+        text.to_string(),
         font_size,
       ));
     }
@@ -139,7 +138,7 @@ fn render_layout_box(list: &mut DisplayList, layout_box: &layout::LayoutBox) {
 pub(super) fn build_display_list(layout_root: &layout::LayoutBox) -> DisplayList {
   let mut list: Vec<DisplayCommand> = Vec::new();
   render_layout_box(&mut list, layout_root);
-  return list;
+  list
 }
 
 #[cfg(test)]
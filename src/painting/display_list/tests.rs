@@ -24,15 +24,12 @@ fn test_get_color() {
   // Rule
   let rule: css::Rule = css::Rule::new(vec![selector], vec![background_declaration]);
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -41,7 +38,7 @@ fn test_get_color() {
     layout::LayoutBox::new(layout::BoxType::BlockNode(&style_node));
 
   assert_eq!(
-    get_color(&layout_box, "background"),
+    get_color(&layout_box, "background-color"),
     Some(css::Color::new(255, 0, 0, 255))
   );
 }
@@ -65,15 +62,12 @@ fn test_render_background() {
   // Rule
   let rule: css::Rule = css::Rule::new(vec![selector], vec![background_declaration]);
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -140,15 +134,12 @@ fn test_render_borders() {
     ],
   );
   // Stylesheet
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
   // Value
   let mut values: style::PropertyMap = hashmap![];
 
-  match node.node_type() {
-    dom::NodeType::Element(element) => {
-      values = style::specified_values(&element, &stylesheet);
-    }
-    _ => {}
+  if let dom::NodeType::Element(_) = node.node_type() {
+    values = style::specified_values(&node, &[], &stylesheets);
   }
   // StyleNode
   let style_node: style::StyledNode = style::StyledNode::new(&node, values, vec![]);
@@ -250,3 +241,32 @@ fn test_render_text_skips_whitespace() {
 
   assert_eq!(display_list.len(), 0);
 }
+
+// Test that render_text adds a DrawText command for the text generated for a pseudo-element.
+#[test]
+fn test_render_text_pseudo_element() {
+  let node: dom::Node = dom::Node::element("p".to_string(), hashmap![], vec![]);
+  let style_node: style::StyledNode = style::StyledNode::new_pseudo_element(
+    &node,
+    css::PseudoElement::Before,
+    hashmap![],
+    "Note: ".to_string(),
+  );
+  let layout_box: layout::LayoutBox =
+    layout::LayoutBox::new(layout::BoxType::InlineNode(&style_node.children()[0]));
+  let mut display_list: DisplayList = vec![];
+
+  render_text(&mut display_list, &layout_box);
+
+  // Assert that the pseudo-element itself has no text, only its child
+  assert_eq!(style_node.text(), None);
+  assert_eq!(
+    display_list,
+    vec![DisplayCommand::DrawText(
+      css::Color::new(0, 0, 0, 255),
+      *layout_box.dimensions().content(),
+      "Note: ".to_string(),
+      16.0,
+    )]
+  );
+}
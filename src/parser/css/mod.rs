@@ -1,201 +1,921 @@
 /**
  * Features to add:
- * - Extend CSS parser to support more values, or one or more selector combinators;
- * - Extend the CSS parser to discard any declaration that contains a parse error, and follow
- *   the error handling rules to resume parsing after the end of the declaration;
- * - Make the HTML parser pass the contents of any <style> nodes to the CSS parser, and
- *   return a Document object that includes a list of Stylesheets in addition to the DOM tree;
+ * - Extend CSS parser to support more values;
  */
+use std::collections::HashMap;
+
 use crate::css;
-use crate::parser::text::TextParser;
+use syntax::ComponentValue;
+use tokenizer::{HashType, Token};
 
+mod syntax;
 #[cfg(test)]
 mod tests;
+mod tokenizer;
 
-fn valid_identifier_char(c: char) -> bool {
-  match c {
-    'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => true, // TODO: Include U+00A0 and higher
-    _ => false,
-  }
+// A component of a color function: a number, which angles are converted to in degrees, or a
+// percentage
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum ColorComponent {
+  Number(f32),
+  Percentage(f32),
 }
 
+// Parser of the CSS grammar: selectors, values and rules, over the component values that the
+// syntax module parses a stylesheet into
 pub struct CSSParser {
-  text_parser: TextParser,
+  // The component values being parsed, e.g. the prelude of a rule or the value of a declaration,
+  // and the index of the next one
+  input: Vec<ComponentValue>,
+  position: usize,
+  // Namespace URLs declared by @namespace rules, by prefix, and the default one if declared
+  namespaces: HashMap<String, String>,
+  default_namespace: Option<String>,
 }
 
 impl CSSParser {
-  pub fn new(position: usize, input: String) -> Self {
+  pub fn new(source: String) -> Self {
+    Self {
+      input: syntax::parse_component_values(&source),
+      position: 0,
+      namespaces: HashMap::new(),
+      default_namespace: None,
+    }
+  }
+
+  // Create a parser for component values nested in the input, e.g. the arguments of a function,
+  // with the namespaces declared so far
+  fn nested(&self, input: &[ComponentValue]) -> CSSParser {
     Self {
-      text_parser: TextParser::new(position, input),
+      input: input.to_vec(),
+      position: 0,
+      namespaces: self.namespaces.clone(),
+      default_namespace: self.default_namespace.clone(),
+    }
+  }
+
+  fn next(&self) -> Option<&ComponentValue> {
+    self.input.get(self.position)
+  }
+
+  // Return the next component value if it is a token
+  fn next_token(&self) -> Option<&Token> {
+    match self.next() {
+      Some(ComponentValue::Token(token)) => Some(token),
+      _ => None,
+    }
+  }
+
+  fn next_is(&self, token: &Token) -> bool {
+    self.next_token() == Some(token)
+  }
+
+  fn consume(&mut self) -> Option<ComponentValue> {
+    let value: Option<ComponentValue> = self.next().cloned();
+    if value.is_some() {
+      self.position += 1;
+    }
+    value
+  }
+
+  // Consume whitespace tokens, and return whether there were any
+  fn consume_whitespace(&mut self) -> bool {
+    let start: usize = self.position;
+    while self.next_is(&Token::Whitespace) {
+      self.position += 1;
+    }
+    self.position > start
+  }
+
+  fn expect(&mut self, token: &Token) -> Result<(), String> {
+    match self.consume() {
+      Some(value) if value.is(token) => Ok(()),
+      Some(value) => Err(format!("Expected '{}' but found '{}'", token, value)),
+      None => Err(format!("Expected '{}' but reached end of input", token)),
+    }
+  }
+
+  // Check that nothing but whitespace is left in the input
+  fn expect_end(&mut self) -> Result<(), String> {
+    self.consume_whitespace();
+    match self.next() {
+      Some(value) => Err(format!("Unexpected '{}'", value)),
+      None => Ok(()),
     }
   }
 
+  fn eof(&self) -> bool {
+    self.position >= self.input.len()
+  }
+
   // Parse a property name or keyword
-  fn parse_identifier(&mut self) -> String {
-    self.text_parser.consume_while(valid_identifier_char)
+  fn parse_identifier(&mut self) -> Result<String, String> {
+    match self.consume() {
+      Some(ComponentValue::Token(Token::Ident(identifier))) => Ok(identifier),
+      Some(value) => Err(format!("Expected an identifier but found '{}'", value)),
+      None => Err("Expected an identifier but reached end of input".to_string()),
+    }
   }
 
-  fn parse_unit(&mut self) -> Result<css::Unit, String> {
-    match &*self.parse_identifier().to_ascii_lowercase() {
+  fn parse_unit(unit: &str) -> Result<css::Unit, String> {
+    match &*unit.to_ascii_lowercase() {
       "px" => Ok(css::Unit::Px),
+      "pt" => Ok(css::Unit::Pt),
+      "cm" => Ok(css::Unit::Cm),
+      "in" => Ok(css::Unit::In),
+      "em" => Ok(css::Unit::Em),
+      "rem" => Ok(css::Unit::Rem),
+      "vw" => Ok(css::Unit::Vw),
+      "vh" => Ok(css::Unit::Vh),
       unit => Err(format!("Unrecognized unit: '{}'", unit)),
     }
   }
 
-  fn parse_float(&mut self) -> Result<f32, String> {
-    let s: String = self.text_parser.consume_while(|c: char| match c {
-      '0'..='9' | '.' => true,
-      _ => false,
-    });
-    s.parse::<f32>().map_err(|_| format!("Invalid float value: '{}'", s))
+  // Parse a number, a percentage or a length, e.g. '1.4', '50%' or '-10px'. A zero length may be
+  // written without a unit, and is parsed as a number
+  fn parse_numeric(&mut self) -> Result<css::Value, String> {
+    match self.consume() {
+      Some(ComponentValue::Token(Token::Number(number))) => Ok(css::Value::Number(number.value())),
+      Some(ComponentValue::Token(Token::Percentage(number))) => {
+        Ok(css::Value::Percentage(number.value()))
+      }
+      Some(ComponentValue::Token(Token::Dimension(number, unit))) => {
+        Ok(css::Value::Length(number.value(), Self::parse_unit(&unit)?))
+      }
+      Some(value) => Err(format!("Expected a number but found '{}'", value)),
+      None => Err("Expected a number but reached end of input".to_string()),
+    }
+  }
+
+  // Parse a hex color with 3, 4, 6 or 8 digits, e.g. '#0f0', '#0f08', '#00ff00' or '#00ff0080'.
+  // The short forms repeat each digit, and the forms with 4 and 8 digits end with the alpha
+  // https://drafts.csswg.org/css-color/#hex-notation
+  fn parse_color(&mut self) -> Result<css::Value, String> {
+    let hex: String = match self.consume() {
+      Some(ComponentValue::Token(Token::Hash(hex, _))) => hex,
+      Some(value) => return Err(format!("Expected a hex color but found '{}'", value)),
+      None => return Err("Expected a hex color but reached end of input".to_string()),
+    };
+    let digits: Vec<u8> = hex
+      .chars()
+      .map(|c: char| c.to_digit(16).map(|digit: u32| digit as u8))
+      .collect::<Option<Vec<u8>>>()
+      .ok_or_else(|| format!("Invalid hex color: '#{}'", hex))?;
+    let channels: Vec<u8> = match digits.len() {
+      3 | 4 => digits.iter().map(|digit: &u8| digit * 17).collect(),
+      6 | 8 => digits.chunks(2).map(|pair: &[u8]| pair[0] * 16 + pair[1]).collect(),
+      length => return Err(format!("Invalid hex color with {} digits", length)),
+    };
+    let alpha: u8 = channels.get(3).copied().unwrap_or(255);
+    Ok(css::Value::ColorValue(css::Color::new(channels[0], channels[1], channels[2], alpha)))
   }
 
-  fn parse_length(&mut self) -> Result<css::Value, String> {
-    Ok(css::Value::Length(self.parse_float()?, self.parse_unit()?))
+  // Parse a number, a percentage or an angle as a component of a color function. Angles are
+  // converted to numbers of degrees
+  fn parse_color_component(&mut self) -> Result<ColorComponent, String> {
+    let (number, unit): (f32, String) = match self.consume() {
+      Some(ComponentValue::Token(Token::Number(number))) => {
+        return Ok(ColorComponent::Number(number.value()));
+      }
+      Some(ComponentValue::Token(Token::Percentage(number))) => {
+        return Ok(ColorComponent::Percentage(number.value()));
+      }
+      Some(ComponentValue::Token(Token::Dimension(number, unit))) => (number.value(), unit),
+      Some(value) => return Err(format!("Unexpected '{}' in a color", value)),
+      None => return Err("Unexpected end of input while parsing a color".to_string()),
+    };
+    let degrees: f32 = match &*unit.to_ascii_lowercase() {
+      "deg" => number,
+      "rad" => number.to_degrees(),
+      "grad" => number * 0.9,
+      "turn" => number * 360.0,
+      unit => return Err(format!("Unrecognized unit in a color: '{}'", unit)),
+    };
+    Ok(ColorComponent::Number(degrees))
   }
 
-  // Parse two hexadecimal digits
-  fn parse_hex_pair(&mut self) -> Result<u8, String> {
-    let pos: usize = self.text_parser.position();
-    if pos + 2 > self.text_parser.input().len() {
-      return Err("Unexpected end of input while parsing hex value".to_string());
+  // Parse the arguments of a color function, 'rgb()', 'rgba()', 'hsl()', 'hsla()' or 'hwb()',
+  // in the legacy syntax with commas, e.g. 'rgba(255, 0, 0, 0.5)', or in the modern one with
+  // spaces and the alpha after a slash, e.g. 'rgb(255 0 0 / 50%)'
+  // https://drafts.csswg.org/css-color/#color-syntax
+  fn parse_color_function(&mut self, function: &str) -> Result<css::Color, String> {
+    self.consume_whitespace();
+    let mut components: Vec<ColorComponent> = vec![self.parse_color_component()?];
+    self.consume_whitespace();
+    let legacy: bool = self.next_is(&Token::Comma);
+    let mut alpha: Option<ColorComponent> = None;
+    if legacy {
+      while self.next_is(&Token::Comma) {
+        self.position += 1;
+        self.consume_whitespace();
+        components.push(self.parse_color_component()?);
+        self.consume_whitespace();
+      }
+      if components.len() == 4 {
+        alpha = components.pop();
+      }
+    } else {
+      while !self.eof() && !self.next_is(&Token::Delim('/')) {
+        components.push(self.parse_color_component()?);
+        self.consume_whitespace();
+      }
+      if self.next_is(&Token::Delim('/')) {
+        self.position += 1;
+        self.consume_whitespace();
+        alpha = Some(self.parse_color_component()?);
+      }
+    }
+    self.expect_end()?;
+
+    let [first, second, third]: [ColorComponent; 3] = components
+      .try_into()
+      .map_err(|_| format!("Expected three color components in '{}()'", function))?;
+    let alpha: f32 = match alpha {
+      Some(ColorComponent::Number(alpha)) => alpha,
+      Some(ColorComponent::Percentage(alpha)) => alpha / 100.0,
+      None => 1.0,
+    };
+    match function {
+      "rgb" | "rgba" => {
+        let percentages: usize = [first, second, third]
+          .iter()
+          .filter(|component: &&ColorComponent| matches!(component, ColorComponent::Percentage(_)))
+          .count();
+        // The legacy syntax doesn't mix numbers and percentages
+        if legacy && !matches!(percentages, 0 | 3) {
+          return Err(format!("Mixed numbers and percentages in '{}()'", function));
+        }
+        let channel = |component: ColorComponent| -> f32 {
+          match component {
+            ColorComponent::Number(value) => value / 255.0,
+            ColorComponent::Percentage(value) => value / 100.0,
+          }
+        };
+        Ok(css::Color::from_fractions(channel(first), channel(second), channel(third), alpha))
+      }
+      "hsl" | "hsla" | "hwb" => {
+        let hue: f32 = match first {
+          ColorComponent::Number(hue) => hue,
+          ColorComponent::Percentage(_) => {
+            return Err(format!("Invalid hue percentage in '{}()'", function));
+          }
+        };
+        // The legacy syntax only takes percentages, and 'hwb()' has no legacy syntax
+        let fraction = |component: ColorComponent| -> Result<f32, String> {
+          match component {
+            ColorComponent::Percentage(value) => Ok(value / 100.0),
+            ColorComponent::Number(value) if !legacy => Ok(value / 100.0),
+            ColorComponent::Number(_) => Err(format!("Expected a percentage in '{}()'", function)),
+          }
+        };
+        if function == "hwb" {
+          if legacy {
+            return Err("Unexpected ',' in 'hwb()'".to_string());
+          }
+          return Ok(css::Color::from_hwb(hue, fraction(second)?, fraction(third)?, alpha));
+        }
+        Ok(css::Color::from_hsl(hue, fraction(second)?, fraction(third)?, alpha))
+      }
+      _ => Err(format!("Unsupported color function: '{}()'", function)),
     }
-    let s: String = self.text_parser.input()[pos..pos + 2].to_string();
-    self.text_parser.increment_position(2);
-    u8::from_str_radix(&s, 16).map_err(|_| format!("Invalid hex value: '{}'", s))
   }
 
-  fn parse_color(&mut self) -> Result<css::Value, String> {
-    self.text_parser.expect_char('#')?;
-    Ok(css::Value::ColorValue(css::Color::new(
-      self.parse_hex_pair()?,
-      self.parse_hex_pair()?,
-      self.parse_hex_pair()?,
-      255,
-    )))
+  // Parse a value starting with an identifier: a named color, 'currentColor', which is resolved
+  // with the other computed values, or a keyword
+  fn parse_identifier_value(identifier: String) -> css::Value {
+    if identifier.eq_ignore_ascii_case("currentcolor") {
+      return css::Value::Keyword("currentcolor".to_string());
+    }
+    match css::Color::from_name(&identifier) {
+      Some(color) => css::Value::ColorValue(color),
+      None => css::Value::Keyword(identifier),
+    }
   }
 
   fn parse_value(&mut self) -> Result<css::Value, String> {
-    match self.text_parser.next_char() {
-      '0'..='9' => self.parse_length(),
-      '#' => self.parse_color(),
-      _ => Ok(css::Value::Keyword(self.parse_identifier())),
+    match self.next_token() {
+      Some(Token::Number(_) | Token::Percentage(_) | Token::Dimension(..)) => self.parse_numeric(),
+      Some(Token::Hash(..)) => self.parse_color(),
+      _ => match self.consume() {
+        Some(ComponentValue::Token(Token::Ident(identifier))) => {
+          Ok(Self::parse_identifier_value(identifier))
+        }
+        Some(ComponentValue::Token(Token::String(string))) => Ok(css::Value::String(string)),
+        Some(ComponentValue::Function(name, arguments)) => {
+          let function: String = name.to_ascii_lowercase();
+          Ok(css::Value::ColorValue(self.nested(&arguments).parse_color_function(&function)?))
+        }
+        Some(value) => Err(format!("Unexpected '{}' in a value", value)),
+        None => Err("Unexpected end of input while parsing a value".to_string()),
+      },
     }
   }
 
-  // Parse one '<property>: <value>;' declaration
-  fn parse_declaration(&mut self) -> Result<css::Declaration, String> {
-    let property_name: String = self.parse_identifier();
-    self.text_parser.consume_whitespace();
-    self.text_parser.expect_char(':')?;
-    self.text_parser.consume_whitespace();
-    let value: css::Value = self.parse_value()?;
-    self.text_parser.consume_whitespace();
-    self.text_parser.expect_char(';')?;
-
-    Ok(css::Declaration::new(property_name, value))
+  // Parse the value of a declaration: one component value, or a list of them separated by
+  // whitespace, '/' or ',', e.g. '0 auto' or 'bold 12px/30px Georgia, serif'
+  fn parse_values(&mut self) -> Result<css::Value, String> {
+    let mut values: Vec<css::Value> = vec![];
+    loop {
+      self.consume_whitespace();
+      let separator: Option<&str> = match self.next_token() {
+        Some(Token::Delim('/')) => Some("/"),
+        Some(Token::Comma) => Some(","),
+        _ => None,
+      };
+      match separator {
+        Some(separator) => {
+          values.push(css::Value::Keyword(separator.to_string()));
+          self.position += 1;
+        }
+        None if self.eof() => break,
+        None => values.push(self.parse_value()?),
+      }
+    }
+    match values.len() {
+      0 => Err("Expected a value".to_string()),
+      1 => Ok(values.remove(0)),
+      _ => Ok(css::Value::List(values)),
+    }
   }
 
-  // Parse a list of declarations enclosed in '{ ... }'
-  fn parse_declarations(&mut self) -> Result<Vec<css::Declaration>, String> {
-    self.text_parser.expect_char('{')?;
-    let mut declarations: Vec<css::Declaration> = Vec::new();
-    loop {
-      self.text_parser.consume_whitespace();
-      if self.text_parser.next_char() == '}' {
-        self.text_parser.consume_char();
-        break;
+  // Parse the value of 'content': 'none', 'normal', or a list of strings, 'attr()' and
+  // 'counter()', e.g. '"Chapter " counter(chapter) ": "'
+  // https://drafts.csswg.org/css-content/#content-property
+  fn parse_content(&mut self) -> Result<css::Value, String> {
+    if let Some(Token::Ident(keyword)) = self.next_token() {
+      let keyword: String = keyword.to_ascii_lowercase();
+      if keyword == "none" || keyword == "normal" {
+        self.position += 1;
+        return Ok(css::Value::Keyword(keyword));
       }
-      match self.parse_declaration() {
-        Ok(declaration) => declarations.push(declaration),
-        Err(_) => {
-          // Recovery: discard this declaration, skip to the next ';' or '}'
-          self.text_parser.consume_while(|c: char| c != ';' && c != '}');
-          if !self.text_parser.eof() && self.text_parser.next_char() == ';' {
-            self.text_parser.consume_char(); // consume ';'
+    }
+    let mut items: Vec<css::ContentItem> = vec![];
+    loop {
+      self.consume_whitespace();
+      let item: css::ContentItem = match self.consume() {
+        Some(ComponentValue::Token(Token::String(string))) => css::ContentItem::String(string),
+        Some(ComponentValue::Function(function, arguments)) => {
+          let mut arguments_parser: CSSParser = self.nested(&arguments);
+          arguments_parser.consume_whitespace();
+          let name: String = arguments_parser.parse_identifier()?;
+          arguments_parser.expect_end()?;
+          match &*function.to_ascii_lowercase() {
+            "attr" => css::ContentItem::Attr(name),
+            "counter" => css::ContentItem::Counter(name),
+            _ => return Err(format!("Unsupported content: '{}()'", function)),
           }
         }
+        Some(value) => return Err(format!("Unexpected '{}' in content", value)),
+        None if items.is_empty() => return Err("Expected a content value".to_string()),
+        None => break,
+      };
+      items.push(item);
+    }
+    Ok(css::Value::Content(items))
+  }
+
+  // Parse the value of 'counter-reset' or 'counter-increment': 'none', or counter names each
+  // followed by an optional integer, which defaults to 'default'
+  // https://drafts.csswg.org/css-lists/#counter-properties
+  fn parse_counters(&mut self, default: i32) -> Result<css::Value, String> {
+    let mut counters: Vec<(String, i32)> = vec![];
+    while !self.eof() {
+      let name: String = self.parse_identifier()?;
+      if name.eq_ignore_ascii_case("none") && counters.is_empty() {
+        self.expect_end()?;
+        return Ok(css::Value::Keyword("none".to_string()));
       }
+      self.consume_whitespace();
+      let value: i32 = match self.next_token() {
+        Some(Token::Number(number)) if number.is_integer() => {
+          let value: i32 = number.value() as i32;
+          self.position += 1;
+          value
+        }
+        _ => default,
+      };
+      self.consume_whitespace();
+      counters.push((name, value));
     }
-    Ok(declarations)
+    if counters.is_empty() {
+      return Err("Expected a counter name".to_string());
+    }
+    Ok(css::Value::Counters(counters))
+  }
+
+  // Parse one '<property>: <value>' declaration. Importance is not part of the cascade, so a
+  // declaration marked '!important' is dropped rather than applied as a normal one
+  fn parse_declaration(
+    &self,
+    declaration: &syntax::Declaration,
+  ) -> Result<css::Declaration, String> {
+    if declaration.is_important() {
+      return Err(format!("Unsupported '!important' in '{}'", declaration.name()));
+    }
+    let mut value_parser: CSSParser = self.nested(declaration.value());
+    let value: css::Value = match declaration.name() {
+      "content" => value_parser.parse_content()?,
+      "counter-reset" => value_parser.parse_counters(0)?,
+      "counter-increment" => value_parser.parse_counters(1)?,
+      _ => value_parser.parse_values()?,
+    };
+    value_parser.expect_end()?;
+    Ok(css::Declaration::new(declaration.name().to_string(), value))
+  }
+
+  // Parse the declarations of the block of a rule set, dropping the invalid ones
+  fn parse_declarations(&self, block: &[ComponentValue]) -> Vec<css::Declaration> {
+    syntax::parse_declaration_list(block)
+      .iter()
+      .filter_map(|declaration: &syntax::Declaration| self.parse_declaration(declaration).ok())
+      .collect()
+  }
+
+  // Parse an element name or '*'
+  fn parse_name_or_star(&mut self) -> Option<String> {
+    let name: String = match self.next_token() {
+      Some(Token::Ident(name)) => name.clone(),
+      Some(Token::Delim('*')) => "*".to_string(),
+      _ => return None,
+    };
+    self.position += 1;
+    Some(name)
   }
 
-  // Parse one simple selector, e.g.: 'type#id.class1.class2.class3'
-  fn parse_simple_selector(&mut self) -> css::SimpleSelector {
+  // Parse a type or universal selector with an optional namespace prefix, e.g. 'div', 'svg|rect',
+  // '*|a' (any namespace) or '|p' (no namespace)
+  // https://drafts.csswg.org/selectors/#type-nmsp
+  fn parse_type_selector(&mut self, selector: &mut css::SimpleSelector) -> Result<(), String> {
+    let mut name: Option<String> = self.parse_name_or_star();
+    if self.next_is(&Token::Delim('|')) {
+      self.position += 1;
+      let namespace: Option<String> = match name.as_deref() {
+        Some("*") => None,
+        None => Some(String::new()),
+        Some(prefix) => Some(
+          self
+            .namespaces
+            .get(prefix)
+            .cloned()
+            .ok_or_else(|| format!("Unknown namespace prefix '{}'", prefix))?,
+        ),
+      };
+      selector.set_namespace(namespace);
+      name = self.parse_name_or_star();
+    }
+    match name {
+      Some(name) if name == "*" => Ok(()),
+      Some(name) => {
+        selector.set_tag_name(Some(name));
+        Ok(())
+      }
+      None => Err("Expected an element name after '|'".to_string()),
+    }
+  }
+
+  // Parse an attribute selector, e.g. '[type]', '[href^="http"]' or '[lang|=en i]'
+  // https://drafts.csswg.org/selectors/#attribute-selectors
+  fn parse_attribute_selector(&mut self) -> Result<css::AttributeSelector, String> {
+    let contents: Vec<ComponentValue> = match self.consume() {
+      Some(ComponentValue::Block('[', contents)) => contents,
+      _ => return Err("Expected an attribute selector".to_string()),
+    };
+    let mut parser: CSSParser = self.nested(&contents);
+    parser.consume_whitespace();
+    let name: String = parser.parse_identifier()?;
+    parser.consume_whitespace();
+    let operator: css::AttributeOperator = match parser.consume() {
+      None => {
+        return Ok(css::AttributeSelector::new(
+          name,
+          css::AttributeOperator::Exists,
+          String::new(),
+          css::AttributeCase::Default,
+        ));
+      }
+      Some(ComponentValue::Token(Token::Delim(c))) => match c {
+        '=' => css::AttributeOperator::Equals,
+        '~' => css::AttributeOperator::Includes,
+        '|' => css::AttributeOperator::DashMatch,
+        '^' => css::AttributeOperator::Prefix,
+        '$' => css::AttributeOperator::Suffix,
+        '*' => css::AttributeOperator::Substring,
+        _ => return Err(format!("Unexpected '{}' in attribute selector", c)),
+      },
+      Some(value) => return Err(format!("Unexpected '{}' in attribute selector", value)),
+    };
+    if operator != css::AttributeOperator::Equals {
+      parser.expect(&Token::Delim('='))?;
+    }
+    parser.consume_whitespace();
+    let value: String = match parser.consume() {
+      Some(ComponentValue::Token(Token::Ident(value) | Token::String(value))) => value,
+      _ => return Err("Expected an attribute value".to_string()),
+    };
+    parser.consume_whitespace();
+    let case: css::AttributeCase = match parser.next_token() {
+      Some(Token::Ident(case)) if case.eq_ignore_ascii_case("i") => css::AttributeCase::Insensitive,
+      Some(Token::Ident(case)) if case.eq_ignore_ascii_case("s") => css::AttributeCase::Sensitive,
+      _ => css::AttributeCase::Default,
+    };
+    if case != css::AttributeCase::Default {
+      parser.position += 1;
+    }
+    parser.expect_end()?;
+    Ok(css::AttributeSelector::new(name, operator, value, case))
+  }
+
+  // Parse one simple selector, e.g.: 'type#id.class1.class2.class3[attr=value]:first-child' or
+  // 'p.note::before'
+  fn parse_simple_selector(&mut self) -> Result<css::SimpleSelector, String> {
     let mut selector: css::SimpleSelector = css::SimpleSelector::new(None, None, vec![]);
-    while !self.text_parser.eof() {
-      match self.text_parser.next_char() {
-        '#' => {
-          self.text_parser.consume_char();
-          selector.set_id(Some(self.parse_identifier()));
+    // Without a namespace prefix, a selector only matches elements in the default namespace
+    selector.set_namespace(self.default_namespace.clone());
+    while let Some(value) = self.next() {
+      match value {
+        ComponentValue::Token(Token::Hash(id, HashType::Id)) => {
+          selector.set_id(Some(id.clone()));
+          self.position += 1;
+        }
+        ComponentValue::Token(Token::Hash(id, HashType::Unrestricted)) => {
+          return Err(format!("Invalid id selector '#{}'", id));
+        }
+        ComponentValue::Token(Token::Delim('.')) => {
+          self.position += 1;
+          selector.add_class(self.parse_identifier()?);
         }
-        '.' => {
-          self.text_parser.consume_char();
-          selector.add_class(self.parse_identifier());
+        ComponentValue::Block('[', _) => selector.add_attribute(self.parse_attribute_selector()?),
+        // A pseudo-element ends the simple selector
+        ComponentValue::Token(Token::Colon) if self.at_pseudo_element() => {
+          selector.set_pseudo_element(Some(self.parse_pseudo_element()?));
+          break;
         }
-        '*' => {
-          // universal selector
-          self.text_parser.consume_char();
+        ComponentValue::Token(Token::Colon) => {
+          selector.add_pseudo_class(self.parse_pseudo_class()?)
         }
-        c if valid_identifier_char(c) => {
-          selector.set_tag_name(Some(self.parse_identifier()));
+        ComponentValue::Token(Token::Ident(_) | Token::Delim('*' | '|')) => {
+          self.parse_type_selector(&mut selector)?
         }
         _ => break,
       }
     }
-    return selector;
+    Ok(selector)
+  }
+
+  // Parse a simple selector that can't be empty, as a selector or after a combinator
+  fn parse_required_simple_selector(&mut self) -> Result<css::SimpleSelector, String> {
+    let start: usize = self.position;
+    let selector: css::SimpleSelector = self.parse_simple_selector()?;
+    if self.position == start {
+      return Err("Expected a selector".to_string());
+    }
+    Ok(selector)
+  }
+
+  // Parse a combinator, '>', '+' or '~', or return None
+  fn parse_combinator(&mut self) -> Option<css::Combinator> {
+    let combinator: css::Combinator = match self.next_token() {
+      Some(Token::Delim('>')) => css::Combinator::Child,
+      Some(Token::Delim('+')) => css::Combinator::NextSibling,
+      Some(Token::Delim('~')) => css::Combinator::SubsequentSibling,
+      _ => return None,
+    };
+    self.position += 1;
+    self.consume_whitespace();
+    Some(combinator)
+  }
+
+  // Parse the combinators and simple selectors that follow 'selector', e.g. ' > ul li + li',
+  // up to the end of the selector
+  // https://drafts.csswg.org/selectors/#combinators
+  fn parse_combinators(&mut self, mut selector: css::Selector) -> Result<css::Selector, String> {
+    loop {
+      let whitespace: bool = self.consume_whitespace();
+      if self.eof() || self.next_is(&Token::Comma) {
+        break;
+      }
+      let combinator: css::Combinator = match self.parse_combinator() {
+        Some(combinator) => combinator,
+        // Whitespace alone is the descendant combinator
+        None if whitespace => css::Combinator::Descendant,
+        None => return Err(format!("Unexpected '{}' in selector", self.input[self.position])),
+      };
+      if selector.pseudo_element().is_some() {
+        return Err("A pseudo-element must end the selector".to_string());
+      }
+      let simple_selector: css::SimpleSelector = self.parse_required_simple_selector()?;
+      selector = css::Selector::Complex(Box::new(selector), combinator, simple_selector);
+    }
+    Ok(selector)
+  }
+
+  // Parse simple selectors separated by combinators, e.g. 'nav > ul li + li'
+  fn parse_complex_selector(&mut self) -> Result<css::Selector, String> {
+    let selector: css::Selector = css::Selector::Simple(self.parse_required_simple_selector()?);
+    self.parse_combinators(selector)
+  }
+
+  // Parse a selector relative to the element it is anchored to, which may start with a
+  // combinator, e.g. '> img' or 'p' (a descendant)
+  // https://drafts.csswg.org/selectors/#relative
+  fn parse_relative_selector(&mut self) -> Result<css::Selector, String> {
+    let combinator: css::Combinator =
+      self.parse_combinator().unwrap_or(css::Combinator::Descendant);
+    let simple_selector: css::SimpleSelector = self.parse_required_simple_selector()?;
+    let selector: css::Selector =
+      css::Selector::Complex(Box::new(css::Selector::Anchor), combinator, simple_selector);
+    self.parse_combinators(selector)
+  }
+
+  // Parse a comma-separated list of relative selectors, the argument of ':has()'
+  fn parse_relative_selectors(&mut self) -> Result<Vec<css::Selector>, String> {
+    let mut selectors: Vec<css::Selector> = vec![self.parse_relative_selector()?];
+    while self.next_is(&Token::Comma) {
+      self.position += 1;
+      self.consume_whitespace();
+      selectors.push(self.parse_relative_selector()?);
+    }
+    Ok(selectors)
   }
 
-  // Parse a comma-separated list of selectors
+  // Parse the 'An+B' argument of ':nth-child()', e.g. 'odd', 'even', '3', '-n+2' or '2n + 1'.
+  // Its tokens depend on how it is written: 'n-1' is an identifier, '2n-1' a dimension, and
+  // '+n' a '+' followed by an identifier
+  // https://drafts.csswg.org/css-syntax/#anb-microsyntax
+  fn parse_nth(&mut self) -> Result<css::Nth, String> {
+    let error = || -> String { "Expected An+B".to_string() };
+    // A, and the rest of the token after its 'n', e.g. '-1' in '2n-1'
+    let (a, rest): (i32, String) = match self.consume() {
+      Some(ComponentValue::Token(Token::Number(b))) if b.is_integer() => {
+        return Ok(css::Nth::new(0, b.value() as i32));
+      }
+      Some(ComponentValue::Token(Token::Dimension(a, unit))) if a.is_integer() => {
+        (a.value() as i32, unit.to_ascii_lowercase())
+      }
+      Some(ComponentValue::Token(Token::Ident(identifier))) => {
+        match &*identifier.to_ascii_lowercase() {
+          "odd" => return Ok(css::Nth::new(2, 1)),
+          "even" => return Ok(css::Nth::new(2, 0)),
+          identifier => match identifier.strip_prefix('-') {
+            Some(rest) => (-1, rest.to_string()),
+            None => (1, identifier.to_string()),
+          },
+        }
+      }
+      // '+' can only be followed by 'n' without whitespace
+      Some(ComponentValue::Token(Token::Delim('+'))) => match self.consume() {
+        Some(ComponentValue::Token(Token::Ident(identifier))) if !identifier.starts_with('-') => {
+          (1, identifier.to_ascii_lowercase())
+        }
+        _ => return Err(error()),
+      },
+      _ => return Err(error()),
+    };
+    let rest: &str = rest.strip_prefix('n').ok_or_else(error)?;
+    // An unsigned integer, after whitespace
+    let parse_unsigned = |parser: &mut CSSParser| -> Result<i32, String> {
+      parser.consume_whitespace();
+      match parser.consume() {
+        Some(ComponentValue::Token(Token::Number(b))) if b.is_integer() && !b.is_signed() => {
+          Ok(b.value() as i32)
+        }
+        _ => Err("Expected B in An+B".to_string()),
+      }
+    };
+    let b: i32 = match rest {
+      "" => {
+        let start: usize = self.position;
+        self.consume_whitespace();
+        match self.next_token() {
+          Some(Token::Number(b)) if b.is_integer() && b.is_signed() => {
+            let b: i32 = b.value() as i32;
+            self.position += 1;
+            b
+          }
+          Some(Token::Delim('+')) => {
+            self.position += 1;
+            parse_unsigned(self)?
+          }
+          Some(Token::Delim('-')) => {
+            self.position += 1;
+            -parse_unsigned(self)?
+          }
+          _ => {
+            self.position = start;
+            0
+          }
+        }
+      }
+      "-" => -parse_unsigned(self)?,
+      _ => match rest.strip_prefix('-') {
+        Some(digits) if digits.chars().all(|c: char| c.is_ascii_digit()) => {
+          -digits.parse::<i32>().map_err(|_| error())?
+        }
+        _ => return Err(error()),
+      },
+    };
+    Ok(css::Nth::new(a, b))
+  }
+
+  // Is the next ':' the start of a pseudo-element, either '::before' or one of the legacy
+  // ':before' and ':after' written like pseudo-classes?
+  fn at_pseudo_element(&self) -> bool {
+    match self.input.get(self.position + 1) {
+      Some(ComponentValue::Token(Token::Colon)) => true,
+      Some(ComponentValue::Token(Token::Ident(name))) => {
+        name.eq_ignore_ascii_case("before") || name.eq_ignore_ascii_case("after")
+      }
+      _ => false,
+    }
+  }
+
+  // Parse a pseudo-element, e.g. '::before' or ':after'
+  fn parse_pseudo_element(&mut self) -> Result<css::PseudoElement, String> {
+    self.expect(&Token::Colon)?;
+    if self.next_is(&Token::Colon) {
+      self.position += 1;
+    }
+    let name: String = self.parse_identifier()?.to_ascii_lowercase();
+    match &*name {
+      "before" => Ok(css::PseudoElement::Before),
+      "after" => Ok(css::PseudoElement::After),
+      _ => Err(format!("Unsupported pseudo-element '::{}'", name)),
+    }
+  }
+
+  // Parse a pseudo-class, e.g. ':hover', ':nth-child(2n+1 of .item)' or ':not(p, div)'
+  fn parse_pseudo_class(&mut self) -> Result<css::PseudoClass, String> {
+    self.expect(&Token::Colon)?;
+    let (name, arguments): (String, Vec<ComponentValue>) = match self.consume() {
+      Some(ComponentValue::Token(Token::Ident(name))) => {
+        return match &*name.to_ascii_lowercase() {
+          "hover" => Ok(css::PseudoClass::Hover),
+          "active" => Ok(css::PseudoClass::Active),
+          "focus" => Ok(css::PseudoClass::Focus),
+          "focus-within" => Ok(css::PseudoClass::FocusWithin),
+          "checked" => Ok(css::PseudoClass::Checked),
+          "disabled" => Ok(css::PseudoClass::Disabled),
+          "enabled" => Ok(css::PseudoClass::Enabled),
+          "link" => Ok(css::PseudoClass::Link),
+          "visited" => Ok(css::PseudoClass::Visited),
+          "root" => Ok(css::PseudoClass::Root),
+          "empty" => Ok(css::PseudoClass::Empty),
+          "first-child" => Ok(css::PseudoClass::FirstChild),
+          "last-child" => Ok(css::PseudoClass::LastChild),
+          "only-child" => Ok(css::PseudoClass::OnlyChild),
+          "first-of-type" => Ok(css::PseudoClass::FirstOfType),
+          "last-of-type" => Ok(css::PseudoClass::LastOfType),
+          "only-of-type" => Ok(css::PseudoClass::OnlyOfType),
+          name => Err(format!("Unsupported pseudo-class ':{}'", name)),
+        };
+      }
+      Some(ComponentValue::Function(name, arguments)) => (name.to_ascii_lowercase(), arguments),
+      _ => return Err("Expected a pseudo-class name after ':'".to_string()),
+    };
+    let mut parser: CSSParser = self.nested(&arguments);
+    parser.consume_whitespace();
+    let pseudo_class: css::PseudoClass = match &*name {
+      "nth-child" | "nth-last-child" => {
+        let nth: css::Nth = parser.parse_nth()?;
+        parser.consume_whitespace();
+        let mut selectors: Vec<css::Selector> = vec![];
+        if let Some(Token::Ident(of)) = parser.next_token() {
+          if of.eq_ignore_ascii_case("of") {
+            parser.position += 1;
+            selectors = parser.parse_selectors()?;
+          }
+        }
+        if name == "nth-child" {
+          css::PseudoClass::NthChild(nth, selectors)
+        } else {
+          css::PseudoClass::NthLastChild(nth, selectors)
+        }
+      }
+      "nth-of-type" => css::PseudoClass::NthOfType(parser.parse_nth()?),
+      "nth-last-of-type" => css::PseudoClass::NthLastOfType(parser.parse_nth()?),
+      "not" => css::PseudoClass::Not(parser.parse_selectors()?),
+      "is" | "matches" => css::PseudoClass::Is(parser.parse_selectors()?),
+      "where" => css::PseudoClass::Where(parser.parse_selectors()?),
+      "has" => css::PseudoClass::Has(parser.parse_relative_selectors()?),
+      _ => return Err(format!("Unsupported pseudo-class ':{}()'", name)),
+    };
+    // Pseudo-elements are not elements, so the arguments of pseudo-classes can't select them
+    if let css::PseudoClass::NthChild(_, selectors)
+    | css::PseudoClass::NthLastChild(_, selectors)
+    | css::PseudoClass::Not(selectors)
+    | css::PseudoClass::Is(selectors)
+    | css::PseudoClass::Where(selectors)
+    | css::PseudoClass::Has(selectors) = &pseudo_class
+    {
+      if selectors.iter().any(|selector: &css::Selector| selector.pseudo_element().is_some()) {
+        return Err(format!("Pseudo-element in the argument of ':{}()'", name));
+      }
+    }
+    parser.expect_end()?;
+    Ok(pseudo_class)
+  }
+
+  // Parse a comma-separated list of selectors, up to the end of the input: the prelude of a rule
+  // set, a standalone list, or the argument of a pseudo-class
   fn parse_selectors(&mut self) -> Result<Vec<css::Selector>, String> {
     let mut selectors: Vec<css::Selector> = Vec::new();
     loop {
-      selectors.push(css::Selector::Simple(self.parse_simple_selector()));
-      self.text_parser.consume_whitespace();
-      match self.text_parser.next_char() {
-        ',' => {
-          self.text_parser.consume_char();
-          self.text_parser.consume_whitespace();
-        }
-        '{' => break, // start of declarations
-        c => return Err(format!("Unexpected character '{}' in selector list", c)),
+      self.consume_whitespace();
+      selectors.push(self.parse_complex_selector()?);
+      match self.consume() {
+        Some(ComponentValue::Token(Token::Comma)) => {}
+        Some(value) => return Err(format!("Unexpected '{}' in selector list", value)),
+        None => break,
       }
     }
     // Return selectors with highest specificity first, for use in matching
-    selectors.sort_by(|a: &css::Selector, b: &css::Selector| b.specificity().cmp(&a.specificity()));
+    selectors.sort_by_key(|selector: &css::Selector| std::cmp::Reverse(selector.specificity()));
     Ok(selectors)
   }
 
   // Parse a rule set: '<selectors> { <declarations> }'
-  fn parse_rule(&mut self) -> Result<css::Rule, String> {
-    Ok(css::Rule::new(self.parse_selectors()?, self.parse_declarations()?))
+  fn parse_rule(&self, rule: &syntax::QualifiedRule) -> Result<css::Rule, String> {
+    let selectors: Vec<css::Selector> = self.nested(rule.prelude()).parse_selectors()?;
+    Ok(css::Rule::new(selectors, self.parse_declarations(rule.block())))
   }
 
-  // Parse a list of rule sets, separated by optional whitespace
-  fn parse_rules(&mut self) -> Result<Vec<css::Rule>, String> {
-    let mut rules: Vec<css::Rule> = Vec::new();
-    loop {
-      self.text_parser.consume_whitespace();
-      if self.text_parser.eof() {
-        break;
-      }
-      match self.parse_rule() {
-        Ok(rule) => rules.push(rule),
-        Err(_) => {
-          // Recovery: discard this rule, skip to its closing '}'
-          self.text_parser.consume_while(|c: char| c != '}');
-          if !self.text_parser.eof() {
-            self.text_parser.consume_char(); // consume '}'
+  // Parse the rules of a stylesheet. Invalid rule sets and unsupported at-rules are dropped
+  fn parse_rules(&mut self, rules: &[syntax::Rule]) -> Vec<css::Rule> {
+    let mut rule_sets: Vec<css::Rule> = Vec::new();
+    // @namespace rules must come before any other rule but @charset, which only declares the
+    // encoding the source was decoded from
+    let mut namespaces_allowed: bool = true;
+    for rule in rules {
+      match rule {
+        syntax::Rule::At(at_rule) if at_rule.name().eq_ignore_ascii_case("namespace") => {
+          // Recovery: an invalid or misplaced @namespace rule is ignored
+          if namespaces_allowed {
+            let _ = self.parse_namespace_rule(at_rule);
+          }
+        }
+        syntax::Rule::At(at_rule) if at_rule.name().eq_ignore_ascii_case("charset") => {}
+        syntax::Rule::At(_) => namespaces_allowed = false,
+        syntax::Rule::Qualified(qualified_rule) => {
+          namespaces_allowed = false;
+          if let Ok(rule_set) = self.parse_rule(qualified_rule) {
+            rule_sets.push(rule_set);
           }
         }
       }
     }
-    Ok(rules)
+    rule_sets
+  }
+
+  // Parse a URL written as a string or as 'url(...)', with or without quotes
+  fn parse_url(&mut self) -> Result<String, String> {
+    match self.consume() {
+      Some(ComponentValue::Token(Token::Url(url) | Token::String(url))) => Ok(url),
+      Some(ComponentValue::Function(name, arguments)) if name.eq_ignore_ascii_case("url") => {
+        let mut parser: CSSParser = self.nested(&arguments);
+        parser.consume_whitespace();
+        let url: String = match parser.consume() {
+          Some(ComponentValue::Token(Token::String(url))) => url,
+          _ => return Err("Expected a string in 'url()'".to_string()),
+        };
+        parser.expect_end()?;
+        Ok(url)
+      }
+      _ => Err("Expected a URL".to_string()),
+    }
+  }
+
+  // Parse an '@namespace <prefix>? <url>;' rule, which declares the default namespace of
+  // selectors or a prefix for it, e.g. '@namespace svg url(http://www.w3.org/2000/svg);'
+  // https://drafts.csswg.org/css-namespaces/#syntax
+  fn parse_namespace_rule(&mut self, rule: &syntax::AtRule) -> Result<(), String> {
+    if rule.block().is_some() {
+      return Err("Unexpected block in '@namespace'".to_string());
+    }
+    let mut parser: CSSParser = self.nested(rule.prelude());
+    parser.consume_whitespace();
+    let prefix: Option<String> = match parser.next_token() {
+      Some(Token::Ident(_)) => Some(parser.parse_identifier()?),
+      _ => None,
+    };
+    parser.consume_whitespace();
+    let url: String = parser.parse_url()?;
+    parser.expect_end()?;
+    match prefix {
+      Some(prefix) => self.namespaces.insert(prefix, url),
+      None => self.default_namespace.replace(url),
+    };
+    Ok(())
+  }
+
+  // Parse a selector list on its own, e.g. the argument of Node::query_selector
+  pub fn parse_selector_list(source: String) -> Result<Vec<css::Selector>, String> {
+    CSSParser::new(source).parse_selectors()
   }
 
   // Parse a whole CSS stylesheet
   pub fn parse(source: String) -> Result<css::Stylesheet, String> {
-    let mut css_parser: CSSParser = CSSParser::new(0, source);
-    Ok(css::Stylesheet::new(css_parser.parse_rules()?))
+    let rules: Vec<syntax::Rule> = syntax::parse_stylesheet(&source);
+    let mut css_parser: CSSParser = CSSParser::new(String::new());
+    Ok(css::Stylesheet::new(css_parser.parse_rules(&rules)))
   }
 }
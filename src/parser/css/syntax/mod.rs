@@ -0,0 +1,327 @@
+use std::fmt;
+
+use super::tokenizer::{Token, Tokenizer};
+
+#[cfg(test)]
+mod tests;
+
+// A token, or a function or a block with the component values it encloses. Parsing a stylesheet
+// into component values pairs up its brackets, so that a grammar never has to
+// https://drafts.csswg.org/css-syntax/#component-value
+#[derive(Clone, PartialEq, Debug)]
+pub enum ComponentValue {
+  Token(Token),
+  // A function name and its arguments, e.g. 'rgb(0, 0, 0)'
+  Function(String, Vec<ComponentValue>),
+  // A block by its opening bracket, '(', '[' or '{', and its contents
+  Block(char, Vec<ComponentValue>),
+}
+
+impl ComponentValue {
+  // Is this the given token?
+  pub fn is(&self, token: &Token) -> bool {
+    matches!(self, ComponentValue::Token(value) if value == token)
+  }
+}
+
+impl fmt::Display for ComponentValue {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let write_values = |f: &mut fmt::Formatter, values: &[ComponentValue]| -> fmt::Result {
+      values
+        .iter()
+        .try_for_each(|value: &ComponentValue| write!(f, "{}", value))
+    };
+    match self {
+      ComponentValue::Token(token) => write!(f, "{}", token),
+      ComponentValue::Function(name, arguments) => {
+        write!(f, "{}(", name)?;
+        write_values(f, arguments)?;
+        write!(f, ")")
+      }
+      ComponentValue::Block(bracket, contents) => {
+        write!(f, "{}", bracket)?;
+        write_values(f, contents)?;
+        write!(f, "{}", closing_bracket(*bracket))
+      }
+    }
+  }
+}
+
+// An at-rule, e.g. '@namespace svg url(...);' or '@media print { ... }'
+// https://drafts.csswg.org/css-syntax/#at-rule
+#[derive(Clone, PartialEq, Debug)]
+pub struct AtRule {
+  name: String,
+  prelude: Vec<ComponentValue>,
+  // The contents of the '{ ... }' block, for an at-rule that doesn't end with a ';'
+  block: Option<Vec<ComponentValue>>,
+}
+
+impl AtRule {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn prelude(&self) -> &[ComponentValue] {
+    &self.prelude
+  }
+
+  pub fn block(&self) -> Option<&[ComponentValue]> {
+    self.block.as_deref()
+  }
+}
+
+// A rule set, of which the prelude is a selector list and the block a list of declarations
+// https://drafts.csswg.org/css-syntax/#qualified-rule
+#[derive(Clone, PartialEq, Debug)]
+pub struct QualifiedRule {
+  prelude: Vec<ComponentValue>,
+  block: Vec<ComponentValue>,
+}
+
+impl QualifiedRule {
+  pub fn prelude(&self) -> &[ComponentValue] {
+    &self.prelude
+  }
+
+  pub fn block(&self) -> &[ComponentValue] {
+    &self.block
+  }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Rule {
+  At(AtRule),
+  Qualified(QualifiedRule),
+}
+
+// A declaration, with the component values of its value, without the whitespace around them and
+// without '!important', which sets 'important'
+// https://drafts.csswg.org/css-syntax/#declaration
+#[derive(Clone, PartialEq, Debug)]
+pub struct Declaration {
+  name: String,
+  value: Vec<ComponentValue>,
+  important: bool,
+}
+
+impl Declaration {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn value(&self) -> &[ComponentValue] {
+    &self.value
+  }
+
+  pub fn is_important(&self) -> bool {
+    self.important
+  }
+}
+
+fn closing_bracket(bracket: char) -> char {
+  match bracket {
+    '(' => ')',
+    '[' => ']',
+    _ => '}',
+  }
+}
+
+// Blocks and functions nested deeper than this are parsed without their contents, so that
+// parsing them, and the grammars over them, can't exhaust the stack
+const MAX_NESTING_DEPTH: usize = 64;
+
+// Parse a source into a list of component values
+// https://drafts.csswg.org/css-syntax/#parse-list-of-component-values
+pub fn parse_component_values(source: &str) -> Vec<ComponentValue> {
+  let mut tokens: std::vec::IntoIter<Token> = Tokenizer::tokenize(source).into_iter();
+  consume_component_values(&mut tokens, None, 0)
+}
+
+// Consume component values up to the token that closes the enclosing block or function, or the
+// end of the input, which closes them all
+// https://drafts.csswg.org/css-syntax/#consume-component-value
+fn consume_component_values<I>(
+  tokens: &mut I,
+  closing: Option<&Token>,
+  depth: usize,
+) -> Vec<ComponentValue>
+where
+  I: Iterator<Item = Token>,
+{
+  let mut values: Vec<ComponentValue> = vec![];
+  while let Some(token) = tokens.next() {
+    if Some(&token) == closing {
+      break;
+    }
+    let (bracket, closing_token): (char, Token) = match token {
+      Token::Function(_) | Token::OpenParen => ('(', Token::CloseParen),
+      Token::OpenSquare => ('[', Token::CloseSquare),
+      Token::OpenCurly => ('{', Token::CloseCurly),
+      token => {
+        values.push(ComponentValue::Token(token));
+        continue;
+      }
+    };
+    let contents: Vec<ComponentValue> = if depth < MAX_NESTING_DEPTH {
+      consume_component_values(tokens, Some(&closing_token), depth + 1)
+    } else {
+      skip_component_values(tokens, closing_token);
+      vec![]
+    };
+    values.push(match token {
+      Token::Function(name) => ComponentValue::Function(name, contents),
+      _ => ComponentValue::Block(bracket, contents),
+    });
+  }
+  values
+}
+
+// Skip the tokens up to the one that closes a block or function, and the blocks and functions
+// nested in it
+fn skip_component_values<I>(tokens: &mut I, closing: Token)
+where
+  I: Iterator<Item = Token>,
+{
+  let mut closings: Vec<Token> = vec![closing];
+  for token in tokens {
+    match token {
+      Token::Function(_) | Token::OpenParen => closings.push(Token::CloseParen),
+      Token::OpenSquare => closings.push(Token::CloseSquare),
+      Token::OpenCurly => closings.push(Token::CloseCurly),
+      token if Some(&token) == closings.last() => {
+        closings.pop();
+        if closings.is_empty() {
+          return;
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+// Parse a stylesheet into its rules
+// https://drafts.csswg.org/css-syntax/#parse-stylesheet
+pub fn parse_stylesheet(source: &str) -> Vec<Rule> {
+  parse_rules(&parse_component_values(source), true)
+}
+
+// Parse a list of rules. At the top level of a stylesheet, '<!--' and '-->' are ignored. A
+// qualified rule without a block is dropped
+// https://drafts.csswg.org/css-syntax/#consume-list-of-rules
+pub fn parse_rules(values: &[ComponentValue], top_level: bool) -> Vec<Rule> {
+  let mut rules: Vec<Rule> = vec![];
+  let mut index: usize = 0;
+  while let Some(value) = values.get(index) {
+    match value {
+      ComponentValue::Token(Token::Whitespace) => index += 1,
+      ComponentValue::Token(Token::Cdo | Token::Cdc) if top_level => index += 1,
+      ComponentValue::Token(Token::AtKeyword(name)) => {
+        let (prelude, block, end): (&[ComponentValue], Option<&[ComponentValue]>, usize) =
+          split_rule(values, index + 1, true);
+        rules.push(Rule::At(AtRule {
+          name: name.clone(),
+          prelude: prelude.to_vec(),
+          block: block.map(<[ComponentValue]>::to_vec),
+        }));
+        index = end;
+      }
+      _ => {
+        let (prelude, block, end): (&[ComponentValue], Option<&[ComponentValue]>, usize) =
+          split_rule(values, index, false);
+        if let Some(block) = block {
+          rules.push(Rule::Qualified(QualifiedRule {
+            prelude: prelude.to_vec(),
+            block: block.to_vec(),
+          }));
+        }
+        index = end;
+      }
+    }
+  }
+  rules
+}
+
+// Split the rule starting at 'start' into its prelude and its '{ ... }' block, and return the
+// index after it. An at-rule can end with a ';' instead of a block
+fn split_rule(
+  values: &[ComponentValue],
+  start: usize,
+  at_rule: bool,
+) -> (&[ComponentValue], Option<&[ComponentValue]>, usize) {
+  for (index, value) in values.iter().enumerate().skip(start) {
+    match value {
+      ComponentValue::Block('{', block) => return (&values[start..index], Some(block), index + 1),
+      ComponentValue::Token(Token::Semicolon) if at_rule => {
+        return (&values[start..index], None, index + 1);
+      }
+      _ => {}
+    }
+  }
+  (&values[start..], None, values.len())
+}
+
+// Parse the contents of a block into declarations, separated by ';'. Invalid declarations and
+// at-rules are dropped, and parsing resumes after the next ';'
+// https://drafts.csswg.org/css-syntax/#consume-list-of-declarations
+pub fn parse_declaration_list(values: &[ComponentValue]) -> Vec<Declaration> {
+  let mut declarations: Vec<Declaration> = vec![];
+  let mut index: usize = 0;
+  while let Some(value) = values.get(index) {
+    match value {
+      ComponentValue::Token(Token::Whitespace) => index += 1,
+      ComponentValue::Token(Token::AtKeyword(_)) => index = split_rule(values, index + 1, true).2,
+      _ => {
+        let end: usize = values[index..]
+          .iter()
+          .position(|value: &ComponentValue| value.is(&Token::Semicolon))
+          .map_or(values.len(), |length: usize| index + length);
+        if let Ok(declaration) = consume_declaration(&values[index..end]) {
+          declarations.push(declaration);
+        }
+        index = end + 1;
+      }
+    }
+  }
+  declarations
+}
+
+// Consume a declaration, '<name> : <value>', and its '!important' if it ends with one
+// https://drafts.csswg.org/css-syntax/#consume-declaration
+fn consume_declaration(values: &[ComponentValue]) -> Result<Declaration, String> {
+  let mut values: &[ComponentValue] = trim_whitespace(values);
+  let name: String = match values.first() {
+    Some(ComponentValue::Token(Token::Ident(name))) => name.clone(),
+    Some(value) => return Err(format!("Expected a property name, found '{}'", value)),
+    None => return Err("Expected a declaration".to_string()),
+  };
+  values = trim_whitespace(&values[1..]);
+  match values.first() {
+    Some(colon) if colon.is(&Token::Colon) => values = trim_whitespace(&values[1..]),
+    _ => return Err(format!("Expected ':' after '{}'", name)),
+  }
+  // The last two component values, apart from whitespace, can be '!' and 'important'
+  let mut important: bool = false;
+  if let [rest @ .., ComponentValue::Token(Token::Ident(keyword))] = values {
+    if let [rest @ .., bang] = trim_whitespace(rest) {
+      if bang.is(&Token::Delim('!')) && keyword.eq_ignore_ascii_case("important") {
+        important = true;
+        values = trim_whitespace(rest);
+      }
+    }
+  }
+  Ok(Declaration {
+    name,
+    value: values.to_vec(),
+    important,
+  })
+}
+
+// Remove the whitespace at the start and end of a list of component values
+fn trim_whitespace(values: &[ComponentValue]) -> &[ComponentValue] {
+  let is_content = |value: &ComponentValue| -> bool { !value.is(&Token::Whitespace) };
+  match values.iter().position(is_content) {
+    Some(start) => &values[start..=values.iter().rposition(is_content).unwrap_or(start)],
+    None => &[],
+  }
+}
@@ -0,0 +1,142 @@
+use super::*;
+use crate::parser::css::tokenizer::Number;
+
+fn token(token: Token) -> ComponentValue {
+  ComponentValue::Token(token)
+}
+
+fn ident(name: &str) -> ComponentValue {
+  ComponentValue::Token(Token::Ident(name.to_string()))
+}
+
+// Test the function parse_component_values
+#[test]
+fn test_parse_component_values() {
+  // Assert that functions and blocks enclose the component values up to their closing bracket
+  assert_eq!(
+    parse_component_values("a(b [c]) {d}"),
+    vec![
+      ComponentValue::Function(
+        "a".to_string(),
+        vec![
+          ident("b"),
+          token(Token::Whitespace),
+          ComponentValue::Block('[', vec![ident("c")])
+        ],
+      ),
+      token(Token::Whitespace),
+      ComponentValue::Block('{', vec![ident("d")]),
+    ]
+  );
+  // Assert that a closing bracket that doesn't match the block is kept as a token
+  assert_eq!(
+    parse_component_values("[a)]"),
+    vec![ComponentValue::Block(
+      '[',
+      vec![ident("a"), token(Token::CloseParen)]
+    )]
+  );
+  // Assert that the end of the input closes the blocks and functions that are still open
+  assert_eq!(
+    parse_component_values("{a(b"),
+    vec![ComponentValue::Block(
+      '{',
+      vec![ComponentValue::Function("a".to_string(), vec![ident("b")])]
+    )]
+  );
+  // Assert that component values are written back as CSS
+  let values: Vec<ComponentValue> = parse_component_values("rgb(0, 0 ,0) [a=\"b\"]");
+  let written: String = values.iter().map(ComponentValue::to_string).collect();
+  assert_eq!(written, "rgb(0, 0 ,0) [a=\"b\"]");
+}
+
+// Test the function parse_stylesheet
+#[test]
+fn test_parse_stylesheet() {
+  let rules: Vec<Rule> =
+    parse_stylesheet("<!-- @charset \"utf-8\"; p, a { color: red } @media print { p {} } -->");
+
+  // Assert that at-rules end with a ';' or a block, and qualified rules with a block
+  assert_eq!(rules.len(), 3);
+  match &rules[0] {
+    Rule::At(rule) => {
+      assert_eq!(rule.name(), "charset");
+      assert_eq!(
+        rule.prelude(),
+        [
+          token(Token::Whitespace),
+          token(Token::String("utf-8".to_string()))
+        ]
+      );
+      assert_eq!(rule.block(), None);
+    }
+    rule => panic!("Expected an at-rule, found {:?}", rule),
+  }
+  match &rules[1] {
+    Rule::Qualified(rule) => {
+      assert_eq!(rule.prelude().len(), 5);
+      assert_eq!(rule.block().len(), 6);
+    }
+    rule => panic!("Expected a qualified rule, found {:?}", rule),
+  }
+  match &rules[2] {
+    Rule::At(rule) => {
+      assert_eq!(rule.name(), "media");
+      assert_eq!(
+        parse_rules(rule.block().unwrap_or_default(), false).len(),
+        1
+      );
+    }
+    rule => panic!("Expected an at-rule, found {:?}", rule),
+  }
+  // Assert that a qualified rule without a block is dropped
+  assert_eq!(parse_stylesheet("p { } a"), parse_stylesheet("p { }"));
+  // Assert that comments between rules are ignored
+  assert_eq!(parse_stylesheet("/* a */ p /* b */ { } /* c */").len(), 1);
+}
+
+// Test the function parse_declaration_list
+#[test]
+fn test_parse_declaration_list() {
+  let declarations: Vec<Declaration> = parse_declaration_list(&parse_component_values(
+    " width : 1px ; ; color: rgb(0;0;0) ; 3: a; margin: 0 !IMPORTANT; @page { a: b } top: 0",
+  ));
+  let names: Vec<&str> = declarations
+    .iter()
+    .map(|declaration: &Declaration| declaration.name())
+    .collect();
+
+  // Assert that invalid declarations and at-rules are dropped, and that a ';' inside a function
+  // doesn't end a declaration
+  assert_eq!(names, ["width", "color", "margin", "top"]);
+  // Assert that the whitespace around the value is removed
+  assert_eq!(
+    declarations[0].value(),
+    [ComponentValue::Token(Token::Dimension(
+      Number::new(1.0, true, false),
+      "px".to_string()
+    ))]
+  );
+  // Assert that '!important' is removed from the value, ignoring case
+  assert!(declarations[2].is_important());
+  assert_eq!(declarations[2].value().len(), 1);
+  assert!(!declarations[3].is_important());
+}
+
+// Test the function consume_declaration
+#[test]
+fn test_consume_declaration() {
+  let parse_declaration = |source: &str| -> Result<Declaration, String> {
+    consume_declaration(&parse_component_values(source))
+  };
+
+  // Assert that '!' and 'important' can be separated by whitespace
+  let declaration: Declaration = parse_declaration("color: red ! important").unwrap();
+  assert_eq!(declaration.name(), "color");
+  assert_eq!(declaration.value(), [ident("red")]);
+  assert!(declaration.is_important());
+  // Assert that a declaration needs a name and a ':'
+  assert!(parse_declaration("color red").is_err());
+  assert!(parse_declaration(": red").is_err());
+  assert!(parse_declaration("").is_err());
+}
@@ -2,80 +2,132 @@ use crate::css;
 use crate::css::Stylesheet;
 use super::*;
 
+// Parse the first declaration of a declaration list
+fn declaration(source: &str) -> Result<css::Declaration, String> {
+  let declarations: Vec<syntax::Declaration> =
+    syntax::parse_declaration_list(&syntax::parse_component_values(source));
+  let declaration: &syntax::Declaration =
+    declarations.first().ok_or_else(|| "Expected a declaration".to_string())?;
+  CSSParser::new(String::new()).parse_declaration(declaration)
+}
+
 // Test the method parse_identifier of the CSSParser struct implementation
 #[test]
 fn test_parse_identifier() {
-  let mut css_parser: CSSParser = CSSParser::new(11, ".container{width:100px;}".to_string());
+  let mut css_parser: CSSParser = CSSParser::new("width:100px".to_string());
 
-  // Assert that the parse_value method correctly parses the string "width"
-  assert_eq!(css_parser.parse_identifier(), "width");
+  // Assert that the parse_identifier method correctly parses the string "width"
+  assert_eq!(css_parser.parse_identifier(), Ok("width".to_string()));
+  // Assert that other tokens are not identifiers
+  assert!(css_parser.parse_identifier().is_err());
 }
 
 // Test the method parse_unit of the CSSParser struct implementation
 #[test]
 fn test_parse_unit() {
-  let mut css_parser: CSSParser = CSSParser::new(20, ".container{width:200px;}".to_string());
-
-  // Assert that the parse_float method correctly parses the unit "px"
-  assert_eq!(css_parser.parse_unit().unwrap(), css::Unit::Px);
-}
-
-// Test the method parse_float of the CSSParser struct implementation
-#[test]
-fn test_parse_float() {
-  let mut css_parser: CSSParser = CSSParser::new(17, ".container{width:100.5px;}".to_string());
-
-  // Assert that the parse_float method correctly parses the float value "100.5"
-  assert_eq!(css_parser.parse_float().unwrap(), 100.5);
-
-  let mut css_parser: CSSParser = CSSParser::new(17, ".container{width:100px;}".to_string());
-
-  // Assert that the parse_float method correctly parses the float value "100"
-  assert_eq!(css_parser.parse_float().unwrap(), 100.0);
+  // Assert that the parse_unit method correctly parses the unit "px"
+  assert_eq!(CSSParser::parse_unit("px").unwrap(), css::Unit::Px);
+
+  // Assert that relative and absolute units are parsed, ignoring case
+  let unit = |source: &str| -> Result<css::Unit, String> { CSSParser::parse_unit(source) };
+  assert_eq!(unit("EM"), Ok(css::Unit::Em));
+  assert_eq!(unit("rem"), Ok(css::Unit::Rem));
+  assert_eq!(unit("vw"), Ok(css::Unit::Vw));
+  assert_eq!(unit("vh"), Ok(css::Unit::Vh));
+  assert_eq!(unit("pt"), Ok(css::Unit::Pt));
+  assert_eq!(unit("cm"), Ok(css::Unit::Cm));
+  assert_eq!(unit("in"), Ok(css::Unit::In));
+  assert!(unit("furlong").is_err());
 }
 
-// Test the method parse_length of the CSSParser struct implementation
+// Test the method parse_numeric of the CSSParser struct implementation
 #[test]
-fn test_parse_length() {
-  let mut css_parser: CSSParser = CSSParser::new(17, ".container{width:100.5px;}".to_string());
+fn test_parse_numeric() {
+  let mut css_parser: CSSParser = CSSParser::new("100.5px".to_string());
   let unit: css::Value = css::Value::Length(100.5, css::Unit::Px);
 
-  // Assert that the parse_value method correctly parses the value "100.5" with unit "px"
-  assert_eq!(css_parser.parse_length().unwrap(), unit);
-}
-
-// Test the method parse_hex_pair of the CSSParser struct implementation
-#[test]
-fn test_parse_hex_pair() {
-  let mut css_parser: CSSParser =
-    CSSParser::new(23, ".container{background:#A3E4D7;}".to_string());
-
-  // Assert that the parse_hex_pair method correctly parses the first hex pair "A3" as 163
-  assert_eq!(css_parser.parse_hex_pair().unwrap(), 163);
-  // Assert that the parse_hex_pair method correctly parses the second hex pair "E4" as 228
-  assert_eq!(css_parser.parse_hex_pair().unwrap(), 228);
-  // Assert that the parse_hex_pair method correctly parses the third hex pair "D7" as 215
-  assert_eq!(css_parser.parse_hex_pair().unwrap(), 215);
+  // Assert that the parse_numeric method correctly parses the value "100.5" with unit "px"
+  assert_eq!(css_parser.parse_numeric().unwrap(), unit);
+
+  let numeric = |source: &str| -> Result<css::Value, String> {
+    CSSParser::new(source.to_string()).parse_numeric()
+  };
+  // Assert that numbers without a unit and percentages have their own values
+  assert_eq!(numeric("1.4;"), Ok(css::Value::Number(1.4)));
+  assert_eq!(numeric("0"), Ok(css::Value::Number(0.0)));
+  assert_eq!(numeric("-50%"), Ok(css::Value::Percentage(-50.0)));
+  assert_eq!(numeric(".5em"), Ok(css::Value::Length(0.5, css::Unit::Em)));
+  assert!(numeric("3furlongs").is_err());
+  assert!(numeric("px").is_err());
 }
 
 // Test the method parse_color of the CSSParser struct implementation
 #[test]
 fn test_parse_color() {
-  let mut css_parser: CSSParser =
-    CSSParser::new(22, ".container{background:#A3E4D7;}".to_string());
+  let mut css_parser: CSSParser = CSSParser::new("#A3E4D7".to_string());
   let color: css::Value = css::Value::ColorValue(css::Color::new(163, 228, 215, 255));
 
   // Assert that the parse_color method correctly parses the color "A3E4D7"
   assert_eq!(css_parser.parse_color().unwrap(), color);
+
+  let parse_color = |input: &str| -> Result<css::Value, String> {
+    CSSParser::new(input.to_string()).parse_color()
+  };
+  let rgba = |red: u8, green: u8, blue: u8, alpha: u8| -> Result<css::Value, String> {
+    Ok(css::Value::ColorValue(css::Color::new(red, green, blue, alpha)))
+  };
+  // Assert that the short forms repeat each digit, and the forms with 4 and 8 digits set the alpha
+  assert_eq!(parse_color("#0f8"), rgba(0, 255, 136, 255));
+  assert_eq!(parse_color("#0F88"), rgba(0, 255, 136, 136));
+  assert_eq!(parse_color("#A3E4D780"), rgba(163, 228, 215, 128));
+  // Assert that hex colors with another number of digits are errors
+  assert!(parse_color("#12345").is_err());
+  assert!(parse_color("#").is_err());
+  assert!(parse_color("#main").is_err());
+}
+
+// Test the method parse_color_function of the CSSParser struct implementation
+#[test]
+fn test_parse_color_function() {
+  let parse_value = |input: &str| -> Result<css::Value, String> {
+    CSSParser::new(input.to_string()).parse_value()
+  };
+  let rgba = |red: u8, green: u8, blue: u8, alpha: u8| -> Result<css::Value, String> {
+    Ok(css::Value::ColorValue(css::Color::new(red, green, blue, alpha)))
+  };
+
+  // Assert that rgb() and rgba() take numbers or percentages, in the legacy and modern syntax
+  assert_eq!(parse_value("rgb(255, 0, 128)"), rgba(255, 0, 128, 255));
+  assert_eq!(parse_value("rgba(255,0,128,0.5)"), rgba(255, 0, 128, 128));
+  assert_eq!(parse_value("RGB(100%, 0%, 50%)"), rgba(255, 0, 128, 255));
+  assert_eq!(parse_value("rgb(255 0 128 / 25%)"), rgba(255, 0, 128, 64));
+  assert_eq!(parse_value("rgba( 255 50% 0 )"), rgba(255, 128, 0, 255));
+  // Assert that out of range channels are clamped
+  assert_eq!(parse_value("rgb(300, -20, 0, 2)"), rgba(255, 0, 0, 255));
+  // Assert that hsl() and hsla() take a hue in degrees or another angle unit
+  assert_eq!(parse_value("hsl(120, 100%, 50%)"), rgba(0, 255, 0, 255));
+  assert_eq!(parse_value("hsla(240deg, 100%, 50%, 0.5)"), rgba(0, 0, 255, 128));
+  assert_eq!(parse_value("hsl(0.5turn 100% 25%)"), rgba(0, 128, 128, 255));
+  assert_eq!(parse_value("hsl(-120 100 50 / 1)"), rgba(0, 0, 255, 255));
+  assert_eq!(parse_value("hsl(0 0% 100%)"), rgba(255, 255, 255, 255));
+  // Assert that hwb() mixes a hue with white and black, and scales them down to a gray
+  assert_eq!(parse_value("hwb(0 0% 0%)"), rgba(255, 0, 0, 255));
+  assert_eq!(parse_value("hwb(120 20% 20% / 50%)"), rgba(51, 204, 51, 128));
+  assert_eq!(parse_value("hwb(0 60% 60%)"), rgba(128, 128, 128, 255));
+  // Assert that invalid color functions are errors
+  assert!(parse_value("rgb(255, 0%, 0)").is_err());
+  assert!(parse_value("rgb(255, 0)").is_err());
+  assert!(parse_value("rgb(255 0 0 0)").is_err());
+  assert!(parse_value("hsl(120, 100, 50)").is_err());
+  assert!(parse_value("hsl(50% 100% 50%)").is_err());
+  assert!(parse_value("hwb(0, 0%, 0%)").is_err());
+  assert!(parse_value("lab(50 0 0)").is_err());
 }
 
 // Test the method parse_value of the CSSParser struct implementation
 #[test]
 fn test_parse_value() {
-  let mut css_parser: CSSParser = CSSParser::new(
-    11,
-    ".container{width:100px;background:#A3E4D7;}".to_string(),
-  );
+  let mut css_parser: CSSParser = CSSParser::new("width 100px #A3E4D7".to_string());
   let keyword: css::Value = css::Value::Keyword(String::from("width"));
   let unit: css::Value = css::Value::Length(100.0, css::Unit::Px);
   let color: css::Value = css::Value::ColorValue(css::Color::new(163, 228, 215, 255));
@@ -83,53 +135,156 @@ fn test_parse_value() {
   // Assert that the parse_value method correctly parses the keyword "width"
   assert_eq!(css_parser.parse_value().unwrap(), keyword);
 
-  css_parser.text_parser.increment_position(1);
+  css_parser.consume_whitespace();
   // Assert that the parse_value method correctly parses the value "100" with unit "px"
   assert_eq!(css_parser.parse_value().unwrap(), unit);
 
-  css_parser.text_parser.increment_position(12);
+  css_parser.consume_whitespace();
   // Assert that the parse_color method correctly parses the color "A3E4D7"
   assert_eq!(css_parser.parse_value().unwrap(), color);
+
+  let parse_value = |input: &str| -> css::Value {
+    CSSParser::new(input.to_string()).parse_value().unwrap()
+  };
+  // Assert that named colors are parsed as colors, ignoring case
+  assert_eq!(
+    parse_value("RebeccaPurple"),
+    css::Value::ColorValue(css::Color::new(102, 51, 153, 255))
+  );
+  assert_eq!(parse_value("grey"), css::Value::ColorValue(css::Color::new(128, 128, 128, 255)));
+  assert_eq!(parse_value("transparent"), css::Value::ColorValue(css::Color::new(0, 0, 0, 0)));
+  // Assert that 'currentColor' is kept as a keyword for the computed values
+  assert_eq!(parse_value("currentColor"), css::Value::Keyword(String::from("currentcolor")));
+  assert_eq!(parse_value("blocks"), css::Value::Keyword(String::from("blocks")));
+  // Assert that strings are values, and that other tokens are not
+  assert_eq!(parse_value("'a\\'b'"), css::Value::String(String::from("a'b")));
+  assert!(CSSParser::new("@".to_string()).parse_value().is_err());
+  assert!(CSSParser::new(String::new()).parse_value().is_err());
 }
 
 // Test the method parse_declaration of the CSSParser struct implementation
 #[test]
 fn test_parse_declaration() {
-  let mut css_parser: CSSParser = CSSParser::new(
-    11,
-    ".container{width:100px;background:#A3E4D7;}".to_string(),
-  );
   let unit: css::Value = css::Value::Length(100.0, css::Unit::Px);
-  let declaration: css::Declaration = css::Declaration::new("width".to_string(), unit);
+  let width: css::Declaration = css::Declaration::new("width".to_string(), unit);
 
   // Assert that the parse_declaration method correctly parses the declaration "width: 100px;"
-  assert_eq!(css_parser.parse_declaration().unwrap(), declaration);
+  assert_eq!(declaration("width:100px;background:#A3E4D7;"), Ok(width));
+
+  let value: css::Value = css::Value::List(vec![
+    css::Value::Length(2.0, css::Unit::Px),
+    css::Value::Keyword("solid".to_string()),
+    css::Value::ColorValue(css::Color::new(51, 51, 51, 255)),
+  ]);
+  // Assert that the parse_declaration method parses a value of several components as a list
+  assert_eq!(
+    declaration("border: 2px solid #333;"),
+    Ok(css::Declaration::new("border".to_string(), value))
+  );
+  // Assert that declarations with '!important' or a value that doesn't parse are errors
+  assert!(declaration("width: 1px !important").is_err());
+  assert!(declaration("width: 1px )").is_err());
+}
+
+// Test the method parse_values of the CSSParser struct implementation
+#[test]
+fn test_parse_values() {
+  let parse_values = |input: &str| -> Result<css::Value, String> {
+    CSSParser::new(input.to_string()).parse_values()
+  };
+  let keyword = |keyword: &str| -> css::Value { css::Value::Keyword(keyword.to_string()) };
+  let px = |length: f32| -> css::Value { css::Value::Length(length, css::Unit::Px) };
+
+  // Assert that a single value is not a list
+  assert_eq!(parse_values("0"), Ok(css::Value::Number(0.0)));
+  // Assert that values separated by whitespace, '/' and ',' are parsed as a list
+  assert_eq!(
+    parse_values("0 auto "),
+    Ok(css::Value::List(vec![css::Value::Number(0.0), keyword("auto")]))
+  );
+  assert_eq!(
+    parse_values("bold 12px/30px 'Times New Roman',serif"),
+    Ok(css::Value::List(vec![
+      keyword("bold"),
+      px(12.0),
+      keyword("/"),
+      px(30.0),
+      css::Value::String("Times New Roman".to_string()),
+      keyword(","),
+      keyword("serif"),
+    ]))
+  );
+  // Assert that an empty value, an unknown unit, or an unexpected character are errors
+  assert!(parse_values(" ").is_err());
+  assert!(parse_values("0 5furlongs").is_err());
+  assert!(parse_values("1px @").is_err());
+  assert!(parse_values("1px (2px)").is_err());
+}
+
+// Test the methods parse_content and parse_counters of the CSSParser struct implementation
+#[test]
+fn test_parse_content() {
+  let value = |name: &str, value: css::Value| -> Result<css::Declaration, String> {
+    Ok(css::Declaration::new(name.to_string(), value))
+  };
+
+  // Assert that 'content' takes a list of strings, 'attr()' and 'counter()'
+  assert_eq!(
+    declaration("content: \"(\" attr(href) ')' counter( item );"),
+    value(
+      "content",
+      css::Value::Content(vec![
+        css::ContentItem::String("(".to_string()),
+        css::ContentItem::Attr("href".to_string()),
+        css::ContentItem::String(")".to_string()),
+        css::ContentItem::Counter("item".to_string()),
+      ])
+    )
+  );
+  assert_eq!(
+    declaration("content: none;"),
+    value("content", css::Value::Keyword("none".to_string()))
+  );
+  // Assert that the counter properties take names and optional integers
+  assert_eq!(
+    declaration("counter-reset: section chapter -2;"),
+    value(
+      "counter-reset",
+      css::Value::Counters(vec![("section".to_string(), 0), ("chapter".to_string(), -2)])
+    )
+  );
+  assert_eq!(
+    declaration("counter-increment: item;"),
+    value("counter-increment", css::Value::Counters(vec![("item".to_string(), 1)]))
+  );
+  // Assert that invalid values are errors
+  assert!(declaration("content: ;").is_err());
+  assert!(declaration("content: attr();").is_err());
+  assert!(declaration("content: url(a.png);").is_err());
+  assert!(declaration("counter-reset: 3;").is_err());
 }
 
 // Test the method parse_declarations of the CSSParser struct implementation
 #[test]
 fn test_parse_declarations() {
-  let mut css_parser: CSSParser = CSSParser::new(
-    10,
-    ".container{width:100px;background:#A3E4D7;}".to_string(),
-  );
+  let css_parser: CSSParser = CSSParser::new(String::new());
+  let block: Vec<ComponentValue> =
+    syntax::parse_component_values("width:100px;height:1furlong;background:#A3E4D7;");
   let unit: css::Value = css::Value::Length(100.0, css::Unit::Px);
   let declaration_1: css::Declaration = css::Declaration::new("width".to_string(), unit);
   let color: css::Value = css::Value::ColorValue(css::Color::new(163, 228, 215, 255));
   let declaration_2: css::Declaration = css::Declaration::new("background".to_string(), color);
 
-  // Assert that the parse_declarations method correctly parses the declarations "{width: 100px;background:#A3E4D7;}"
-  assert_eq!(
-    css_parser.parse_declarations().unwrap(),
-    vec![declaration_1, declaration_2]
-  );
+  // Assert that the parse_declarations method correctly parses the declarations
+  // "width:100px;background:#A3E4D7;", dropping the invalid one between them
+  assert_eq!(css_parser.parse_declarations(&block), vec![declaration_1, declaration_2]);
 }
 
 // Test the method parse_simple_selector of the CSSParser struct implementation
 #[test]
 fn test_parse_simple_selector() {
   let mut css_parser: CSSParser =
-    CSSParser::new(0, "div#main-container.class1.class2{}".to_string());
+    CSSParser::new("div#main-container.class1.class2".to_string());
   let simple_selector: css::SimpleSelector = css::SimpleSelector::new(
     Some("div".to_string()),
     Some("main-container".to_string()),
@@ -137,16 +292,197 @@ fn test_parse_simple_selector() {
   );
 
   // Assert that the parse_declarations method correctly parses the simple selector "div#main-container.class1.class2"
-  assert_eq!(css_parser.parse_simple_selector(), simple_selector);
+  assert_eq!(css_parser.parse_simple_selector(), Ok(simple_selector));
+}
+
+// Test the method parse_attribute_selector of the CSSParser struct implementation
+#[test]
+fn test_parse_attribute_selector() {
+  let attribute = |source: &str| -> Result<css::AttributeSelector, String> {
+    CSSParser::new(source.to_string()).parse_attribute_selector()
+  };
+  let expected = |name: &str, operator: css::AttributeOperator, value: &str| {
+    css::AttributeSelector::new(
+      name.to_string(),
+      operator,
+      value.to_string(),
+      css::AttributeCase::Default,
+    )
+  };
+
+  // Assert that every operator is parsed, with unquoted or quoted values and optional whitespace
+  assert_eq!(
+    attribute("[disabled]"),
+    Ok(expected("disabled", css::AttributeOperator::Exists, ""))
+  );
+  assert_eq!(
+    attribute("[ type = checkbox ]"),
+    Ok(expected("type", css::AttributeOperator::Equals, "checkbox"))
+  );
+  assert_eq!(
+    attribute("[data-state~=open]"),
+    Ok(expected("data-state", css::AttributeOperator::Includes, "open"))
+  );
+  assert_eq!(
+    attribute("[lang|=en]"),
+    Ok(expected("lang", css::AttributeOperator::DashMatch, "en"))
+  );
+  assert_eq!(
+    attribute("[href^=\"http\"]"),
+    Ok(expected("href", css::AttributeOperator::Prefix, "http"))
+  );
+  assert_eq!(
+    attribute("[src$='.png']"),
+    Ok(expected("src", css::AttributeOperator::Suffix, ".png"))
+  );
+  assert_eq!(
+    attribute("[title*=\"a b\"]"),
+    Ok(expected("title", css::AttributeOperator::Substring, "a b"))
+  );
+  // Assert that the case flags are parsed
+  let insensitive: css::AttributeSelector = css::AttributeSelector::new(
+    "type".to_string(),
+    css::AttributeOperator::Equals,
+    "A".to_string(),
+    css::AttributeCase::Insensitive,
+  );
+  assert_eq!(attribute("[type=A i]"), Ok(insensitive));
+  assert_eq!(attribute("[type='a' S]").unwrap().case(), css::AttributeCase::Sensitive);
+  // Assert that malformed attribute selectors are errors
+  assert!(attribute("[]").is_err());
+  assert!(attribute("[type!=a]").is_err());
+  assert!(attribute("[type~a]").is_err());
+  assert!(attribute("[type=a x]").is_err());
+  assert!(attribute("type").is_err());
+  // Assert that the end of the input closes an unterminated attribute selector, as it closes
+  // any block
+  assert_eq!(
+    attribute("[type=a"),
+    Ok(expected("type", css::AttributeOperator::Equals, "a"))
+  );
+  // Assert that attribute selectors are part of simple selectors and count as classes
+  let selectors: Vec<css::Selector> =
+    CSSParser::parse_selector_list("input[type=checkbox][checked]".to_string()).unwrap();
+  assert_eq!(selectors[0].specificity(), (0, 2, 1));
+}
+
+// Test the method parse_nth of the CSSParser struct implementation
+#[test]
+fn test_parse_nth() {
+  let nth = |source: &str| -> Result<(i32, i32), String> {
+    let mut css_parser: CSSParser = CSSParser::new(source.to_string());
+    let nth: css::Nth = css_parser.parse_nth()?;
+    match css_parser.next() {
+      Some(value) => Err(format!("Unexpected '{}'", value)),
+      None => Ok((nth.a(), nth.b())),
+    }
+  };
+
+  // Assert that keywords, integers and every form of An+B are parsed
+  assert_eq!(nth("odd"), Ok((2, 1)));
+  assert_eq!(nth("EVEN"), Ok((2, 0)));
+  assert_eq!(nth("3"), Ok((0, 3)));
+  assert_eq!(nth("-2"), Ok((0, -2)));
+  assert_eq!(nth("n"), Ok((1, 0)));
+  assert_eq!(nth("+n"), Ok((1, 0)));
+  assert_eq!(nth("-n+3"), Ok((-1, 3)));
+  assert_eq!(nth("2n+1"), Ok((2, 1)));
+  assert_eq!(nth("+3N - 2"), Ok((3, -2)));
+  assert_eq!(nth("-10n"), Ok((-10, 0)));
+  assert_eq!(nth("2n-1"), Ok((2, -1)));
+  assert_eq!(nth("-n- 4"), Ok((-1, -4)));
+  assert_eq!(nth("n -1"), Ok((1, -1)));
+  // Assert that malformed arguments are errors
+  assert!(nth("").is_err());
+  assert!(nth("- n").is_err());
+  assert!(nth("++n").is_err());
+  assert!(nth("2n+").is_err());
+  assert!(nth("2n 1").is_err());
+  assert!(nth("2.5n").is_err());
+  assert!(nth("+ n").is_err());
+  assert!(nth("n-a").is_err());
+  // Assert which positions An+B matches
+  let positions = |a: i32, b: i32| -> Vec<usize> {
+    (1..=7).filter(|position: &usize| css::Nth::new(a, b).matches(*position)).collect()
+  };
+  assert_eq!(positions(2, 1), vec![1, 3, 5, 7]);
+  assert_eq!(positions(0, 3), vec![3]);
+  assert_eq!(positions(-1, 3), vec![1, 2, 3]);
+  assert_eq!(positions(3, -2), vec![1, 4, 7]);
+  assert_eq!(positions(0, -1), Vec::<usize>::new());
+}
+
+// Test the method parse_pseudo_class of the CSSParser struct implementation
+#[test]
+fn test_parse_pseudo_class() {
+  let pseudo_class = |source: &str| -> Result<css::PseudoClass, String> {
+    CSSParser::new(source.to_string()).parse_pseudo_class()
+  };
+  let simple = |name: &str| -> css::Selector {
+    css::Selector::Simple(css::SimpleSelector::new(Some(name.to_string()), None, vec![]))
+  };
+
+  // Assert that structural pseudo-classes are parsed, ignoring case
+  assert_eq!(pseudo_class(":First-Child"), Ok(css::PseudoClass::FirstChild));
+  assert_eq!(pseudo_class(":empty"), Ok(css::PseudoClass::Empty));
+  assert_eq!(
+    pseudo_class(":nth-child( 2n+1 )"),
+    Ok(css::PseudoClass::NthChild(css::Nth::new(2, 1), vec![]))
+  );
+  assert_eq!(
+    pseudo_class(":nth-last-child(odd of p, div)"),
+    Ok(css::PseudoClass::NthLastChild(css::Nth::new(2, 1), vec![simple("p"), simple("div")]))
+  );
+  assert_eq!(
+    pseudo_class(":nth-of-type(3)"),
+    Ok(css::PseudoClass::NthOfType(css::Nth::new(0, 3)))
+  );
+  // Assert that the dynamic pseudo-classes are parsed
+  assert_eq!(pseudo_class(":hover"), Ok(css::PseudoClass::Hover));
+  assert_eq!(pseudo_class(":Focus-Within"), Ok(css::PseudoClass::FocusWithin));
+  assert_eq!(pseudo_class(":visited"), Ok(css::PseudoClass::Visited));
+  // Assert that logical pseudo-classes take selector lists, and ':has()' relative selectors
+  assert_eq!(pseudo_class(":not(p)"), Ok(css::PseudoClass::Not(vec![simple("p")])));
+  let child_of_anchor: css::Selector = css::Selector::Complex(
+    Box::new(css::Selector::Anchor),
+    css::Combinator::Child,
+    css::SimpleSelector::new(Some("img".to_string()), None, vec![]),
+  );
+  let descendant_of_anchor: css::Selector = css::Selector::Complex(
+    Box::new(css::Selector::Anchor),
+    css::Combinator::Descendant,
+    css::SimpleSelector::new(Some("p".to_string()), None, vec![]),
+  );
+  assert_eq!(
+    pseudo_class(":has(> img, p)"),
+    Ok(css::PseudoClass::Has(vec![child_of_anchor, descendant_of_anchor]))
+  );
+  // Assert that unknown and malformed pseudo-classes are errors
+  assert!(pseudo_class(":unknown").is_err());
+  assert!(pseudo_class(":first-child()").is_err());
+  assert!(pseudo_class(":is()").is_err());
+  assert!(pseudo_class(":has(> )").is_err());
+  assert!(pseudo_class(": hover").is_err());
+  assert!(CSSParser::parse_selector_list("p:first-child)".to_string()).is_err());
+  // Assert that the end of the input closes an unterminated argument
+  assert_eq!(pseudo_class(":not(p"), Ok(css::PseudoClass::Not(vec![simple("p")])));
+  // Assert the specificity of pseudo-classes
+  let specificity = |source: &str| -> css::Specificity {
+    CSSParser::parse_selector_list(source.to_string()).unwrap()[0].specificity()
+  };
+  assert_eq!(specificity("li:first-child"), (0, 1, 1));
+  assert_eq!(specificity(":not(#a, .b) p"), (1, 0, 1));
+  assert_eq!(specificity(":is(ul li, .x)"), (0, 1, 0));
+  assert_eq!(specificity("p:where(#a .b)"), (0, 0, 1));
+  assert_eq!(specificity(":has(> img.icon)"), (0, 1, 1));
+  assert_eq!(specificity(":nth-child(2 of .item, #main)"), (1, 1, 0));
 }
 
 // Test the method parse_selectors of the CSSParser struct implementation
 #[test]
 fn test_parse_selectors() {
-  let mut css_parser: CSSParser = CSSParser::new(
-    0,
-    "div#main-container.class1.class2,h1#main-title.class3.class4{}".to_string(),
-  );
+  let mut css_parser: CSSParser =
+    CSSParser::new("div#main-container.class1.class2,h1#main-title.class3.class4".to_string());
   let simple_selector_1: css::SimpleSelector = css::SimpleSelector::new(
     Some("div".to_string()),
     Some("main-container".to_string()),
@@ -167,10 +503,105 @@ fn test_parse_selectors() {
   );
 }
 
+// Test the function parse_selector_list of the CSSParser struct implementation
+#[test]
+fn test_parse_selector_list() {
+  let selector: css::Selector =
+    css::Selector::Simple(css::SimpleSelector::new(Some("p".to_string()), None, vec![]));
+
+  // Assert that a standalone selector list ends at the end of the input
+  assert_eq!(
+    CSSParser::parse_selector_list(" p ".to_string()).unwrap(),
+    vec![selector]
+  );
+  // Assert that empty selectors and trailing characters are errors
+  assert!(CSSParser::parse_selector_list(String::new()).is_err());
+  assert!(CSSParser::parse_selector_list("p,".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p {}".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p >".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p > > a".to_string()).is_err());
+}
+
+// Test complex selectors parsed by the function parse_selector_list
+#[test]
+fn test_parse_complex_selector() {
+  let simple = |name: &str| -> css::SimpleSelector {
+    css::SimpleSelector::new(Some(name.to_string()), None, vec![])
+  };
+  let nav: css::Selector = css::Selector::Simple(simple("nav"));
+  let nav_ul: css::Selector =
+    css::Selector::Complex(Box::new(nav), css::Combinator::Child, simple("ul"));
+  let nav_ul_li: css::Selector =
+    css::Selector::Complex(Box::new(nav_ul), css::Combinator::Descendant, simple("li"));
+  let expected: css::Selector =
+    css::Selector::Complex(Box::new(nav_ul_li), css::Combinator::NextSibling, simple("li"));
+
+  // Assert that combinators group from the left, with or without whitespace around them
+  assert_eq!(
+    CSSParser::parse_selector_list("nav>ul  li\n+ li".to_string()).unwrap(),
+    vec![expected.clone()]
+  );
+  assert_eq!(
+    CSSParser::parse_selector_list("nav > ul li+li ".to_string()).unwrap(),
+    vec![expected.clone()]
+  );
+  // Assert that the specificity of a complex selector adds up its simple selectors
+  assert_eq!(expected.specificity(), (0, 0, 4));
+  let selectors: Vec<css::Selector> =
+    CSSParser::parse_selector_list("h1 ~ p, #main .a, a".to_string()).unwrap();
+  let specificities: Vec<css::Specificity> =
+    selectors.iter().map(|selector: &css::Selector| selector.specificity()).collect();
+  assert_eq!(specificities, vec![(1, 1, 0), (0, 0, 2), (0, 0, 1)]);
+  assert_eq!(
+    selectors[1],
+    css::Selector::Complex(
+      Box::new(css::Selector::Simple(simple("h1"))),
+      css::Combinator::SubsequentSibling,
+      simple("p")
+    )
+  );
+}
+
+// Test pseudo-elements parsed by the function parse_selector_list
+#[test]
+fn test_parse_pseudo_element() {
+  let mut before: css::SimpleSelector =
+    css::SimpleSelector::new(Some("p".to_string()), None, vec!["note".to_string()]);
+  before.set_pseudo_element(Some(css::PseudoElement::Before));
+  let mut after: css::SimpleSelector = css::SimpleSelector::new(None, None, vec![]);
+  after.set_pseudo_element(Some(css::PseudoElement::After));
+
+  // Assert that pseudo-elements are parsed with two colons, or one for the legacy ones
+  assert_eq!(
+    CSSParser::parse_selector_list("p.note::Before".to_string()),
+    Ok(vec![css::Selector::Simple(before.clone())])
+  );
+  assert_eq!(
+    CSSParser::parse_selector_list("p.note:before".to_string()),
+    Ok(vec![css::Selector::Simple(before)])
+  );
+  let selectors: Vec<css::Selector> =
+    CSSParser::parse_selector_list("li > ::after".to_string()).unwrap();
+  assert_eq!(selectors[0].pseudo_element(), Some(css::PseudoElement::After));
+  // Assert that a pseudo-element counts as a type selector
+  assert_eq!(selectors[0].specificity(), (0, 0, 2));
+  // Assert that ':before-x' is a pseudo-class, not a pseudo-element
+  assert!(CSSParser::parse_selector_list(":before-x".to_string()).is_err());
+  // Assert that a pseudo-element must end the selector, outside of pseudo-classes
+  assert!(CSSParser::parse_selector_list("p::before span".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p::before.note".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p:not(::after)".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("p::first-line".to_string()).is_err());
+}
+
 // Test the method parse_rule of the CSSParser struct implementation
 #[test]
 fn test_parse_rule() {
-  let mut css_parser: CSSParser = CSSParser::new(0, ".class1{width:100px;}".to_string());
+  let css_parser: CSSParser = CSSParser::new(String::new());
+  let rules: Vec<syntax::Rule> = syntax::parse_stylesheet(".class1{width:100px;}");
+  let syntax::Rule::Qualified(qualified_rule) = &rules[0] else {
+    panic!("Expected a qualified rule");
+  };
   // Selector
   let simple_selector: css::SimpleSelector =
     css::SimpleSelector::new(None, None, vec!["class1".to_string()]);
@@ -182,15 +613,15 @@ fn test_parse_rule() {
   let rule: css::Rule = css::Rule::new(vec![selector], vec![declaration]);
 
   // Assert that the parse_rule method correctly parses the selector and its declaration ".class1{width:100px;}"
-  assert_eq!(css_parser.parse_rule().unwrap(), rule);
+  assert_eq!(css_parser.parse_rule(qualified_rule).unwrap(), rule);
 }
 
 // Test the method parse_rules of the CSSParser struct implementation
 #[test]
 fn test_parse_rules() {
-  let mut css_parser: CSSParser = CSSParser::new(
-    0,
-    ".class1{width:100px;}.class2{background:#A3E4D7;}".to_string(),
+  let mut css_parser: CSSParser = CSSParser::new(String::new());
+  let rules: Vec<syntax::Rule> = syntax::parse_stylesheet(
+    ".class1{width:100px;} @media print { p {} } .class2{background:#A3E4D7;}",
   );
   // Selectors
   let simple_selector_1: css::SimpleSelector =
@@ -209,7 +640,8 @@ fn test_parse_rules() {
   let rule_2: css::Rule = css::Rule::new(vec![selector_2], vec![declaration_2]);
 
   // Assert that the parse_rules method correctly parses the selectors and their declaration ".class1{width:100px;}.class2{background:#A3E4D7;}"
-  assert_eq!(css_parser.parse_rules().unwrap(), vec![rule_1, rule_2]);
+  // The unsupported @media rule between them is dropped
+  assert_eq!(css_parser.parse_rules(&rules), vec![rule_1, rule_2]);
 }
 
 // Test the method parse of the CSSParser struct implementation
@@ -247,6 +679,57 @@ fn test_parse() {
   );
 }
 
+// Test that the function parse of the CSSParser struct implementation skips an @charset rule
+#[test]
+fn test_parse_charset_rule() {
+  let stylesheet: Stylesheet =
+    CSSParser::parse("@charset \"utf-8\";\np { width: 10px; }".to_string()).unwrap();
+
+  // Assert that the rule after @charset is kept
+  assert_eq!(stylesheet.rules().len(), 1);
+}
+
+// Test @namespace rules and namespace prefixes parsed by the function parse of the CSSParser
+// struct implementation
+#[test]
+fn test_parse_namespace_rule() {
+  let stylesheet: Stylesheet = CSSParser::parse(
+    "@namespace url(http://www.w3.org/1999/xhtml);\n\
+    @namespace svg \"http://www.w3.org/2000/svg\";\n\
+    svg|rect, *|a, |p, .b { width: 1px; } math|mi { width: 2px; }"
+      .to_string(),
+  )
+  .unwrap();
+  let namespaces: Vec<Option<&str>> = stylesheet.rules()[0]
+    .selectors()
+    .iter()
+    .map(|selector: &css::Selector| {
+      let css::Selector::Simple(simple) = selector else {
+        panic!("Expected a simple selector");
+      };
+      simple.namespace().as_deref()
+    })
+    .collect();
+
+  // Assert that selectors without a prefix get the default namespace, that prefixes are
+  // resolved, and that '*|' matches any namespace (selectors are sorted by specificity)
+  assert_eq!(
+    namespaces,
+    vec![
+      Some("http://www.w3.org/1999/xhtml"),
+      Some("http://www.w3.org/2000/svg"),
+      None,
+      Some(""),
+    ]
+  );
+  // Assert that a rule with an undeclared prefix is discarded
+  assert_eq!(stylesheet.rules().len(), 1);
+  // Assert that a standalone selector list can't use prefixes, which it can't declare
+  assert!(CSSParser::parse_selector_list("*|rect".to_string()).is_ok());
+  assert!(CSSParser::parse_selector_list("svg|rect".to_string()).is_err());
+  assert!(CSSParser::parse_selector_list("svg|".to_string()).is_err());
+}
+
 // Test recovery: a declaration with an unknown unit is discarded; valid ones are kept
 #[test]
 fn test_recovery_invalid_declaration() {
@@ -291,3 +774,30 @@ fn test_recovery_invalid_rule() {
   assert_eq!(declarations_2[0].name(), "height");
   assert_eq!(declarations_2[0].value(), &css::Value::Length(50.0, css::Unit::Px));
 }
+
+// Test that the function parse of the CSSParser struct implementation ignores comments, and
+// reads escapes and strings as the tokenizer does
+#[test]
+fn test_parse_comments() {
+  let stylesheet: Stylesheet = CSSParser::parse(
+    "/* header */ p/**/.a { width: /* } */ 1px; } /* { */ .b\\:c { content: '/* x */'; }"
+      .to_string(),
+  )
+  .unwrap();
+
+  // Assert that a comment anywhere doesn't break the rule after it
+  assert_eq!(stylesheet.rules().len(), 2);
+  assert_eq!(
+    stylesheet.rules()[0].declarations(),
+    &vec![css::Declaration::new("width".to_string(), css::Value::Length(1.0, css::Unit::Px))]
+  );
+  // Assert that escaped characters are part of the identifier, and comments in strings are kept
+  assert_eq!(
+    stylesheet.rules()[1].selectors(),
+    &vec![css::Selector::Simple(css::SimpleSelector::new(None, None, vec!["b:c".to_string()]))]
+  );
+  assert_eq!(
+    stylesheet.rules()[1].declarations()[0].value(),
+    &css::Value::Content(vec![css::ContentItem::String("/* x */".to_string())])
+  );
+}
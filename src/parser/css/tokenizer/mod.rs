@@ -0,0 +1,470 @@
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+// Whether a hash token could be an id selector: 'Id' when its value is an identifier, e.g. '#main',
+// and 'Unrestricted' otherwise, e.g. '#0f0'
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HashType {
+  Id,
+  Unrestricted,
+}
+
+// The value of a numeric token, and whether it was written as an integer and with a sign, which
+// the An+B microsyntax depends on
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Number {
+  value: f32,
+  integer: bool,
+  signed: bool,
+}
+
+impl Number {
+  pub fn new(value: f32, integer: bool, signed: bool) -> Self {
+    Self {
+      value,
+      integer,
+      signed,
+    }
+  }
+
+  pub fn value(&self) -> f32 {
+    self.value
+  }
+
+  pub fn is_integer(&self) -> bool {
+    self.integer
+  }
+
+  pub fn is_signed(&self) -> bool {
+    self.signed
+  }
+}
+
+// https://drafts.csswg.org/css-syntax/#tokenization
+#[derive(Clone, PartialEq, Debug)]
+pub enum Token {
+  Ident(String),
+  // The name of a function, which the '(' that follows it is part of, e.g. 'rgb('
+  Function(String),
+  AtKeyword(String),
+  Hash(String, HashType),
+  String(String),
+  // A string with an unescaped newline, which is dropped
+  BadString,
+  // An unquoted 'url(...)'. A quoted URL is a 'url(' function with a string argument
+  Url(String),
+  BadUrl,
+  Delim(char),
+  Number(Number),
+  Percentage(Number),
+  Dimension(Number, String),
+  Whitespace,
+  // '<!--' and '-->', which are ignored around the rules of a stylesheet
+  Cdo,
+  Cdc,
+  Colon,
+  Semicolon,
+  Comma,
+  OpenSquare,
+  CloseSquare,
+  OpenParen,
+  CloseParen,
+  OpenCurly,
+  CloseCurly,
+}
+
+impl fmt::Display for Token {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Token::Ident(name) => write!(f, "{}", name),
+      Token::Function(name) => write!(f, "{}(", name),
+      Token::AtKeyword(name) => write!(f, "@{}", name),
+      Token::Hash(name, _) => write!(f, "#{}", name),
+      Token::String(string) => write!(f, "{:?}", string),
+      Token::BadString => write!(f, "\""),
+      Token::Url(url) => write!(f, "url({})", url),
+      Token::BadUrl => write!(f, "url("),
+      Token::Delim(c) => write!(f, "{}", c),
+      Token::Number(number) => write!(f, "{}", number.value),
+      Token::Percentage(number) => write!(f, "{}%", number.value),
+      Token::Dimension(number, unit) => write!(f, "{}{}", number.value, unit),
+      Token::Whitespace => write!(f, " "),
+      Token::Cdo => write!(f, "<!--"),
+      Token::Cdc => write!(f, "-->"),
+      Token::Colon => write!(f, ":"),
+      Token::Semicolon => write!(f, ";"),
+      Token::Comma => write!(f, ","),
+      Token::OpenSquare => write!(f, "["),
+      Token::CloseSquare => write!(f, "]"),
+      Token::OpenParen => write!(f, "("),
+      Token::CloseParen => write!(f, ")"),
+      Token::OpenCurly => write!(f, "{{"),
+      Token::CloseCurly => write!(f, "}}"),
+    }
+  }
+}
+
+// https://drafts.csswg.org/css-syntax/#name-code-point
+fn is_name_start(c: char) -> bool {
+  c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_name(c: char) -> bool {
+  is_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+// Would these characters start a valid escape, a '\' that isn't followed by a newline?
+fn is_valid_escape(first: Option<char>, second: Option<char>) -> bool {
+  first == Some('\\') && second != Some('\n')
+}
+
+// Characters that can't appear in an unquoted URL
+// https://drafts.csswg.org/css-syntax/#non-printable-code-point
+fn is_non_printable(c: char) -> bool {
+  matches!(c, '\u{0}'..='\u{8}' | '\u{B}' | '\u{E}'..='\u{1F}' | '\u{7F}')
+}
+
+pub struct Tokenizer {
+  input: Vec<char>,
+  position: usize,
+}
+
+impl Tokenizer {
+  // Preprocess the source: newlines are normalized to '\n', and NULL characters are replaced
+  // https://drafts.csswg.org/css-syntax/#input-preprocessing
+  pub fn new(source: &str) -> Self {
+    let source: String = source
+      .replace("\r\n", "\n")
+      .replace(['\r', '\u{C}'], "\n")
+      .replace('\0', "\u{FFFD}");
+    Self {
+      input: source.chars().collect(),
+      position: 0,
+    }
+  }
+
+  // Split a whole source into tokens
+  pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokenizer: Tokenizer = Tokenizer::new(source);
+    std::iter::from_fn(|| tokenizer.next_token()).collect()
+  }
+
+  fn peek(&self, offset: usize) -> Option<char> {
+    self.input.get(self.position + offset).copied()
+  }
+
+  fn consume(&mut self) -> Option<char> {
+    let c: Option<char> = self.peek(0);
+    if c.is_some() {
+      self.position += 1;
+    }
+    c
+  }
+
+  fn starts_with(&self, s: &str) -> bool {
+    s.chars()
+      .enumerate()
+      .all(|(offset, c): (usize, char)| self.peek(offset) == Some(c))
+  }
+
+  fn consume_whitespace(&mut self) {
+    while self.peek(0).is_some_and(|c: char| c.is_ascii_whitespace()) {
+      self.position += 1;
+    }
+  }
+
+  // Would the characters at 'offset' start an identifier? e.g. 'a', '-a', '--' or '\66', but
+  // not '-5'
+  // https://drafts.csswg.org/css-syntax/#would-start-an-identifier
+  fn starts_identifier(&self, offset: usize) -> bool {
+    match self.peek(offset) {
+      Some('-') => {
+        self
+          .peek(offset + 1)
+          .is_some_and(|c: char| is_name_start(c) || c == '-')
+          || is_valid_escape(self.peek(offset + 1), self.peek(offset + 2))
+      }
+      Some(c) if is_name_start(c) => true,
+      first => is_valid_escape(first, self.peek(offset + 1)),
+    }
+  }
+
+  // Would the next characters start a number? e.g. '5', '-5', '+.5' or '.5', but not '-webkit'
+  // https://drafts.csswg.org/css-syntax/#starts-with-a-number
+  fn starts_number(&self) -> bool {
+    let digit_at =
+      |offset: usize| -> bool { self.peek(offset).is_some_and(|c: char| c.is_ascii_digit()) };
+    match self.peek(0) {
+      Some('+' | '-') => digit_at(1) || (self.peek(1) == Some('.') && digit_at(2)),
+      Some('.') => digit_at(1),
+      Some(c) => c.is_ascii_digit(),
+      None => false,
+    }
+  }
+
+  // https://drafts.csswg.org/css-syntax/#consume-token
+  fn next_token(&mut self) -> Option<Token> {
+    self.consume_comments();
+    let c: char = self.peek(0)?;
+    let token: Token = match c {
+      _ if c.is_ascii_whitespace() => {
+        self.consume_whitespace();
+        Token::Whitespace
+      }
+      '"' | '\'' => {
+        self.consume();
+        self.consume_string(c)
+      }
+      '#' if self.peek(1).is_some_and(is_name) || is_valid_escape(self.peek(1), self.peek(2)) => {
+        self.consume();
+        let hash_type: HashType = if self.starts_identifier(0) {
+          HashType::Id
+        } else {
+          HashType::Unrestricted
+        };
+        Token::Hash(self.consume_name(), hash_type)
+      }
+      _ if self.starts_number() => self.consume_numeric(),
+      '-' if self.starts_with("-->") => {
+        self.position += 3;
+        Token::Cdc
+      }
+      '<' if self.starts_with("<!--") => {
+        self.position += 4;
+        Token::Cdo
+      }
+      '@' if self.starts_identifier(1) => {
+        self.consume();
+        Token::AtKeyword(self.consume_name())
+      }
+      _ if self.starts_identifier(0) => self.consume_ident_like(),
+      _ => {
+        self.consume();
+        match c {
+          '(' => Token::OpenParen,
+          ')' => Token::CloseParen,
+          '[' => Token::OpenSquare,
+          ']' => Token::CloseSquare,
+          '{' => Token::OpenCurly,
+          '}' => Token::CloseCurly,
+          ':' => Token::Colon,
+          ';' => Token::Semicolon,
+          ',' => Token::Comma,
+          // Including a '\' that doesn't start an escape
+          _ => Token::Delim(c),
+        }
+      }
+    };
+    Some(token)
+  }
+
+  // Skip comments, '/* ... */'. An unterminated comment ends at the end of the input
+  // https://drafts.csswg.org/css-syntax/#consume-comments
+  fn consume_comments(&mut self) {
+    while self.starts_with("/*") {
+      self.position += 2;
+      while self.peek(0).is_some() && !self.starts_with("*/") {
+        self.position += 1;
+      }
+      self.position = (self.position + 2).min(self.input.len());
+    }
+  }
+
+  // Consume an escape after its '\': up to 6 hex digits and an optional whitespace, or any
+  // other character, e.g. '\26 ' or '\&'. Invalid code points are replaced
+  // https://drafts.csswg.org/css-syntax/#consume-escaped-code-point
+  fn consume_escape(&mut self) -> char {
+    match self.consume() {
+      Some(c) if c.is_ascii_hexdigit() => {
+        let mut hex: String = c.to_string();
+        while hex.len() < 6 && self.peek(0).is_some_and(|c: char| c.is_ascii_hexdigit()) {
+          hex.extend(self.consume());
+        }
+        if self.peek(0).is_some_and(|c: char| c.is_ascii_whitespace()) {
+          self.consume();
+        }
+        u32::from_str_radix(&hex, 16)
+          .ok()
+          .filter(|&code_point: &u32| code_point != 0)
+          .and_then(char::from_u32)
+          .unwrap_or('\u{FFFD}')
+      }
+      Some(c) => c,
+      None => '\u{FFFD}',
+    }
+  }
+
+  // Consume the name of an identifier, function, at-keyword or hash, with its escapes
+  // https://drafts.csswg.org/css-syntax/#consume-name
+  fn consume_name(&mut self) -> String {
+    let mut name: String = String::new();
+    loop {
+      match self.peek(0) {
+        Some(c) if is_name(c) => {
+          self.consume();
+          name.push(c);
+        }
+        first if is_valid_escape(first, self.peek(1)) => {
+          self.consume();
+          name.push(self.consume_escape());
+        }
+        _ => return name,
+      }
+    }
+  }
+
+  // Consume a number: an optional sign, digits with an optional fraction or a fraction alone, and
+  // an optional exponent, e.g. '-10', '.5', '+1.5e3' or '2E-2'
+  // https://drafts.csswg.org/css-syntax/#consume-number
+  fn consume_number(&mut self) -> Number {
+    let mut number: String = String::new();
+    let mut integer: bool = true;
+    let signed: bool = matches!(self.peek(0), Some('+' | '-'));
+    if signed {
+      number.extend(self.consume());
+    }
+    let is_digit = |c: Option<char>| -> bool { c.is_some_and(|c: char| c.is_ascii_digit()) };
+    let consume_digits = |tokenizer: &mut Tokenizer, number: &mut String| {
+      while is_digit(tokenizer.peek(0)) {
+        number.extend(tokenizer.consume());
+      }
+    };
+    consume_digits(self, &mut number);
+    if self.peek(0) == Some('.') && is_digit(self.peek(1)) {
+      integer = false;
+      number.extend(self.consume());
+      consume_digits(self, &mut number);
+    }
+    // An exponent is only part of the number when digits follow it, so that '1em' is a dimension
+    if matches!(self.peek(0), Some('e' | 'E')) {
+      let sign: usize = if matches!(self.peek(1), Some('+' | '-')) {
+        1
+      } else {
+        0
+      };
+      if is_digit(self.peek(1 + sign)) {
+        integer = false;
+        for _ in 0..1 + sign {
+          number.extend(self.consume());
+        }
+        consume_digits(self, &mut number);
+      }
+    }
+    Number::new(number.parse::<f32>().unwrap_or(0.0), integer, signed)
+  }
+
+  // Consume a number, a percentage or a dimension, e.g. '1.4', '50%' or '-10px'
+  // https://drafts.csswg.org/css-syntax/#consume-numeric-token
+  fn consume_numeric(&mut self) -> Token {
+    let number: Number = self.consume_number();
+    if self.starts_identifier(0) {
+      return Token::Dimension(number, self.consume_name());
+    }
+    if self.peek(0) == Some('%') {
+      self.consume();
+      return Token::Percentage(number);
+    }
+    Token::Number(number)
+  }
+
+  // Consume an identifier, a function, or an unquoted URL. A quoted URL is left to be parsed as
+  // a 'url(' function and its string
+  // https://drafts.csswg.org/css-syntax/#consume-ident-like-token
+  fn consume_ident_like(&mut self) -> Token {
+    let name: String = self.consume_name();
+    if self.peek(0) != Some('(') {
+      return Token::Ident(name);
+    }
+    self.consume();
+    if name.eq_ignore_ascii_case("url") {
+      let start: usize = self.position;
+      self.consume_whitespace();
+      if !matches!(self.peek(0), Some('"' | '\'')) {
+        return self.consume_url();
+      }
+      // The whitespace before the string is kept as a token of the function
+      self.position = start.max(self.position.saturating_sub(1));
+    }
+    Token::Function(name)
+  }
+
+  // Consume a string after its opening quote, up to the closing one or the end of the input. An
+  // escaped newline is skipped, and an unescaped one makes a bad string
+  // https://drafts.csswg.org/css-syntax/#consume-string-token
+  fn consume_string(&mut self, quote: char) -> Token {
+    let mut string: String = String::new();
+    loop {
+      match self.peek(0) {
+        Some(c) if c == quote => {
+          self.consume();
+          return Token::String(string);
+        }
+        None => return Token::String(string),
+        Some('\n') => return Token::BadString,
+        Some('\\') => {
+          self.consume();
+          match self.peek(0) {
+            None => {}
+            Some('\n') => self.position += 1,
+            Some(_) => string.push(self.consume_escape()),
+          }
+        }
+        Some(c) => {
+          self.consume();
+          string.push(c);
+        }
+      }
+    }
+  }
+
+  // Consume an unquoted URL after 'url(' and its whitespace, e.g. 'url(image.png)'
+  // https://drafts.csswg.org/css-syntax/#consume-url-token
+  fn consume_url(&mut self) -> Token {
+    let mut url: String = String::new();
+    loop {
+      match self.consume() {
+        Some(')') | None => return Token::Url(url),
+        Some(c) if c.is_ascii_whitespace() => {
+          self.consume_whitespace();
+          if matches!(self.peek(0), Some(')') | None) {
+            self.consume();
+            return Token::Url(url);
+          }
+          self.consume_bad_url();
+          return Token::BadUrl;
+        }
+        Some('"' | '\'' | '(') => {
+          self.consume_bad_url();
+          return Token::BadUrl;
+        }
+        Some(c) if is_non_printable(c) => {
+          self.consume_bad_url();
+          return Token::BadUrl;
+        }
+        Some('\\') if is_valid_escape(Some('\\'), self.peek(0)) => url.push(self.consume_escape()),
+        Some('\\') => {
+          self.consume_bad_url();
+          return Token::BadUrl;
+        }
+        Some(c) => url.push(c),
+      }
+    }
+  }
+
+  // Skip the rest of a bad URL, up to its ')' or the end of the input, so that parsing resumes
+  // after it
+  // https://drafts.csswg.org/css-syntax/#consume-remnants-of-bad-url
+  fn consume_bad_url(&mut self) {
+    loop {
+      match self.consume() {
+        Some(')') | None => return,
+        Some('\\') if is_valid_escape(Some('\\'), self.peek(0)) => {
+          self.consume_escape();
+        }
+        Some(_) => {}
+      }
+    }
+  }
+}
@@ -0,0 +1,240 @@
+use super::*;
+
+fn ident(name: &str) -> Token {
+  Token::Ident(name.to_string())
+}
+
+fn number(value: f32, integer: bool, signed: bool) -> Number {
+  Number::new(value, integer, signed)
+}
+
+// Test the method tokenize of the Tokenizer struct implementation
+#[test]
+fn test_tokenize() {
+  // Assert that a rule is split into identifiers, delimiters, punctuation and whitespace
+  assert_eq!(
+    Tokenizer::tokenize("p > .a{color:red;}"),
+    vec![
+      ident("p"),
+      Token::Whitespace,
+      Token::Delim('>'),
+      Token::Whitespace,
+      Token::Delim('.'),
+      ident("a"),
+      Token::OpenCurly,
+      ident("color"),
+      Token::Colon,
+      ident("red"),
+      Token::Semicolon,
+      Token::CloseCurly,
+    ]
+  );
+  // Assert that functions, at-keywords and hashes are tokens of their own
+  assert_eq!(
+    Tokenizer::tokenize("@media rgb(#0f0,#main)"),
+    vec![
+      Token::AtKeyword("media".to_string()),
+      Token::Whitespace,
+      Token::Function("rgb".to_string()),
+      Token::Hash("0f0".to_string(), HashType::Unrestricted),
+      Token::Comma,
+      Token::Hash("main".to_string(), HashType::Id),
+      Token::CloseParen,
+    ]
+  );
+  // Assert that '-' starts an identifier, a number or a delimiter depending on what follows it
+  assert_eq!(
+    Tokenizer::tokenize("-webkit -5 --x - <!-- -->"),
+    vec![
+      ident("-webkit"),
+      Token::Whitespace,
+      Token::Number(number(-5.0, true, true)),
+      Token::Whitespace,
+      ident("--x"),
+      Token::Whitespace,
+      Token::Delim('-'),
+      Token::Whitespace,
+      Token::Cdo,
+      Token::Whitespace,
+      Token::Cdc,
+    ]
+  );
+  // Assert that carriage returns and form feeds are newlines, and NULL characters are replaced
+  assert_eq!(
+    Tokenizer::tokenize("a\r\n\u{C}b\0"),
+    vec![ident("a"), Token::Whitespace, ident("b\u{FFFD}")]
+  );
+  assert_eq!(Tokenizer::tokenize(""), vec![]);
+}
+
+// Test the method consume_comments of the Tokenizer struct implementation
+#[test]
+fn test_consume_comments() {
+  // Assert that comments are skipped anywhere, without separating the tokens around them
+  assert_eq!(
+    Tokenizer::tokenize("/* a */p/**/{/* b } */}"),
+    vec![ident("p"), Token::OpenCurly, Token::CloseCurly]
+  );
+  assert_eq!(
+    Tokenizer::tokenize("a/* */ /* */b"),
+    vec![ident("a"), Token::Whitespace, ident("b")]
+  );
+  // Assert that an unterminated comment ends at the end of the input
+  assert_eq!(
+    Tokenizer::tokenize("a /* b"),
+    vec![ident("a"), Token::Whitespace]
+  );
+  // Assert that a comment can't start inside a string
+  assert_eq!(
+    Tokenizer::tokenize("'/**/'"),
+    vec![Token::String("/**/".to_string())]
+  );
+}
+
+// Test the method consume_escape of the Tokenizer struct implementation
+#[test]
+fn test_consume_escape() {
+  // Assert that escapes are hex code points with an optional whitespace after them, or any other
+  // character
+  assert_eq!(Tokenizer::tokenize("\\66 oo"), vec![ident("foo")]);
+  assert_eq!(Tokenizer::tokenize("a\\.b"), vec![ident("a.b")]);
+  assert_eq!(Tokenizer::tokenize("\\31 23"), vec![ident("123")]);
+  assert_eq!(
+    Tokenizer::tokenize("#\\#id"),
+    vec![Token::Hash("#id".to_string(), HashType::Id)]
+  );
+  // Assert that NULL, surrogates and code points out of range are replaced
+  assert_eq!(Tokenizer::tokenize("\\0"), vec![ident("\u{FFFD}")]);
+  assert_eq!(Tokenizer::tokenize("\\D800"), vec![ident("\u{FFFD}")]);
+  assert_eq!(Tokenizer::tokenize("\\110000"), vec![ident("\u{FFFD}")]);
+  // Assert that a '\' before a newline is not an escape
+  assert_eq!(
+    Tokenizer::tokenize("\\\n"),
+    vec![Token::Delim('\\'), Token::Whitespace]
+  );
+}
+
+// Test the method consume_string of the Tokenizer struct implementation
+#[test]
+fn test_consume_string() {
+  // Assert that strings take single or double quotes, and escapes
+  assert_eq!(
+    Tokenizer::tokenize("\"it's\" 'a\\'b'"),
+    vec![
+      Token::String("it's".to_string()),
+      Token::Whitespace,
+      Token::String("a'b".to_string()),
+    ]
+  );
+  // Assert that an escaped newline is skipped, and an unescaped one makes a bad string
+  assert_eq!(
+    Tokenizer::tokenize("'a\\\nb'"),
+    vec![Token::String("ab".to_string())]
+  );
+  assert_eq!(
+    Tokenizer::tokenize("'a\nb"),
+    vec![Token::BadString, Token::Whitespace, ident("b")]
+  );
+  // Assert that an unterminated string ends at the end of the input
+  assert_eq!(
+    Tokenizer::tokenize("'abc"),
+    vec![Token::String("abc".to_string())]
+  );
+}
+
+// Test the method consume_url of the Tokenizer struct implementation
+#[test]
+fn test_consume_url() {
+  // Assert that an unquoted URL is a token of its own, without the whitespace around it
+  assert_eq!(
+    Tokenizer::tokenize("url( a.png )"),
+    vec![Token::Url("a.png".to_string())]
+  );
+  assert_eq!(
+    Tokenizer::tokenize("URL(a\\)b)"),
+    vec![Token::Url("a)b".to_string())]
+  );
+  // Assert that a quoted URL is a function with a string argument
+  assert_eq!(
+    Tokenizer::tokenize("url( 'a.png')"),
+    vec![
+      Token::Function("url".to_string()),
+      Token::Whitespace,
+      Token::String("a.png".to_string()),
+      Token::CloseParen,
+    ]
+  );
+  // Assert that whitespace, quotes and parentheses inside an unquoted URL make a bad URL, which
+  // ends at its ')'
+  assert_eq!(
+    Tokenizer::tokenize("url(a b) c"),
+    vec![Token::BadUrl, Token::Whitespace, ident("c")]
+  );
+  assert_eq!(Tokenizer::tokenize("url(a\"b)"), vec![Token::BadUrl]);
+  assert_eq!(Tokenizer::tokenize("url(a(b)"), vec![Token::BadUrl]);
+}
+
+// Test the method consume_number of the Tokenizer struct implementation
+#[test]
+fn test_consume_number() {
+  let numbers = |source: &str| -> Vec<Token> { Tokenizer::tokenize(source) };
+
+  // Assert that numbers can have a sign, a leading dot and an exponent
+  assert_eq!(
+    numbers("100.5"),
+    vec![Token::Number(number(100.5, false, false))]
+  );
+  assert_eq!(
+    numbers("+.5"),
+    vec![Token::Number(number(0.5, false, true))]
+  );
+  assert_eq!(
+    numbers("1.5e3"),
+    vec![Token::Number(number(1500.0, false, false))]
+  );
+  assert_eq!(
+    numbers("2E-2"),
+    vec![Token::Number(number(0.02, false, false))]
+  );
+  // Assert that an 'e' or a dot without digits after it is not part of the number
+  assert_eq!(
+    numbers("1e+x"),
+    vec![
+      Token::Dimension(number(1.0, true, false), "e".to_string()),
+      Token::Delim('+'),
+      ident("x")
+    ]
+  );
+  assert_eq!(
+    numbers("3."),
+    vec![Token::Number(number(3.0, true, false)), Token::Delim('.')]
+  );
+  // Assert that a sign without digits is a delimiter
+  assert_eq!(numbers("+"), vec![Token::Delim('+')]);
+}
+
+// Test the method consume_numeric of the Tokenizer struct implementation
+#[test]
+fn test_consume_numeric() {
+  // Assert that a number followed by an identifier or '%' is a dimension or a percentage
+  assert_eq!(
+    Tokenizer::tokenize("-10px 50% 1em 2n-1"),
+    vec![
+      Token::Dimension(number(-10.0, true, true), "px".to_string()),
+      Token::Whitespace,
+      Token::Percentage(number(50.0, true, false)),
+      Token::Whitespace,
+      Token::Dimension(number(1.0, true, false), "em".to_string()),
+      Token::Whitespace,
+      Token::Dimension(number(2.0, true, false), "n-1".to_string()),
+    ]
+  );
+  // Assert that a number followed by a '-' that doesn't start an identifier is not a dimension
+  assert_eq!(
+    Tokenizer::tokenize("1-2"),
+    vec![
+      Token::Number(number(1.0, true, false)),
+      Token::Number(number(-2.0, true, true))
+    ]
+  );
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::css;
+use crate::dom;
+use crate::parser::css::CSSParser;
+use crate::parser::encoding;
+use crate::parser::html::HTMLParser;
+
+#[cfg(test)]
+mod tests;
+
+pub struct DocumentParser {
+  // Directory that relative stylesheet links are resolved against
+  base_directory: PathBuf,
+  // Encoding of the document, used for linked stylesheets that don't declare their own
+  encoding: &'static str,
+}
+
+impl DocumentParser {
+  pub fn new(base_directory: &Path) -> Self {
+    Self {
+      base_directory: base_directory.to_path_buf(),
+      encoding: "UTF-8",
+    }
+  }
+
+  // Does this <style> element contain CSS? A missing or empty 'type' means CSS
+  fn is_css_style(element: &dom::ElementData) -> bool {
+    match element.attributes().get("type") {
+      Some(style_type) => style_type.is_empty() || style_type.eq_ignore_ascii_case("text/css"),
+      None => true,
+    }
+  }
+
+  // Does this <link> element reference a stylesheet? The 'rel' attribute is a list of keywords
+  fn is_stylesheet_link(element: &dom::ElementData) -> bool {
+    let keywords: Vec<String> = match element.attributes().get("rel") {
+      Some(rel) => rel.split_whitespace().map(|keyword: &str| keyword.to_ascii_lowercase()).collect(),
+      None => return false,
+    };
+    // Alternative stylesheets are disabled until the user picks one, so they are not applied
+    keywords.iter().any(|keyword: &String| keyword == "stylesheet")
+      && !keywords.iter().any(|keyword: &String| keyword == "alternate")
+  }
+
+  // Read and parse the local stylesheet referenced by 'href', or return None if it can't be loaded
+  fn load_stylesheet(&self, href: &str) -> Option<css::Stylesheet> {
+    // Only local files are supported: remote URLs are skipped
+    if href.is_empty() || href.contains("://") || href.starts_with("//") {
+      return None;
+    }
+    // The query string and fragment don't identify a different file
+    let path: &Path = Path::new(href.split(['?', '#']).next().unwrap_or_default());
+    // The document is untrusted, so it may only read files under the base directory: absolute
+    // paths and '..' components, which would lead out of it, are skipped
+    let inside_base_directory: bool = path
+      .components()
+      .all(|component: Component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside_base_directory {
+      return None;
+    }
+    let bytes: Vec<u8> = fs::read(self.base_directory.join(path)).ok()?;
+    let source: String = encoding::decode_css(&bytes, None, Some(self.encoding)).into_text();
+    CSSParser::parse(source).ok()
+  }
+
+  // Collect the stylesheets of a node and its descendants, in document order
+  fn collect_stylesheets(&self, node: &dom::Node, stylesheets: &mut Vec<css::Stylesheet>) {
+    if let dom::NodeType::Element(element) = node.node_type() {
+      match &*element.tag_name().to_ascii_lowercase() {
+        "style" if DocumentParser::is_css_style(element) => {
+          // Recovery: a style element that can't be parsed is ignored
+          if let Ok(stylesheet) = CSSParser::parse(node.text_content()) {
+            stylesheets.push(stylesheet);
+          }
+        }
+        "link" if DocumentParser::is_stylesheet_link(element) => {
+          // Recovery: a stylesheet that can't be read or parsed is ignored, as browsers do
+          if let Some(stylesheet) = element
+            .attributes()
+            .get("href")
+            .and_then(|href: &String| self.load_stylesheet(href.trim()))
+          {
+            stylesheets.push(stylesheet);
+          }
+        }
+        _ => {}
+      }
+    }
+    for child in node.children() {
+      self.collect_stylesheets(child, stylesheets);
+    }
+  }
+
+  // Collect the stylesheets of a parsed document into it
+  fn add_stylesheets(&self, document: &mut dom::Document) {
+    let mut stylesheets: Vec<css::Stylesheet> = Vec::new();
+    self.collect_stylesheets(document.root(), &mut stylesheets);
+    for stylesheet in stylesheets {
+      document.add_stylesheet(stylesheet);
+    }
+  }
+
+  // Parse an HTML document together with the stylesheets embedded in <style> elements and
+  // linked with <link rel="stylesheet">. Relative links are resolved against 'base_directory'
+  pub fn parse(source: String, base_directory: &Path) -> Result<dom::Document, String> {
+    let mut document: dom::Document = HTMLParser::parse_document(source)?;
+    DocumentParser::new(base_directory).add_stylesheets(&mut document);
+    Ok(document)
+  }
+
+  // Decode and parse the bytes of an HTML document, like parse. The encoding is sniffed from
+  // the bytes unless 'encoding_hint' (e.g. the charset of an HTTP response) names one, and is
+  // recorded in the document
+  pub fn parse_bytes(
+    bytes: &[u8],
+    encoding_hint: Option<&str>,
+    base_directory: &Path,
+  ) -> Result<dom::Document, String> {
+    let source: encoding::DecodedText = encoding::decode_html(bytes, encoding_hint);
+    let mut document_parser: DocumentParser = DocumentParser::new(base_directory);
+    document_parser.encoding = source.encoding();
+    let mut document: dom::Document = HTMLParser::parse_document(source.into_text())?;
+    document.set_encoding(document_parser.encoding);
+    document_parser.add_stylesheets(&mut document);
+    Ok(document)
+  }
+}
@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::css;
+use crate::dom;
+use crate::hashmap;
+use super::*;
+
+// Create a scratch directory holding the given stylesheet files
+fn write_stylesheets(name: &str, files: &[(&str, &str)]) -> PathBuf {
+  let directory: PathBuf = std::env::temp_dir().join(format!(
+    "web-rendering-engine-{}-{}",
+    name,
+    std::process::id()
+  ));
+  fs::create_dir_all(&directory).unwrap();
+  for (filename, source) in files {
+    let path: PathBuf = directory.join(filename);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+  }
+  directory
+}
+
+// Test the function is_stylesheet_link of the DocumentParser struct implementation
+#[test]
+fn test_is_stylesheet_link() {
+  let link: dom::ElementData = dom::ElementData::new(
+    "link".to_string(),
+    hashmap![String::from("rel") => String::from("preload StyleSheet")],
+  );
+  let alternate: dom::ElementData = dom::ElementData::new(
+    "link".to_string(),
+    hashmap![String::from("rel") => String::from("alternate stylesheet")],
+  );
+  let icon: dom::ElementData = dom::ElementData::new(
+    "link".to_string(),
+    hashmap![String::from("rel") => String::from("icon")],
+  );
+
+  // Assert that the 'rel' keywords are matched case-insensitively
+  assert!(DocumentParser::is_stylesheet_link(&link));
+  // Assert that alternative stylesheets and other link types are not applied
+  assert!(!DocumentParser::is_stylesheet_link(&alternate));
+  assert!(!DocumentParser::is_stylesheet_link(&icon));
+}
+
+// Test the method load_stylesheet of the DocumentParser struct implementation
+#[test]
+fn test_load_stylesheet() {
+  let directory: PathBuf = write_stylesheets("load", &[("site.css", "p { width: 10px; }")]);
+  let document_parser: DocumentParser = DocumentParser::new(&directory);
+
+  // Assert that a local stylesheet is read relative to the base directory
+  assert_eq!(
+    document_parser
+      .load_stylesheet("site.css?v=2")
+      .unwrap()
+      .rules()
+      .len(),
+    1
+  );
+  // Assert that missing files and remote URLs are skipped
+  assert_eq!(document_parser.load_stylesheet("missing.css"), None);
+  assert_eq!(
+    document_parser.load_stylesheet("https://www.test.com/site.css"),
+    None
+  );
+}
+
+// Test the method load_stylesheet of the DocumentParser struct implementation with paths that
+// lead out of the base directory
+#[test]
+fn test_load_stylesheet_outside_base_directory() {
+  let directory: PathBuf = write_stylesheets(
+    "outside",
+    &[("secret.css", "p { width: 10px; }"), ("base/site.css", "")],
+  );
+  let document_parser: DocumentParser = DocumentParser::new(&directory.join("base"));
+  let secret: PathBuf = directory.join("secret.css");
+
+  // Assert that files in the base directory can still be read through '.'
+  assert!(document_parser.load_stylesheet("./site.css").is_some());
+  // Assert that an existing file is skipped when it's reached through '..'
+  assert_eq!(document_parser.load_stylesheet("../secret.css"), None);
+  assert_eq!(document_parser.load_stylesheet("./../base/../secret.css"), None);
+  // Assert that an existing file is skipped when it's referenced by an absolute path
+  assert_eq!(document_parser.load_stylesheet(secret.to_str().unwrap()), None);
+}
+
+// Test the function parse of the DocumentParser struct implementation
+#[test]
+fn test_parse() {
+  let directory: PathBuf = write_stylesheets("parse", &[("linked.css", "p { width: 20px; }")]);
+  let source: String = "<html>\
+    <style>p { width: 10px; }</style>\
+    <link rel='stylesheet' href='linked.css'>\
+    <link rel='stylesheet' href='missing.css'>\
+    <style type='text/plain'>p { width: 40px; }</style>\
+    <p>Hello World!</p>\
+    <style>p { width: 30px; }</style>\
+  </html>"
+    .to_string();
+  let document: dom::Document = DocumentParser::parse(source, &directory).unwrap();
+  let widths: Vec<css::Value> = document
+    .stylesheets()
+    .iter()
+    .map(|stylesheet: &css::Stylesheet| stylesheet.rules()[0].declarations()[0].value().clone())
+    .collect();
+
+  // Assert that the embedded and linked stylesheets are collected in document order, skipping
+  // the missing file and the non-CSS style element
+  assert_eq!(
+    widths,
+    vec![
+      css::Value::Length(10.0, css::Unit::Px),
+      css::Value::Length(20.0, css::Unit::Px),
+      css::Value::Length(30.0, css::Unit::Px),
+    ]
+  );
+  // Assert that the document root is the parsed DOM tree, with the elements before the
+  // paragraph in its head
+  let children: &Vec<dom::Node> = document.root().children();
+  assert_eq!(children.len(), 2);
+  assert_eq!(children[0].children().len(), 4);
+  assert_eq!(children[1].children().len(), 2);
+}
+
+// Test the function parse_bytes of the DocumentParser struct implementation
+#[test]
+fn test_parse_bytes() {
+  let directory: PathBuf = write_stylesheets(
+    "parse-bytes",
+    &[("site.css", "@charset \"utf-8\"; p { width: 10px; }")],
+  );
+  let source: &[u8] =
+    b"<meta charset='windows-1252'><link rel=stylesheet href=site.css><p>na\xEFve</p>";
+
+  // Assert that the document is decoded with its declared encoding, which is recorded
+  let document: dom::Document = DocumentParser::parse_bytes(source, None, &directory).unwrap();
+  assert_eq!(document.encoding(), "windows-1252");
+  assert_eq!(document.root().text_content(), "na\u{EF}ve");
+  assert_eq!(document.stylesheets().len(), 1);
+  // Assert that the caller's hint wins over the declaration
+  let document: dom::Document =
+    DocumentParser::parse_bytes(source, Some("iso-8859-5"), &directory).unwrap();
+  assert_eq!(document.encoding(), "ISO-8859-5");
+  assert_eq!(document.root().text_content(), "na\u{44F}ve");
+  // Assert that documents parsed from text are UTF-8
+  let document: dom::Document = DocumentParser::parse(String::new(), &directory).unwrap();
+  assert_eq!(document.encoding(), "UTF-8");
+}
@@ -0,0 +1,314 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252, X_USER_DEFINED};
+
+#[cfg(test)]
+mod tests;
+
+// How many bytes at the start of the input are searched for an encoding declaration
+const PRESCAN_LENGTH: usize = 1024;
+
+// Where the encoding of a document or stylesheet comes from, from most to least certain
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EncodingSource {
+  // A UTF-8 or UTF-16 byte order mark at the start of the input
+  ByteOrderMark,
+  // An encoding label given by the caller, e.g. the charset of an HTTP Content-Type header
+  Hint,
+  // A <meta charset> or http-equiv declaration in a document, or an @charset rule in a
+  // stylesheet
+  Declaration,
+  // The encoding of the document that links to a stylesheet
+  Environment,
+  // Nothing declared the encoding: UTF-8 if the input is valid UTF-8, otherwise windows-1252
+  Default,
+}
+
+// Input bytes decoded to text, with the encoding chosen to decode them
+#[derive(Debug)]
+pub struct DecodedText {
+  text: String,
+  encoding: &'static Encoding,
+  source: EncodingSource,
+}
+
+impl DecodedText {
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  pub fn into_text(self) -> String {
+    self.text
+  }
+
+  // The name of the encoding, e.g. 'UTF-8' or 'windows-1252'
+  pub fn encoding(&self) -> &'static str {
+    self.encoding.name()
+  }
+
+  pub fn source(&self) -> EncodingSource {
+    self.source
+  }
+}
+
+// Find the encoding for a label such as 'utf-8', 'latin1' or 'ISO-8859-1', ignoring ASCII case
+// and surrounding whitespace
+// https://encoding.spec.whatwg.org/#concept-encoding-get
+fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+  Encoding::for_label(label.as_bytes())
+}
+
+fn is_whitespace(byte: u8) -> bool {
+  matches!(byte, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
+}
+
+// Return the position of 'target' in 'bytes', starting the search at 'from'
+fn find(bytes: &[u8], from: usize, target: &[u8]) -> Option<usize> {
+  bytes
+    .get(from..)?
+    .windows(target.len())
+    .position(|window: &[u8]| window == target)
+    .map(|index: usize| from + index)
+}
+
+// Does 'bytes' have 'prefix' at 'position', ignoring ASCII case?
+fn starts_with_ignore_case(bytes: &[u8], position: usize, prefix: &[u8]) -> bool {
+  bytes
+    .get(position..position + prefix.len())
+    .is_some_and(|start: &[u8]| start.eq_ignore_ascii_case(prefix))
+}
+
+// Read one attribute of a tag, lowercased, starting at 'position', and move 'position' past it.
+// Return None at the end of the tag or of the input
+// https://html.spec.whatwg.org/multipage/parsing.html#concept-get-attributes-when-sniffing
+fn prescan_attribute(bytes: &[u8], position: &mut usize) -> Option<(Vec<u8>, Vec<u8>)> {
+  while bytes.get(*position).is_some_and(|&b: &u8| is_whitespace(b) || b == b'/') {
+    *position += 1;
+  }
+  if *bytes.get(*position)? == b'>' {
+    return None;
+  }
+  let mut name: Vec<u8> = Vec::new();
+  loop {
+    match *bytes.get(*position)? {
+      b'=' if !name.is_empty() => break,
+      b'/' | b'>' => return Some((name, vec![])),
+      byte if is_whitespace(byte) => {
+        while bytes.get(*position).is_some_and(|&b: &u8| is_whitespace(b)) {
+          *position += 1;
+        }
+        if *bytes.get(*position)? != b'=' {
+          return Some((name, vec![]));
+        }
+        break;
+      }
+      byte => name.push(byte.to_ascii_lowercase()),
+    }
+    *position += 1;
+  }
+  // Skip the '=' and the whitespace after it
+  *position += 1;
+  while bytes.get(*position).is_some_and(|&b: &u8| is_whitespace(b)) {
+    *position += 1;
+  }
+  let mut value: Vec<u8> = Vec::new();
+  match *bytes.get(*position)? {
+    quote @ (b'"' | b'\'') => {
+      *position += 1;
+      let end: usize = bytes[*position..].iter().position(|&b: &u8| b == quote)? + *position;
+      value.extend(bytes[*position..end].iter().map(u8::to_ascii_lowercase));
+      *position = end + 1;
+    }
+    b'>' => {}
+    _ => {
+      while let Some(&byte) = bytes
+        .get(*position)
+        .filter(|&&b: &&u8| !is_whitespace(b) && b != b'>')
+      {
+        value.push(byte.to_ascii_lowercase());
+        *position += 1;
+      }
+    }
+  }
+  Some((name, value))
+}
+
+// Find the encoding in the 'content' attribute of a http-equiv meta element, e.g.
+// 'text/html; charset=iso-8859-1'
+// https://html.spec.whatwg.org/multipage/urls-and-fetching.html#algorithm-for-extracting-a-character-encoding-from-a-meta-element
+fn encoding_from_content(content: &[u8]) -> Option<&'static Encoding> {
+  let mut position: usize = 0;
+  loop {
+    position = (position..content.len())
+      .find(|&index: &usize| starts_with_ignore_case(content, index, b"charset"))?
+      + "charset".len();
+    while content.get(position).is_some_and(|&b: &u8| is_whitespace(b)) {
+      position += 1;
+    }
+    if content.get(position) == Some(&b'=') {
+      break;
+    }
+  }
+  position += 1;
+  while content.get(position).is_some_and(|&b: &u8| is_whitespace(b)) {
+    position += 1;
+  }
+  match *content.get(position)? {
+    quote @ (b'"' | b'\'') => {
+      let end: usize = find(content, position + 1, &[quote])?;
+      Encoding::for_label(&content[position + 1..end])
+    }
+    _ => {
+      let end: usize = (position..content.len())
+        .find(|&index: &usize| is_whitespace(content[index]) || content[index] == b';')
+        .unwrap_or(content.len());
+      Encoding::for_label(&content[position..end])
+    }
+  }
+}
+
+// Find the encoding declared by a <meta charset> or <meta http-equiv="content-type"> element
+// in the first bytes of a document, skipping comments and the attributes of other tags
+// https://html.spec.whatwg.org/multipage/parsing.html#prescan-a-byte-stream-to-determine-its-encoding
+fn prescan(bytes: &[u8]) -> Option<&'static Encoding> {
+  let bytes: &[u8] = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+  let mut position: usize = 0;
+  while position < bytes.len() {
+    let next: Option<u8> = bytes.get(position + 1).copied();
+    if bytes[position..].starts_with(b"<!--") {
+      position = find(bytes, position + 2, b"-->")? + 2;
+    } else if starts_with_ignore_case(bytes, position, b"<meta")
+      && bytes
+        .get(position + 5)
+        .is_some_and(|&b: &u8| is_whitespace(b) || b == b'/')
+    {
+      position += 5;
+      let mut names: Vec<Vec<u8>> = Vec::new();
+      let mut got_pragma: bool = false;
+      let mut need_pragma: Option<bool> = None;
+      let mut charset: Option<&'static Encoding> = None;
+      while let Some((name, value)) = prescan_attribute(bytes, &mut position) {
+        if names.contains(&name) {
+          continue;
+        }
+        match &name[..] {
+          b"http-equiv" => got_pragma |= value == b"content-type",
+          b"content" if charset.is_none() => {
+            if let Some(encoding) = encoding_from_content(&value) {
+              charset = Some(encoding);
+              need_pragma = Some(true);
+            }
+          }
+          b"charset" => {
+            charset = Encoding::for_label(&value);
+            need_pragma = Some(false);
+          }
+          _ => {}
+        }
+        names.push(name);
+      }
+      if let (Some(need_pragma), Some(encoding)) = (need_pragma, charset) {
+        if !need_pragma || got_pragma {
+          // A document that declares UTF-16 in ASCII bytes can't actually be UTF-16
+          return Some(if encoding == UTF_16BE || encoding == UTF_16LE {
+            UTF_8
+          } else if encoding == X_USER_DEFINED {
+            WINDOWS_1252
+          } else {
+            encoding
+          });
+        }
+      }
+    } else if bytes[position] == b'<'
+      && (next.is_some_and(|b: u8| b.is_ascii_alphabetic())
+        || (next == Some(b'/')
+          && bytes
+            .get(position + 2)
+            .is_some_and(|b: &u8| b.is_ascii_alphabetic())))
+    {
+      // Skip the tag name and attributes of any other tag
+      while bytes
+        .get(position)
+        .is_some_and(|&b: &u8| !is_whitespace(b) && b != b'>')
+      {
+        position += 1;
+      }
+      while prescan_attribute(bytes, &mut position).is_some() {}
+    } else if bytes[position] == b'<' && matches!(next, Some(b'!' | b'/' | b'?')) {
+      position = find(bytes, position, b">")?;
+    }
+    position += 1;
+  }
+  None
+}
+
+// Decode the bytes of an HTML document. The encoding is taken, in order, from a byte order
+// mark, the caller's hint (if it is a known label), or a <meta> declaration
+// https://html.spec.whatwg.org/multipage/parsing.html#encoding-sniffing-algorithm
+pub fn decode_html(bytes: &[u8], hint: Option<&str>) -> DecodedText {
+  let (encoding, source): (&'static Encoding, EncodingSource) =
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+      (encoding, EncodingSource::ByteOrderMark)
+    } else if let Some(encoding) = hint.and_then(encoding_for_label) {
+      (encoding, EncodingSource::Hint)
+    } else if let Some(encoding) = prescan(bytes) {
+      (encoding, EncodingSource::Declaration)
+    } else {
+      (default_encoding(bytes), EncodingSource::Default)
+    };
+  decode(bytes, encoding, source)
+}
+
+// Decode the bytes of a stylesheet. The encoding is taken, in order, from a byte order mark,
+// the caller's hint, an @charset rule, or the encoding of the document linking to it
+// https://drafts.csswg.org/css-syntax/#input-byte-stream
+pub fn decode_css(bytes: &[u8], hint: Option<&str>, environment: Option<&str>) -> DecodedText {
+  let (encoding, source): (&'static Encoding, EncodingSource) =
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+      (encoding, EncodingSource::ByteOrderMark)
+    } else if let Some(encoding) = hint.and_then(encoding_for_label) {
+      (encoding, EncodingSource::Hint)
+    } else if let Some(encoding) = charset_rule(bytes) {
+      (encoding, EncodingSource::Declaration)
+    } else if let Some(encoding) = environment.and_then(encoding_for_label) {
+      (encoding, EncodingSource::Environment)
+    } else {
+      (default_encoding(bytes), EncodingSource::Default)
+    };
+  decode(bytes, encoding, source)
+}
+
+// Find the encoding of an '@charset "<label>";' rule at the very start of a stylesheet
+fn charset_rule(bytes: &[u8]) -> Option<&'static Encoding> {
+  let bytes: &[u8] = &bytes[..bytes.len().min(PRESCAN_LENGTH)];
+  let label_start: usize = "@charset \"".len();
+  if !bytes.starts_with(b"@charset \"") {
+    return None;
+  }
+  let label_end: usize = find(bytes, label_start, b"\";")?;
+  let encoding: &'static Encoding = Encoding::for_label(&bytes[label_start..label_end])?;
+  // As in HTML, a stylesheet that declares UTF-16 in ASCII bytes is really UTF-8
+  Some(if encoding == UTF_16BE || encoding == UTF_16LE {
+    UTF_8
+  } else {
+    encoding
+  })
+}
+
+// Without a declared encoding, text that is valid UTF-8 is decoded as UTF-8, and anything else
+// as windows-1252, the legacy encoding of most Western pages
+fn default_encoding(bytes: &[u8]) -> &'static Encoding {
+  if std::str::from_utf8(bytes).is_ok() {
+    UTF_8
+  } else {
+    WINDOWS_1252
+  }
+}
+
+// Decode the bytes, replacing invalid sequences with U+FFFD and removing a byte order mark
+fn decode(bytes: &[u8], encoding: &'static Encoding, source: EncodingSource) -> DecodedText {
+  let (text, encoding, _) = encoding.decode(bytes);
+  DecodedText {
+    text: text.into_owned(),
+    encoding,
+    source,
+  }
+}
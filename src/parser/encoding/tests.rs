@@ -0,0 +1,85 @@
+use super::*;
+
+// Test the function prescan
+#[test]
+fn test_prescan() {
+  let prescan_name = |source: &[u8]| prescan(source).map(|encoding: &Encoding| encoding.name());
+
+  // Assert that <meta charset> is found, ignoring case, quotes and the attributes before it
+  assert_eq!(prescan_name(b"<META lang=en CharSet=\"ISO-8859-1\">"), Some("windows-1252"));
+  assert_eq!(prescan_name(b"<!DOCTYPE html><meta charset=utf-8 />"), Some("UTF-8"));
+  // Assert that http-equiv needs both the pragma and a charset in its content
+  assert_eq!(
+    prescan_name(b"<meta content='text/html; charset=koi8-r' http-equiv=Content-Type>"),
+    Some("KOI8-R")
+  );
+  assert_eq!(prescan_name(b"<meta content='text/html; charset=koi8-r'>"), None);
+  // Assert that comments and the attribute values of other tags are skipped
+  assert_eq!(prescan_name(b"<!-- <meta charset=koi8-r> --><p>"), None);
+  assert_eq!(prescan_name(b"<div title='<meta charset=koi8-r>'>"), None);
+  assert_eq!(prescan_name(b"<!--><meta charset=gbk>"), Some("GBK"));
+  // Assert that UTF-16 declarations mean UTF-8, and that unknown labels are ignored
+  assert_eq!(prescan_name(b"<meta charset=utf-16le>"), Some("UTF-8"));
+  assert_eq!(prescan_name(b"<meta charset=unknown>"), None);
+  // Assert that only the first 1024 bytes are searched, and that truncated tags end the search
+  let mut late: Vec<u8> = vec![b' '; PRESCAN_LENGTH];
+  late.extend_from_slice(b"<meta charset=gbk>");
+  assert_eq!(prescan_name(&late), None);
+  assert_eq!(prescan_name(b"<meta charset='gbk"), None);
+  assert_eq!(prescan_name(b"<!-- <meta charset=gbk>"), None);
+}
+
+// Test the function decode_html
+#[test]
+fn test_decode_html() {
+  let latin1: &[u8] = b"<meta charset=latin1><p>caf\xE9 \x80</p>";
+
+  // Assert that a declared legacy encoding is used to decode the document
+  let decoded: DecodedText = decode_html(latin1, None);
+  assert_eq!(decoded.text(), "<meta charset=latin1><p>caf\u{E9} \u{20AC}</p>");
+  assert_eq!(decoded.encoding(), "windows-1252");
+  assert_eq!(decoded.source(), EncodingSource::Declaration);
+  // Assert that a byte order mark wins over the hint, which wins over the declaration
+  let decoded: DecodedText = decode_html(b"\xEF\xBB\xBF<p>caf\xC3\xA9</p>", Some("latin1"));
+  assert_eq!(decoded.text(), "<p>caf\u{E9}</p>");
+  assert_eq!(decoded.source(), EncodingSource::ByteOrderMark);
+  let decoded: DecodedText = decode_html(b"\xFF\xFE<\x00p\x00>\x00", None);
+  assert_eq!((decoded.text(), decoded.encoding()), ("<p>", "UTF-16LE"));
+  let decoded: DecodedText = decode_html(latin1, Some(" UTF-8 "));
+  assert_eq!(decoded.text(), "<meta charset=latin1><p>caf\u{FFFD} \u{FFFD}</p>");
+  assert_eq!(decoded.source(), EncodingSource::Hint);
+  // Assert that an unknown hint is ignored
+  assert_eq!(decode_html(latin1, Some("unknown")).source(), EncodingSource::Declaration);
+  // Assert that undeclared input is UTF-8 if it is valid UTF-8, and windows-1252 otherwise
+  let decoded: DecodedText = decode_html("<p>\u{65E5}\u{672C}</p>".as_bytes(), None);
+  assert_eq!((decoded.encoding(), decoded.source()), ("UTF-8", EncodingSource::Default));
+  assert_eq!(decode_html(b"<p>caf\xE9</p>", None).into_text(), "<p>caf\u{E9}</p>");
+}
+
+// Test the function decode_css
+#[test]
+fn test_decode_css() {
+  let charset: &[u8] = b"@charset \"iso-8859-15\"; p::before { content: '\xA4' }";
+
+  // Assert that an @charset rule wins over the encoding of the document
+  let decoded: DecodedText = decode_css(charset, None, Some("windows-1252"));
+  assert_eq!(decoded.encoding(), "ISO-8859-15");
+  assert_eq!(decoded.source(), EncodingSource::Declaration);
+  assert!(decoded.text().ends_with("content: '\u{20AC}' }"));
+  // Assert that the rule must be exactly at the start, with double quotes
+  let decoded: DecodedText = decode_css(b" @charset \"gbk\"; p {}", None, Some("latin1"));
+  assert_eq!(
+    (decoded.encoding(), decoded.source()),
+    ("windows-1252", EncodingSource::Environment)
+  );
+  assert_eq!(
+    decode_css(b"@charset 'gbk'; p {}", None, None).source(),
+    EncodingSource::Default
+  );
+  // Assert that the hint wins over the rule, and that UTF-16 rules mean UTF-8
+  assert_eq!(decode_css(charset, Some("gbk"), None).encoding(), "GBK");
+  assert_eq!(
+    decode_css(b"@charset \"utf-16be\"; p {}", None, None).encoding(),
+    "UTF-8"
+  );
+}
@@ -0,0 +1,138 @@
+// Rules for parsing SVG and MathML elements inside an HTML document ("foreign content")
+// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign
+
+use crate::dom;
+
+// SVG element names are case-sensitive. Tag names are lowercased when parsed, and these get their
+// case back
+// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign (any other start tag)
+const SVG_TAG_NAMES: [&str; 37] = [
+  "altGlyph", "altGlyphDef", "altGlyphItem", "animateColor", "animateMotion", "animateTransform",
+  "clipPath", "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite",
+  "feConvolveMatrix", "feDiffuseLighting", "feDisplacementMap", "feDistantLight", "feDropShadow",
+  "feFlood", "feFuncA", "feFuncB", "feFuncG", "feFuncR", "feGaussianBlur", "feImage", "feMerge",
+  "feMergeNode", "feMorphology", "feOffset", "fePointLight", "feSpecularLighting", "feSpotLight",
+  "feTile", "feTurbulence", "foreignObject", "glyphRef", "linearGradient", "radialGradient",
+  "textPath",
+];
+
+// SVG attribute names with uppercase letters
+// https://html.spec.whatwg.org/multipage/parsing.html#adjust-svg-attributes
+const SVG_ATTRIBUTE_NAMES: [&str; 58] = [
+  "attributeName", "attributeType", "baseFrequency", "baseProfile", "calcMode", "clipPathUnits",
+  "diffuseConstant", "edgeMode", "filterUnits", "glyphRef", "gradientTransform", "gradientUnits",
+  "kernelMatrix", "kernelUnitLength", "keyPoints", "keySplines", "keyTimes", "lengthAdjust",
+  "limitingConeAngle", "markerHeight", "markerUnits", "markerWidth", "maskContentUnits",
+  "maskUnits", "numOctaves", "pathLength", "patternContentUnits", "patternTransform",
+  "patternUnits", "pointsAtX", "pointsAtY", "pointsAtZ", "preserveAlpha", "preserveAspectRatio",
+  "primitiveUnits", "refX", "refY", "repeatCount", "repeatDur", "requiredExtensions",
+  "requiredFeatures", "specularConstant", "specularExponent", "spreadMethod", "startOffset",
+  "stdDeviation", "stitchTiles", "surfaceScale", "systemLanguage", "tableValues", "targetX",
+  "targetY", "textLength", "viewBox", "viewTarget", "xChannelSelector", "yChannelSelector",
+  "zoomAndPan",
+];
+
+// HTML start tags that close the open SVG and MathML elements, as they can't be foreign
+const BREAKOUT_ELEMENTS: [&str; 44] = [
+  "b", "big", "blockquote", "body", "br", "center", "code", "dd", "div", "dl", "dt", "em",
+  "embed", "h1", "h2", "h3", "h4", "h5", "h6", "head", "hr", "i", "img", "li", "listing", "menu",
+  "meta", "nobr", "ol", "p", "pre", "ruby", "s", "small", "span", "strong", "strike", "sub",
+  "sup", "table", "tt", "u", "ul", "var",
+];
+
+// SVG and MathML elements are kept in the list of open elements as 'svg <name>' and
+// 'math <name>', so that they are never mistaken for the HTML elements of the same name
+pub(super) fn open_element_name(namespace: dom::Namespace, tag_name: &str) -> String {
+  match namespace {
+    dom::Namespace::Html => tag_name.to_string(),
+    dom::Namespace::Svg => format!("svg {}", tag_name),
+    dom::Namespace::MathMl => format!("math {}", tag_name),
+  }
+}
+
+// Return the namespace and the tag name of an entry of the list of open elements
+pub(super) fn split_open_element_name(name: &str) -> (dom::Namespace, &str) {
+  if let Some(tag_name) = name.strip_prefix("svg ") {
+    (dom::Namespace::Svg, tag_name)
+  } else if let Some(tag_name) = name.strip_prefix("math ") {
+    (dom::Namespace::MathMl, tag_name)
+  } else {
+    (dom::Namespace::Html, name)
+  }
+}
+
+// Elements of foreign content whose contents are HTML
+// https://html.spec.whatwg.org/multipage/parsing.html#html-integration-point
+pub(super) fn is_html_integration_point(open_element: &str) -> bool {
+  matches!(open_element, "svg foreignObject" | "svg desc" | "svg title")
+}
+
+// A MathML <annotation-xml> is an HTML integration point too when its 'encoding' says that it
+// contains HTML. This depends on an attribute, so it is recorded when the element is opened
+// https://html.spec.whatwg.org/multipage/parsing.html#html-integration-point
+pub(super) fn is_html_annotation_xml(
+  namespace: dom::Namespace,
+  tag_name: &str,
+  attributes: &dom::AttributeMap,
+) -> bool {
+  namespace == dom::Namespace::MathMl
+    && tag_name == "annotation-xml"
+    && attributes.get("encoding").is_some_and(|encoding: &String| {
+      encoding.eq_ignore_ascii_case("text/html")
+        || encoding.eq_ignore_ascii_case("application/xhtml+xml")
+    })
+}
+
+// MathML elements whose contents are text and HTML elements, except for <mglyph> and
+// <malignmark>
+// https://html.spec.whatwg.org/multipage/parsing.html#mathml-text-integration-point
+pub(super) fn is_mathml_text_integration_point(open_element: &str) -> bool {
+  matches!(open_element, "math mi" | "math mo" | "math mn" | "math ms" | "math mtext")
+}
+
+// Does this start tag close the open SVG and MathML elements?
+pub(super) fn is_breakout_element(tag_name: &str) -> bool {
+  BREAKOUT_ELEMENTS.contains(&tag_name)
+}
+
+// A <font> start tag only closes the open SVG and MathML elements if it has one of the
+// presentational attributes
+pub(super) fn is_breakout_font(attributes: &dom::AttributeMap) -> bool {
+  ["color", "face", "size"]
+    .iter()
+    .any(|name: &&str| attributes.contains_key(name))
+}
+
+// Find a name of 'names' that is the same as 'lowercase_name' but for case
+fn restore_case(names: &[&'static str], lowercase_name: &str) -> Option<&'static str> {
+  names.iter().copied().find(|name: &&str| name.eq_ignore_ascii_case(lowercase_name))
+}
+
+// Restore the case of an SVG tag name
+pub(super) fn adjust_tag_name(namespace: dom::Namespace, tag_name: String) -> String {
+  match namespace {
+    dom::Namespace::Svg => restore_case(&SVG_TAG_NAMES, &tag_name)
+      .map(str::to_string)
+      .unwrap_or(tag_name),
+    _ => tag_name,
+  }
+}
+
+// Restore the case of SVG and MathML attribute names
+// https://html.spec.whatwg.org/multipage/parsing.html#adjust-mathml-attributes
+pub(super) fn adjust_attributes(
+  namespace: dom::Namespace,
+  attributes: dom::AttributeMap,
+) -> dom::AttributeMap {
+  attributes
+    .into_iter()
+    .map(|(name, value): (String, String)| {
+      let adjusted_name: Option<&'static str> = match namespace {
+        dom::Namespace::Svg => restore_case(&SVG_ATTRIBUTE_NAMES, &name),
+        dom::Namespace::MathMl if name == "definitionurl" => Some("definitionURL"),
+        _ => None,
+      };
+      (adjusted_name.map(str::to_string).unwrap_or(name), value)
+    })
+    .collect()
+}
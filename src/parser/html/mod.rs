@@ -6,27 +6,131 @@
 use crate::dom;
 use crate::hashmap;
 use crate::parser::text::TextParser;
+use entities::NAMED_CHARACTER_REFERENCES;
 
+mod entities;
+mod foreign_content;
+mod quirks;
 #[cfg(test)]
 mod tests;
+mod tree_construction;
+
+pub(crate) use tree_construction::is_void_element;
+
+// Code points in the C1 control range that numeric character references remap to their
+// Windows-1252 characters, as browsers have always done
+// https://html.spec.whatwg.org/multipage/parsing.html#numeric-character-reference-end-state
+const C1_REPLACEMENTS: [(u32, char); 27] = [
+  (0x80, '\u{20AC}'),
+  (0x82, '\u{201A}'),
+  (0x83, '\u{0192}'),
+  (0x84, '\u{201E}'),
+  (0x85, '\u{2026}'),
+  (0x86, '\u{2020}'),
+  (0x87, '\u{2021}'),
+  (0x88, '\u{02C6}'),
+  (0x89, '\u{2030}'),
+  (0x8A, '\u{0160}'),
+  (0x8B, '\u{2039}'),
+  (0x8C, '\u{0152}'),
+  (0x8E, '\u{017D}'),
+  (0x91, '\u{2018}'),
+  (0x92, '\u{2019}'),
+  (0x93, '\u{201C}'),
+  (0x94, '\u{201D}'),
+  (0x95, '\u{2022}'),
+  (0x96, '\u{2013}'),
+  (0x97, '\u{2014}'),
+  (0x98, '\u{02DC}'),
+  (0x99, '\u{2122}'),
+  (0x9A, '\u{0161}'),
+  (0x9B, '\u{203A}'),
+  (0x9C, '\u{0153}'),
+  (0x9E, '\u{017E}'),
+  (0x9F, '\u{0178}'),
+];
+
+// Elements whose contents are raw text, consumed verbatim up to the matching end tag
+// https://html.spec.whatwg.org/multipage/syntax.html#raw-text-elements
+pub(crate) const RAW_TEXT_ELEMENTS: [&str; 6] = ["script", "style", "xmp", "iframe", "noembed", "noframes"];
+
+// Elements whose contents are raw text in which character references are still decoded
+const ESCAPABLE_RAW_TEXT_ELEMENTS: [&str; 2] = ["textarea", "title"];
+
+// The longest name in the named character reference table ("CounterClockwiseContourIntegral;")
+const MAX_CHARACTER_REFERENCE_NAME_LENGTH: usize = 32;
+
+// Elements nested deeper than this are parsed as siblings of the deepest element instead, so
+// that hostile input can't exhaust the stack. Each level of nesting takes a few kilobytes of
+// stack in debug builds, so this stays well within a 2 MB thread stack
+pub(crate) const MAX_NESTING_DEPTH: usize = 128;
+
+// Tag and attribute names end at whitespace, '/' or '>'
+fn is_tag_name_char(c: char) -> bool {
+  !c.is_whitespace() && c != '/' && c != '>'
+}
 
 pub struct HTMLParser {
   text_parser: TextParser,
+  // Force-quirks flag of the last doctype parsed
+  force_quirks: bool,
+  // Document mode, which changes how some tags are parsed (e.g. <table> doesn't close <p> in quirks mode)
+  quirks_mode: dom::QuirksMode,
+  // Tag names of the elements being parsed, from the root to the current element. SVG and
+  // MathML elements have the prefix of their namespace, e.g. 'svg rect'
+  open_elements: Vec<String>,
+  // Indices in 'open_elements' of the MathML <annotation-xml> elements that contain HTML
+  html_annotation_xml: Vec<usize>,
+  // Attributes of the <html>, <head> and <body> start tags, wherever they appear in the input
+  html_attributes: dom::AttributeMap,
+  head_attributes: dom::AttributeMap,
+  body_attributes: dom::AttributeMap,
+  // Errors recovered from so far, in source order
+  diagnostics: Vec<dom::Diagnostic>,
 }
 
 impl HTMLParser {
   pub fn new(position: usize, input: String) -> Self {
     Self {
       text_parser: TextParser::new(position, input),
+      force_quirks: false,
+      quirks_mode: dom::QuirksMode::NoQuirks,
+      open_elements: vec![],
+      html_annotation_xml: vec![],
+      html_attributes: hashmap![],
+      head_attributes: hashmap![],
+      body_attributes: hashmap![],
+      diagnostics: vec![],
     }
   }
 
-  // Parse a tag or attribute name
+  // Return the span from a byte offset of the input to the current position
+  fn span_from(&self, start: usize) -> dom::Span {
+    let (line, column): (usize, usize) = self.text_parser.line_column(start);
+    dom::Span::new(start, self.text_parser.position(), line, column)
+  }
+
+  // Record an error that the parser recovered from
+  fn report(&mut self, kind: dom::DiagnosticKind, span: dom::Span, message: String) {
+    self.diagnostics.push(dom::Diagnostic::new(kind, span, message));
+  }
+
+  // Parse a tag name: anything up to whitespace, '/' or '>', e.g. 'my-element'
   fn parse_tag_name(&mut self) -> String {
-    self.text_parser.consume_while(|c: char| match c {
-      'a'..='z' | 'A'..='Z' | '0'..='9' => true,
-      _ => false,
-    })
+    self.text_parser.consume_while(is_tag_name_char)
+  }
+
+  // Parse an attribute name: anything up to whitespace, '/', '>' or '='. A leading '=' belongs
+  // to the name, so that every attribute consumes some input
+  fn parse_attribute_name(&mut self) -> String {
+    let mut name: String = String::new();
+    if self.text_parser.next_char() == Some('=') {
+      self.text_parser.consume_char();
+      name.push('=');
+    }
+    name.push_str(&self.text_parser.consume_while(|c: char| is_tag_name_char(c) && c != '='));
+    // Attribute names of HTML elements are case-insensitive
+    name.to_ascii_lowercase()
   }
 
   // Parse a comment
@@ -34,66 +138,396 @@ impl HTMLParser {
     self.text_parser.consume_until_match("-->")
   }
 
-  // Parse a text node
+  // Parse a numeric character reference, after the '&#' has been consumed
+  fn parse_numeric_character_reference(&mut self) -> String {
+    let prefix: String = match self.text_parser.next_char() {
+      Some(x @ ('x' | 'X')) => {
+        self.text_parser.consume_char();
+        format!("&#{}", x)
+      }
+      _ => "&#".to_string(),
+    };
+    let hexadecimal: bool = prefix.len() > 2;
+    let digits: String = if hexadecimal {
+      self.text_parser.consume_while(|c: char| c.is_ascii_hexdigit())
+    } else {
+      self.text_parser.consume_while(|c: char| c.is_ascii_digit())
+    };
+    // Recovery: a reference without digits is not a reference at all, keep it as text
+    if digits.is_empty() {
+      return prefix;
+    }
+    // Recovery: the ';' is optional
+    if self.text_parser.next_char() == Some(';') {
+      self.text_parser.consume_char();
+    }
+
+    // Overflowing values are out of range anyway, so saturate instead of failing
+    let code_point: u32 = u32::from_str_radix(&digits, if hexadecimal { 16 } else { 10 })
+      .unwrap_or(u32::MAX);
+    let character: char = match code_point {
+      // Null, out of range and surrogate code points become the replacement character
+      0 | 0xD800..=0xDFFF | 0x110000.. => '\u{FFFD}',
+      0x80..=0x9F => C1_REPLACEMENTS
+        .iter()
+        .find(|&&(c1, _)| c1 == code_point)
+        .map(|&(_, replacement)| replacement)
+        .unwrap_or_else(|| char::from_u32(code_point).unwrap_or('\u{FFFD}')),
+      _ => char::from_u32(code_point).unwrap_or('\u{FFFD}'),
+    };
+    character.to_string()
+  }
+
+  // Parse a named character reference, after the '&' has been consumed, matching the longest
+  // name in the table
+  fn parse_named_character_reference(&mut self, in_attribute: bool) -> String {
+    let rest: &str = self.text_parser.rest();
+    let longest: usize = rest.len().min(MAX_CHARACTER_REFERENCE_NAME_LENGTH);
+    let reference: Option<(&str, &str)> = (1..=longest)
+      .rev()
+      .filter(|&length: &usize| rest.is_char_boundary(length))
+      .find_map(|length: usize| {
+        NAMED_CHARACTER_REFERENCES
+          .binary_search_by_key(&&rest[..length], |&(name, _)| name)
+          .ok()
+          .map(|index: usize| NAMED_CHARACTER_REFERENCES[index])
+      });
+
+    match reference {
+      Some((name, characters)) => {
+        // For historical reasons, a legacy reference without ';' inside an attribute value
+        // is left alone when followed by '=' or an alphanumeric character (e.g. "?a=1&copy=2")
+        let next: Option<char> = rest[name.len()..].chars().next();
+        if in_attribute
+          && !name.ends_with(';')
+          && next.is_some_and(|c: char| c == '=' || c.is_ascii_alphanumeric())
+        {
+          return "&".to_string();
+        }
+        self.text_parser.increment_position(name.len());
+        characters.to_string()
+      }
+      // Recovery: an unknown name is kept as text
+      None => "&".to_string(),
+    }
+  }
+
+  // Parse a character reference (e.g. '&amp;', '&#169;' or '&#x1F600;') and return the
+  // characters it stands for, or the source text itself if it is not a valid reference
+  // https://html.spec.whatwg.org/multipage/parsing.html#character-reference-state
+  fn parse_character_reference(&mut self, in_attribute: bool) -> String {
+    self.text_parser.consume_char(); // consume '&'
+    if self.text_parser.eof() {
+      return "&".to_string();
+    }
+    if self.text_parser.next_char() == Some('#') {
+      self.text_parser.consume_char();
+      if self.text_parser.eof() {
+        return "&#".to_string();
+      }
+      return self.parse_numeric_character_reference();
+    }
+    self.parse_named_character_reference(in_attribute)
+  }
+
+  // Consume characters until 'test' returns false, decoding character references on the way
+  fn consume_decoded_while<F>(&mut self, test: F, in_attribute: bool) -> String
+  where
+    F: Fn(char) -> bool,
+  {
+    let mut result: String = String::new();
+    loop {
+      result.push_str(&self.text_parser.consume_while(|c: char| c != '&' && test(c)));
+      if !self.text_parser.next_char().is_some_and(&test) {
+        break;
+      }
+      result.push_str(&self.parse_character_reference(in_attribute));
+    }
+    result
+  }
+
+  // Is the input at markup, i.e. a '<' that starts a tag, an end tag, a comment or a declaration?
+  // Any other '<' is just text
+  fn at_markup(&self) -> bool {
+    let mut chars = self.text_parser.rest().chars();
+    chars.next() == Some('<')
+      && chars
+        .next()
+        .is_some_and(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+  }
+
+  // Return whether the input is at an end tag and the lowercase tag name, without consuming
+  // anything. Return None if the input is not at a start or end tag
+  fn peek_tag(&self) -> Option<(bool, String)> {
+    let rest: &str = self.text_parser.rest();
+    let (is_end_tag, rest): (bool, &str) = match rest.strip_prefix("</") {
+      Some(rest) => (true, rest),
+      None => (false, rest.strip_prefix('<')?),
+    };
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+      return None;
+    }
+    let tag_name: String = rest
+      .chars()
+      .take_while(|&c: &char| is_tag_name_char(c))
+      .collect::<String>()
+      .to_ascii_lowercase();
+    Some((is_end_tag, tag_name))
+  }
+
+  // The namespace of the current element
+  fn current_namespace(&self) -> dom::Namespace {
+    match self.open_elements.last() {
+      Some(current) => foreign_content::split_open_element_name(current).0,
+      None => dom::Namespace::Html,
+    }
+  }
+
+  // Is the input at a CDATA section? They are only allowed in SVG and MathML elements, elsewhere
+  // they are bogus comments
+  fn at_cdata_section(&self) -> bool {
+    self.text_parser.starts_with("<![CDATA[") && self.current_namespace() != dom::Namespace::Html
+  }
+
+  // Parse a CDATA section, e.g. '<![CDATA[a < b]]>', and return its contents as they are
+  fn parse_cdata_section(&mut self) -> String {
+    let start: usize = self.text_parser.position();
+    self.text_parser.increment_position("<![CDATA[".len());
+    let text: String = self.text_parser.consume_until_match("]]>");
+    // Recovery: the end of the input closes the section
+    if self.text_parser.eof() {
+      let span: dom::Span = self.span_from(start);
+      self.report(
+        dom::DiagnosticKind::UnterminatedCdata,
+        span,
+        "CDATA section is not closed with ']]>'".to_string(),
+      );
+    } else {
+      self.text_parser.increment_position("]]>".len());
+    }
+    text
+  }
+
+  // Parse a text node, including the CDATA sections in it
   fn parse_text(&mut self) -> dom::Node {
-    dom::Node::text(self.text_parser.consume_while(|c: char| c != '<'))
+    let start: usize = self.text_parser.position();
+    let mut text: String = String::new();
+    loop {
+      text.push_str(&self.consume_decoded_while(|c: char| c != '<', false));
+      if self.at_cdata_section() {
+        text.push_str(&self.parse_cdata_section());
+        continue;
+      }
+      if self.text_parser.eof() || self.at_markup() {
+        break;
+      }
+      // Recovery: a '<' that doesn't start markup, e.g. 'a < b', is kept as text
+      let less_than: usize = self.text_parser.position();
+      text.extend(self.text_parser.consume_char());
+      let span: dom::Span = self.span_from(less_than);
+      self.report(
+        dom::DiagnosticKind::UnescapedLessThan,
+        span,
+        "'<' doesn't start a tag, write '&lt;' instead".to_string(),
+      );
+    }
+    dom::Node::text(text).with_span(self.span_from(start))
   }
 
-  // Parse a single element, including its open tag, contents, and closing tag
-  fn parse_element(&mut self) -> Result<dom::Node, String> {
-    let self_closing_tags_by_default: Vec<&str> = vec!["br", "img", "input", "meta", "link", "hr"];
-    let mut self_closing_tag_flag: bool = false;
-    let mut children: Vec<dom::Node> = vec![];
+  // Parse the contents of a raw text element up to (but not including) its matching end tag,
+  // decoding character references only if the element is escapable
+  fn parse_raw_text(&mut self, tag_name: &str, escapable: bool) -> String {
+    let rest: &str = self.text_parser.rest();
+    // ASCII lowercasing keeps byte offsets intact, so indices found here are valid in 'rest'
+    let lowercase_rest: String = rest.to_ascii_lowercase();
+    let end_tag: String = format!("</{}", tag_name.to_ascii_lowercase());
 
-    // Opening tag
+    // The end tag must be followed by whitespace, '/' or '>' (e.g. "</scripts>" does not count)
+    let mut end: usize = rest.len();
+    let mut search_from: usize = 0;
+    while let Some(index) = lowercase_rest[search_from..].find(&end_tag) {
+      let candidate: usize = search_from + index;
+      let after: Option<char> = lowercase_rest[candidate + end_tag.len()..].chars().next();
+      if after.is_none_or(|c: char| c.is_whitespace() || c == '/' || c == '>') {
+        end = candidate;
+        break;
+      }
+      search_from = candidate + end_tag.len();
+    }
+
+    let text: String = rest[..end].to_string();
+    self.text_parser.increment_position(end);
+    if escapable {
+      HTMLParser::new(0, text).consume_decoded_while(|_| true, false)
+    } else {
+      text
+    }
+  }
+
+  // Parse a start tag and return its lowercase tag name, its attributes and whether it is
+  // self-closing
+  fn parse_start_tag(&mut self) -> Result<(String, dom::AttributeMap, bool), String> {
     self.text_parser.expect_char('<')?;
-    let tag_name: String = self.parse_tag_name();
+    let tag_name: String = self.parse_tag_name().to_ascii_lowercase();
     let attributes: dom::AttributeMap = self.parse_attributes()?;
 
-    if self.text_parser.next_char() == '/' {
-      self_closing_tag_flag = true;
+    // The self-closing flag only matters for SVG and MathML elements: HTML elements ignore it,
+    // and void elements never have contents anyway
+    let self_closing: bool = self.text_parser.starts_with("/>");
+    if self_closing {
       self.text_parser.expect_char('/')?;
     }
-    if self.text_parser.next_char() == '>' {
-      // Closing character '>' could not be inline
-      self.text_parser.consume_whitespace();
+    // Recovery: a tag cut off by the end of the input ends there
+    if !self.text_parser.eof() {
       self.text_parser.expect_char('>')?;
     }
+    Ok((tag_name, attributes, self_closing))
+  }
 
-    if !self_closing_tag_flag
-      && self_closing_tags_by_default
-        .iter()
-        .all(|self_closing_tag| !tag_name.contains(self_closing_tag))
-    {
-      // Contents
-      children = self.parse_nodes()?;
+  // Return the attributes of the start tag at the input, without consuming it
+  fn peek_attributes(&self) -> dom::AttributeMap {
+    let rest: &str = self.text_parser.rest();
+    let end: usize = rest.find('>').map_or(rest.len(), |index: usize| index + 1);
+    HTMLParser::new(0, rest[..end].to_string())
+      .parse_start_tag()
+      .map(|(_, attributes, _)| attributes)
+      .unwrap_or_default()
+  }
 
-      // Recovery: if we reached EOF, the element was never closed — return it as-is
-      if self.text_parser.eof() {
-        return Ok(dom::Node::element(tag_name, attributes, children));
+  // Parse an end tag, ignoring anything between its name and the closing '>'
+  fn parse_end_tag(&mut self) -> Result<String, String> {
+    self.text_parser.expect_char('<')?;
+    self.text_parser.expect_char('/')?;
+    let tag_name: String = self.parse_tag_name().to_ascii_lowercase();
+    self.text_parser.consume_while(|c: char| c != '>');
+    if !self.text_parser.eof() {
+      self.text_parser.consume_char(); // consume '>'
+    }
+    Ok(tag_name)
+  }
+
+  // Parse the contents of an element whose start tag has been consumed, then its end tag if the
+  // input is at it, and return whether the end tag was found. Otherwise the element was closed
+  // implicitly: by EOF, by a start tag that implies its end tag, or by the end tag of one of
+  // its ancestors, left for the ancestor
+  fn parse_element_contents(
+    &mut self,
+    namespace: dom::Namespace,
+    tag_name: &str,
+  ) -> Result<(Vec<dom::Node>, bool), String> {
+    self.open_elements.push(foreign_content::open_element_name(namespace, tag_name));
+    let children: Vec<dom::Node> = self.parse_nodes()?;
+    self.open_elements.pop();
+
+    if let Some((true, end_tag_name)) = self.peek_tag() {
+      let matches: bool = match namespace {
+        dom::Namespace::Html => tree_construction::end_tag_matches(tag_name, &end_tag_name),
+        _ => tag_name.eq_ignore_ascii_case(&end_tag_name),
+      };
+      if matches {
+        self.parse_end_tag()?;
+        return Ok((children, true));
       }
+    }
+    Ok((children, false))
+  }
 
-      // Closing tag
-      self.text_parser.expect_char('<')?;
-      self.text_parser.expect_char('/')?;
-      let closing_tag: String = self.parse_tag_name();
-      if closing_tag != tag_name {
-        // Recovery: mismatched closing tag — close the current element anyway and skip to '>'
-        self.text_parser.consume_while(|c: char| c != '>');
-        if !self.text_parser.eof() {
-          self.text_parser.consume_char(); // consume '>'
-        }
-        return Ok(dom::Node::element(tag_name, attributes, children));
+  // Report an element closed without its end tag, unless the end tag may be left out
+  fn report_unclosed_element(&mut self, tag_name: &str, start_tag_span: dom::Span) {
+    if !tree_construction::has_optional_end_tag(tag_name) {
+      self.report(
+        dom::DiagnosticKind::UnclosedElement,
+        start_tag_span,
+        format!("Element '<{}>' is not closed", tag_name),
+      );
+    }
+  }
+
+  // Parse a single element, including its open tag, contents, and closing tag
+  fn parse_element(&mut self) -> Result<dom::Node, String> {
+    let start: usize = self.text_parser.position();
+    let (tag_name, attributes, _): (String, dom::AttributeMap, bool) = self.parse_start_tag()?;
+    let start_tag_span: dom::Span = self.span_from(start);
+    if tree_construction::is_void_element(&tag_name) {
+      return Ok(dom::Node::element(tag_name, attributes, vec![]).with_span(start_tag_span));
+    }
+    // A newline right after the start tag of <pre>, <listing> and <textarea> is not part of
+    // their contents, so that they can start on the line after the tag
+    if matches!(&*tag_name, "pre" | "listing" | "textarea") && self.text_parser.starts_with("\n") {
+      self.text_parser.consume_char();
+    }
+
+    let escapable: bool = ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&&*tag_name);
+    if escapable || RAW_TEXT_ELEMENTS.contains(&&*tag_name) {
+      let text_start: usize = self.text_parser.position();
+      let text: String = self.parse_raw_text(&tag_name, escapable);
+      let text_span: dom::Span = self.span_from(text_start);
+      if self.text_parser.eof() {
+        self.report_unclosed_element(&tag_name, start_tag_span);
+      } else {
+        self.parse_end_tag()?;
       }
-      // Closing character '>' could not be inline
-      self.text_parser.consume_whitespace();
-      self.text_parser.expect_char('>')?;
+      let children: Vec<dom::Node> = if text.is_empty() {
+        vec![]
+      } else {
+        vec![dom::Node::text(text).with_span(text_span)]
+      };
+      let element: dom::Node = dom::Node::element(tag_name, attributes, children);
+      return Ok(element.with_span(self.span_from(start)));
     }
-    Ok(dom::Node::element(tag_name, attributes, children))
+
+    let (children, closed): (Vec<dom::Node>, bool) =
+      self.parse_element_contents(dom::Namespace::Html, &tag_name)?;
+    if !closed {
+      self.report_unclosed_element(&tag_name, start_tag_span);
+    }
+    let element: dom::Node = dom::Node::element(tag_name, attributes, children);
+    Ok(element.with_span(self.span_from(start)))
+  }
+
+  // Parse an SVG or MathML element, adjusting the case of its names. Unlike HTML elements, any
+  // of them can be self-closing, and none of them contains raw text
+  fn parse_foreign_element(&mut self, namespace: dom::Namespace) -> Result<dom::Node, String> {
+    let start: usize = self.text_parser.position();
+    let (tag_name, attributes, self_closing): (String, dom::AttributeMap, bool) =
+      self.parse_start_tag()?;
+    let tag_name: String = foreign_content::adjust_tag_name(namespace, tag_name);
+    let attributes: dom::AttributeMap = foreign_content::adjust_attributes(namespace, attributes);
+    let start_tag_span: dom::Span = self.span_from(start);
+    if self_closing {
+      let element: dom::Node =
+        dom::Node::element_in_namespace(namespace, tag_name, attributes, vec![]);
+      return Ok(element.with_span(start_tag_span));
+    }
+
+    let html_annotation_xml: bool =
+      foreign_content::is_html_annotation_xml(namespace, &tag_name, &attributes);
+    if html_annotation_xml {
+      self.html_annotation_xml.push(self.open_elements.len());
+    }
+    let (children, closed): (Vec<dom::Node>, bool) =
+      self.parse_element_contents(namespace, &tag_name)?;
+    if html_annotation_xml {
+      self.html_annotation_xml.pop();
+    }
+    if !closed {
+      self.report_unclosed_element(&tag_name, start_tag_span);
+    }
+    let element: dom::Node =
+      dom::Node::element_in_namespace(namespace, tag_name, attributes, children);
+    Ok(element.with_span(self.span_from(start)))
+  }
+
+  // Parse an element whose start tag is implied by its first child, such as the <tbody> of a
+  // table row written directly inside <table>. It has no span, as it doesn't appear in the source
+  fn parse_implied_element(&mut self, tag_name: &str) -> Result<dom::Node, String> {
+    let children: Vec<dom::Node> = self.parse_element_contents(dom::Namespace::Html, tag_name)?.0;
+    Ok(dom::Node::element(tag_name.to_string(), hashmap![], children))
   }
 
   // Parse a single comment, including its open tag, contents, and closing tag
   fn parse_comment(&mut self) -> Result<dom::Node, String> {
+    let start: usize = self.text_parser.position();
     // Opening tag
     self.text_parser.expect_char('<')?;
     self.text_parser.expect_char('!')?;
@@ -105,34 +539,152 @@ impl HTMLParser {
 
     // Closing characters '-->' could not be inline
     self.text_parser.consume_whitespace();
+    // Recovery: the end of the input closes the comment
+    if self.text_parser.eof() {
+      let span: dom::Span = self.span_from(start);
+      self.report(
+        dom::DiagnosticKind::UnterminatedComment,
+        span,
+        "Comment is not closed with '-->'".to_string(),
+      );
+      return Ok(dom::Node::comment(comment).with_span(span));
+    }
     // Closing tag
     self.text_parser.expect_char('-')?;
     self.text_parser.expect_char('-')?;
     self.text_parser.expect_char('>')?;
 
-    Ok(dom::Node::comment(comment))
+    Ok(dom::Node::comment(comment).with_span(self.span_from(start)))
+  }
+
+  // Parse a quoted public or system identifier of a doctype, or return None if it is not quoted
+  fn parse_doctype_identifier(&mut self) -> Option<String> {
+    self.text_parser.consume_whitespace();
+    let open_quote: char = self.text_parser.next_char().filter(|&c: &char| c == '"' || c == '\'')?;
+    self.text_parser.consume_char();
+    // Recovery: a '>' ends the identifier even if the closing quote is missing
+    let identifier: String = self.text_parser.consume_while(|c: char| c != open_quote && c != '>');
+    if self.text_parser.next_char() == Some(open_quote) {
+      self.text_parser.consume_char();
+    }
+    Some(identifier)
+  }
+
+  // Parse a doctype, e.g. '<!DOCTYPE html>' or
+  // '<!DOCTYPE html PUBLIC "-//W3C//DTD HTML 4.01//EN" "http://www.w3.org/TR/html4/strict.dtd">'
+  // https://html.spec.whatwg.org/multipage/parsing.html#doctype-state
+  fn parse_doctype(&mut self) -> Result<dom::Node, String> {
+    let start: usize = self.text_parser.position();
+    // Opening tag
+    self.text_parser.expect_char('<')?;
+    self.text_parser.expect_char('!')?;
+    let keyword: String = self.text_parser.consume_while(|c: char| c.is_ascii_alphabetic());
+    if !keyword.eq_ignore_ascii_case("doctype") {
+      return Err(format!("Expected 'DOCTYPE', found '{}'", keyword));
+    }
+    self.text_parser.consume_whitespace();
+
+    // A doctype without a name is malformed and forces quirks mode
+    let name: String = self
+      .text_parser
+      .consume_while(|c: char| !c.is_whitespace() && c != '>')
+      .to_ascii_lowercase();
+    let mut force_quirks: bool = name.is_empty();
+    self.text_parser.consume_whitespace();
+
+    let mut public_id: Option<String> = None;
+    let mut system_id: Option<String> = None;
+    let identifier_keyword: String = self
+      .text_parser
+      .consume_while(|c: char| c.is_ascii_alphabetic())
+      .to_ascii_lowercase();
+    match &*identifier_keyword {
+      "public" => {
+        public_id = self.parse_doctype_identifier();
+        force_quirks |= public_id.is_none();
+        system_id = self.parse_doctype_identifier();
+      }
+      "system" => {
+        system_id = self.parse_doctype_identifier();
+        force_quirks |= system_id.is_none();
+      }
+      "" => force_quirks |= self.text_parser.next_char().is_some_and(|c: char| c != '>'),
+      // Recovery: an unknown keyword makes the rest of the doctype bogus
+      _ => force_quirks = true,
+    }
+
+    // Recovery: skip anything else up to the closing '>'
+    self.text_parser.consume_while(|c: char| c != '>');
+    if self.text_parser.eof() {
+      force_quirks = true;
+    } else {
+      self.text_parser.expect_char('>')?;
+    }
+
+    let span: dom::Span = self.span_from(start);
+    if force_quirks {
+      self.report(
+        dom::DiagnosticKind::MalformedDoctype,
+        span,
+        "Malformed doctype, the document is rendered in quirks mode".to_string(),
+      );
+    }
+    self.force_quirks = force_quirks;
+    Ok(dom::Node::doctype(name, public_id, system_id).with_span(span))
+  }
+
+  // Parse a bogus comment such as '<!ELEMENT br EMPTY>', '<?xml version="1.0"?>' or '</ 3>',
+  // keeping its contents as a comment
+  fn parse_bogus_comment(&mut self) -> Result<dom::Node, String> {
+    let start: usize = self.text_parser.position();
+    self.text_parser.expect_char('<')?;
+    if matches!(self.text_parser.next_char(), Some('!' | '/')) {
+      self.text_parser.consume_char();
+    }
+    let comment: String = self.text_parser.consume_while(|c: char| c != '>');
+    if !self.text_parser.eof() {
+      self.text_parser.consume_char(); // consume '>'
+    }
+    let span: dom::Span = self.span_from(start);
+    self.report(
+      dom::DiagnosticKind::BogusComment,
+      span,
+      format!("Unexpected markup '<{}>', treated as a comment", comment),
+    );
+    Ok(dom::Node::comment(comment).with_span(span))
   }
 
   // Parse a single node
   fn parse_node(&mut self) -> Result<dom::Node, String> {
-    match self.text_parser.next_char() {
-      '<' => {
-        if self.text_parser.next_offset_char(1) == '!' {
-          return self.parse_comment();
-        }
-        self.parse_element()
-      }
-      _ => Ok(self.parse_text()),
+    if !self.at_markup() || self.at_cdata_section() {
+      return Ok(self.parse_text());
+    }
+    if self.text_parser.starts_with("<!--") {
+      return self.parse_comment();
+    }
+    if self.text_parser.starts_with_ignore_case("<!doctype") {
+      return self.parse_doctype();
     }
+    if self.peek_tag().is_some_and(|(is_end_tag, _)| !is_end_tag) {
+      return self.parse_element();
+    }
+    self.parse_bogus_comment()
   }
 
   // Parse a single name="value" pair
   fn parse_attribute(&mut self) -> Result<(String, String), String> {
-    let name: String = self.parse_tag_name();
+    let start: usize = self.text_parser.position();
+    let name: String = self.parse_attribute_name();
+    let name_span: dom::Span = self.span_from(start);
     // Consume optional whitespace between the name and '=' (e.g. class = "foo")
     self.text_parser.consume_whitespace();
     // Recovery: an attribute without '=' is treated as a boolean attribute with an empty value
-    if self.text_parser.eof() || self.text_parser.next_char() != '=' {
+    if self.text_parser.next_char() != Some('=') {
+      self.report(
+        dom::DiagnosticKind::AttributeWithoutValue,
+        name_span,
+        format!("Attribute '{}' has no value, treated as empty", name),
+      );
       return Ok((name, String::new()));
     }
     self.text_parser.expect_char('=')?;
@@ -144,16 +696,27 @@ impl HTMLParser {
 
   // Parse a quoted value
   fn parse_attribute_value(&mut self) -> Result<String, String> {
-    let open_quote: char = self.text_parser.consume_char();
-    if open_quote != '"' && open_quote != '\'' {
-      // Recovery: unquoted value — read until whitespace, '/', or '>'
-      let rest: String = self.text_parser.consume_while(|c: char| {
-        c != ' ' && c != '\t' && c != '\n' && c != '/' && c != '>'
-      });
-      return Ok(format!("{}{}", open_quote, rest));
+    let open_quote: Option<char> =
+      self.text_parser.next_char().filter(|&c: &char| c == '"' || c == '\'');
+    let Some(open_quote) = open_quote else {
+      // Recovery: unquoted value — read until whitespace or '>'
+      let start: usize = self.text_parser.position();
+      let value: String =
+        self.consume_decoded_while(|c: char| !c.is_whitespace() && c != '>', true);
+      let span: dom::Span = self.span_from(start);
+      self.report(
+        dom::DiagnosticKind::UnquotedAttributeValue,
+        span,
+        format!("Attribute value '{}' is not quoted", value),
+      );
+      return Ok(value);
+    };
+    self.text_parser.consume_char();
+    let value: String = self.consume_decoded_while(|c: char| c != open_quote, true);
+    // Recovery: the end of the input closes the value
+    if !self.text_parser.eof() {
+      self.text_parser.expect_char(open_quote)?;
     }
-    let value: String = self.text_parser.consume_while(|c: char| c != open_quote);
-    self.text_parser.expect_char(open_quote)?;
     Ok(value)
   }
 
@@ -162,37 +725,428 @@ impl HTMLParser {
     let mut attributes: dom::AttributeMap = hashmap![];
     loop {
       self.text_parser.consume_whitespace();
-      if self.text_parser.next_char() == '>' || self.text_parser.next_char() == '/' {
+      if self.text_parser.eof() || self.text_parser.starts_with(">") || self.text_parser.starts_with("/>") {
         break;
       }
+      // Recovery: a '/' that is not part of '/>' is ignored, e.g. '<div / class="a">'
+      if self.text_parser.starts_with("/") {
+        self.text_parser.consume_char();
+        continue;
+      }
+      let start: usize = self.text_parser.position();
       let (name, value): (String, String) = self.parse_attribute()?;
-      attributes.insert(name, value);
+      // Recovery: a repeated attribute is ignored, the first value wins
+      if attributes.contains_key(&name) {
+        let span: dom::Span = self.span_from(start);
+        self.report(
+          dom::DiagnosticKind::DuplicateAttribute,
+          span,
+          format!("Duplicate attribute '{}' ignored", name),
+        );
+      } else {
+        attributes.insert(name, value);
+      }
+    }
+    Ok(attributes)
+  }
+
+  // Add the attributes of a repeated <html>, <head> or <body> start tag to the ones already
+  // seen. The first value of each attribute wins
+  fn merge_attributes(attributes: &mut dom::AttributeMap, new_attributes: dom::AttributeMap) {
+    for (name, value) in new_attributes {
+      if !attributes.contains_key(&name) {
+        attributes.insert(name, value);
+      }
     }
+  }
+
+  // Parse an <html>, <head> or <body> start tag found after its element already exists, and
+  // return its attributes
+  fn parse_misplaced_tag(&mut self, tag_name: &str) -> Result<dom::AttributeMap, String> {
+    let start: usize = self.text_parser.position();
+    let attributes: dom::AttributeMap = self.parse_start_tag()?.1;
+    let span: dom::Span = self.span_from(start);
+    self.report(
+      dom::DiagnosticKind::MisplacedTag,
+      span,
+      format!("Unexpected '<{}>' tag", tag_name),
+    );
     Ok(attributes)
   }
 
-  // Parse a sequence of sibling nodes
+  // Is the current element an HTML integration point, such as <foreignObject> or an
+  // <annotation-xml> whose encoding is HTML?
+  fn in_html_integration_point(&self) -> bool {
+    let Some(current) = self.open_elements.last() else {
+      return false;
+    };
+    foreign_content::is_html_integration_point(current)
+      || self.html_annotation_xml.last() == Some(&(self.open_elements.len() - 1))
+  }
+
+  // Should a start tag be parsed with the rules for SVG and MathML content? The HTML rules apply
+  // in HTML elements, in HTML integration points, in MathML text integration points such as
+  // <mi> (except for <mglyph> and <malignmark>), and to an <svg> in a MathML <annotation-xml>
+  // https://html.spec.whatwg.org/multipage/parsing.html#tree-construction-dispatcher
+  fn in_foreign_content(&self, tag_name: &str) -> bool {
+    let Some(current) = self.open_elements.last() else {
+      return false;
+    };
+    match foreign_content::split_open_element_name(current).0 {
+      dom::Namespace::Html => false,
+      _ if self.in_html_integration_point() => false,
+      _ if foreign_content::is_mathml_text_integration_point(current) => {
+        matches!(tag_name, "mglyph" | "malignmark")
+      }
+      _ => !(current == "math annotation-xml" && tag_name == "svg"),
+    }
+  }
+
+  // Does the end tag close the current element? An end tag closes the open SVG or MathML element
+  // with the same name, ignoring case, up to the nearest HTML element; past it the HTML rules apply
+  // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign (any other end tag)
+  fn end_tag_closes_current_element(&self, tag_name: &str) -> bool {
+    let closes_foreign_element: bool = self
+      .open_elements
+      .iter()
+      .rev()
+      .map(|name: &String| foreign_content::split_open_element_name(name))
+      .take_while(|&(namespace, _): &(dom::Namespace, &str)| namespace != dom::Namespace::Html)
+      .any(|(_, name): (dom::Namespace, &str)| name.eq_ignore_ascii_case(tag_name));
+    closes_foreign_element
+      || tree_construction::end_tag_closes_current_element(&self.open_elements, tag_name)
+  }
+
+  fn report_nesting_too_deep(&mut self) {
+    let span: dom::Span = self.span_from(self.text_parser.position());
+    self.report(
+      dom::DiagnosticKind::NestingTooDeep,
+      span,
+      format!("Elements nested more than {} deep", MAX_NESTING_DEPTH),
+    );
+  }
+
+  fn report_misplaced_doctype(&mut self, doctype: &dom::Node) {
+    self.report(
+      dom::DiagnosticKind::MisplacedDoctype,
+      doctype.span().copied().unwrap_or_default(),
+      "Doctype not at the start of the document, ignored".to_string(),
+    );
+  }
+
+  // Parse a sequence of sibling nodes, up to the end of the current element
+  // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
   fn parse_nodes(&mut self) -> Result<Vec<dom::Node>, String> {
     let mut nodes: Vec<dom::Node> = Vec::new();
     loop {
-      self.text_parser.consume_whitespace();
-      if self.text_parser.eof() || self.text_parser.starts_with("</") {
+      if self.text_parser.eof() {
         break;
       }
-      nodes.push(self.parse_node()?);
+      match self.peek_tag() {
+        Some((true, tag_name)) => {
+          if self.end_tag_closes_current_element(&tag_name) {
+            break;
+          }
+          // Recovery: '</p>' and '</br>' close the open SVG and MathML elements, like the HTML
+          // start tags below
+          if (tag_name == "p" || tag_name == "br") && self.in_foreign_content(&tag_name) {
+            break;
+          }
+          // Recovery: an end tag that closes nothing is ignored, except '</p>' and '</br>' which
+          // browsers turn into an empty paragraph and a line break
+          let start: usize = self.text_parser.position();
+          self.parse_end_tag()?;
+          let span: dom::Span = self.span_from(start);
+          self.report(
+            dom::DiagnosticKind::UnexpectedEndTag,
+            span,
+            format!("End tag '</{}>' doesn't close any open element", tag_name),
+          );
+          if tag_name == "p" || tag_name == "br" {
+            nodes.push(dom::Node::element(tag_name, hashmap![], vec![]).with_span(span));
+          }
+        }
+        Some((false, tag_name)) if self.in_foreign_content(&tag_name) => {
+          // Recovery: an HTML start tag such as <p> closes the open SVG and MathML elements, up
+          // to the nearest element where HTML is allowed, which then parses it
+          if foreign_content::is_breakout_element(&tag_name)
+            || (tag_name == "font" && foreign_content::is_breakout_font(&self.peek_attributes()))
+          {
+            break;
+          }
+          if self.open_elements.len() >= MAX_NESTING_DEPTH {
+            self.report_nesting_too_deep();
+            break;
+          }
+          let namespace: dom::Namespace = self.current_namespace();
+          nodes.push(self.parse_foreign_element(namespace)?);
+        }
+        Some((false, tag_name)) => {
+          let quirks: bool = self.quirks_mode == dom::QuirksMode::Quirks;
+          let closes_current_element: bool = tree_construction::start_tag_closes_current_element(
+            &self.open_elements,
+            &tag_name,
+            quirks,
+          );
+          let current: Option<&String> = self.open_elements.last();
+          let implied_parent: Option<&'static str> = current
+            .and_then(|current: &String| tree_construction::implied_parent(current, &tag_name));
+          match &*tag_name {
+            // Recovery: misplaced <html> and <body> tags only add their attributes, a misplaced
+            // <head> is ignored
+            "html" | "body" | "head" => {
+              let attributes: dom::AttributeMap = self.parse_misplaced_tag(&tag_name)?;
+              match &*tag_name {
+                "html" => HTMLParser::merge_attributes(&mut self.html_attributes, attributes),
+                "body" => HTMLParser::merge_attributes(&mut self.body_attributes, attributes),
+                _ => {}
+              }
+            }
+            _ if closes_current_element => break,
+            // Recovery: past the maximum depth, close the current element so that the new one
+            // becomes its sibling
+            _ if self.open_elements.len() >= MAX_NESTING_DEPTH => {
+              self.report_nesting_too_deep();
+              break;
+            }
+            _ => match implied_parent {
+              Some(parent) => nodes.push(self.parse_implied_element(parent)?),
+              // <svg> and <math> start SVG and MathML content
+              None if tag_name == "svg" => {
+                nodes.push(self.parse_foreign_element(dom::Namespace::Svg)?)
+              }
+              None if tag_name == "math" => {
+                nodes.push(self.parse_foreign_element(dom::Namespace::MathMl)?)
+              }
+              None => nodes.push(self.parse_element()?),
+            },
+          }
+        }
+        None => {
+          let node: dom::Node = self.parse_node()?;
+          // A doctype is only allowed at the start of the document, anywhere else it's ignored
+          if matches!(node.node_type(), dom::NodeType::Doctype(_)) {
+            self.report_misplaced_doctype(&node);
+          } else {
+            nodes.push(node);
+          }
+        }
+      }
     }
     Ok(nodes)
   }
 
+  // Return the span that covers a list of sibling nodes, or None if none of them has a span
+  fn children_span(children: &[dom::Node]) -> Option<dom::Span> {
+    let first: &dom::Span = children.iter().find_map(|child: &dom::Node| child.span())?;
+    let last: &dom::Span = children.iter().rev().find_map(|child: &dom::Node| child.span())?;
+    Some(dom::Span::new(first.start(), last.end(), first.line(), first.column()))
+  }
+
+  // Parse the whole input and return its doctype, if any, and its root element. As in browsers,
+  // the root is always an <html> element with a <head> and a <body>, whether or not the input
+  // contains their tags: head elements (<title>, <meta>, <style>...) go in the head until the
+  // first text or other element, which starts the body
+  // https://html.spec.whatwg.org/multipage/parsing.html#the-before-head-insertion-mode
+  fn parse_top_level_nodes(&mut self) -> Result<(Option<dom::Node>, dom::Node), String> {
+    let mut doctype: Option<dom::Node> = None;
+    // The doctype counts only if nothing but comments precede it; any other doctype is ignored
+    let mut doctype_allowed: bool = true;
+    let mut head_children: Vec<dom::Node> = vec![];
+    let mut body_children: Vec<dom::Node> = vec![];
+    // Which of the <html>, <head> and <body> tags may still appear without being misplaced
+    let mut html_tag_allowed: bool = true;
+    let mut head_tag_allowed: bool = true;
+    let mut in_body: bool = false;
+    // A document without a doctype is in quirks mode
+    self.quirks_mode = quirks::quirks_mode(None, true);
+    self.open_elements = vec!["html".to_string(), "head".to_string()];
+
+    loop {
+      // Whitespace before the body is ignored, whitespace in the body is text
+      if !in_body {
+        self.text_parser.consume_whitespace();
+      }
+      if self.text_parser.eof() {
+        break;
+      }
+      if self.text_parser.starts_with("<!--") {
+        let comment: dom::Node = self.parse_comment()?;
+        if in_body {
+          body_children.push(comment);
+        } else {
+          head_children.push(comment);
+        }
+        continue;
+      }
+      if self.text_parser.starts_with_ignore_case("<!doctype") {
+        let node: dom::Node = self.parse_doctype()?;
+        match (doctype_allowed, node.node_type()) {
+          (true, dom::NodeType::Doctype(data)) => {
+            self.quirks_mode = quirks::quirks_mode(Some(data), self.force_quirks);
+            doctype = Some(node);
+          }
+          _ => self.report_misplaced_doctype(&node),
+        }
+        doctype_allowed = false;
+        continue;
+      }
+      doctype_allowed = false;
+
+      match self.peek_tag() {
+        // The end tags of the root, head and body are optional, and any other end tag at this
+        // level closes nothing
+        Some((true, tag_name)) => {
+          let start: usize = self.text_parser.position();
+          self.parse_end_tag()?;
+          if !matches!(&*tag_name, "html" | "head" | "body") {
+            let span: dom::Span = self.span_from(start);
+            self.report(
+              dom::DiagnosticKind::UnexpectedEndTag,
+              span,
+              format!("End tag '</{}>' doesn't close any open element", tag_name),
+            );
+          }
+        }
+        Some((false, tag_name)) if matches!(&*tag_name, "html" | "head" | "body") => {
+          let allowed: bool = match &*tag_name {
+            "html" => html_tag_allowed,
+            "head" => head_tag_allowed,
+            _ => !in_body,
+          };
+          let attributes: dom::AttributeMap = if allowed {
+            self.parse_start_tag()?.1
+          } else {
+            self.parse_misplaced_tag(&tag_name)?
+          };
+          html_tag_allowed = false;
+          match &*tag_name {
+            "html" => HTMLParser::merge_attributes(&mut self.html_attributes, attributes),
+            // Recovery: a <head> tag inside the body is ignored
+            "head" if !in_body => {
+              head_tag_allowed = false;
+              HTMLParser::merge_attributes(&mut self.head_attributes, attributes)
+            }
+            "head" => {}
+            _ => {
+              HTMLParser::merge_attributes(&mut self.body_attributes, attributes);
+              in_body = true;
+            }
+          }
+        }
+        Some((false, tag_name)) if !in_body && tree_construction::is_head_element(&tag_name) => {
+          html_tag_allowed = false;
+          head_tag_allowed = false;
+          head_children.push(self.parse_element()?);
+        }
+        _ => {
+          in_body = true;
+          self.open_elements = vec!["html".to_string(), "body".to_string()];
+          body_children.append(&mut self.parse_nodes()?);
+          // 'parse_nodes' stops at an end tag that closes the body, e.g. '</body>', which the
+          // loop above consumes
+        }
+      }
+      if in_body {
+        html_tag_allowed = false;
+        head_tag_allowed = false;
+        self.open_elements = vec!["html".to_string(), "body".to_string()];
+      }
+    }
+    self.open_elements.clear();
+
+    // The root covers the whole input, the head and the body just their contents
+    let head_span: Option<dom::Span> = HTMLParser::children_span(&head_children);
+    let body_span: Option<dom::Span> = HTMLParser::children_span(&body_children);
+    let mut head: dom::Node =
+      dom::Node::element("head".to_string(), self.head_attributes.clone(), head_children);
+    if let Some(span) = head_span {
+      head = head.with_span(span);
+    }
+    let mut body: dom::Node =
+      dom::Node::element("body".to_string(), self.body_attributes.clone(), body_children);
+    if let Some(span) = body_span {
+      body = body.with_span(span);
+    }
+    let root: dom::Node =
+      dom::Node::element("html".to_string(), self.html_attributes.clone(), vec![head, body])
+        .with_span(self.span_from(0));
+    Ok((doctype, root))
+  }
+
+  // Parse an HTML document and return its doctype, root element, quirks mode, and the errors
+  // recovered from. The document has no stylesheets yet: see 'DocumentParser' to collect them
+  pub fn parse_document(source: String) -> Result<dom::Document, String> {
+    let mut html_parser: HTMLParser = HTMLParser::new(0, source);
+    let (doctype, root): (Option<dom::Node>, dom::Node) = html_parser.parse_top_level_nodes()?;
+    let mut document: dom::Document =
+      dom::Document::new(doctype, root, vec![], html_parser.quirks_mode);
+    for diagnostic in html_parser.diagnostics {
+      document.add_diagnostic(diagnostic);
+    }
+    Ok(document)
+  }
+
+  // Parse the nodes of a fragment until the end of the input. The context element is not part
+  // of the fragment, so the tags that would close it are handled here instead: an end tag is
+  // ignored, and a start tag is parsed as one more node of the fragment
+  fn parse_fragment_nodes(&mut self) -> Result<Vec<dom::Node>, String> {
+    let mut nodes: Vec<dom::Node> = vec![];
+    loop {
+      nodes.append(&mut self.parse_nodes()?);
+      match self.peek_tag() {
+        Some((true, tag_name)) => {
+          let start: usize = self.text_parser.position();
+          self.parse_end_tag()?;
+          let span: dom::Span = self.span_from(start);
+          self.report(
+            dom::DiagnosticKind::UnexpectedEndTag,
+            span,
+            format!("End tag '</{}>' doesn't close any element of the fragment", tag_name),
+          );
+        }
+        Some((false, _)) => nodes.push(self.parse_element()?),
+        None => break,
+      }
+    }
+    Ok(nodes)
+  }
+
+  // Parse an HTML fragment as the contents of a context element, as when setting the innerHTML
+  // of an element with the tag name 'context_tag', and return its nodes. The context decides
+  // how the fragment is parsed: in a <tr> cells need no <tbody> or row, the contents of a
+  // <textarea> or <script> are text, and the contents of an <svg> or <math> are SVG or MathML
+  // https://html.spec.whatwg.org/multipage/parsing.html#parsing-html-fragments
+  pub fn parse_fragment(source: String, context_tag: &str) -> Result<Vec<dom::Node>, String> {
+    let context_tag: String = context_tag.to_ascii_lowercase();
+    let mut html_parser: HTMLParser = HTMLParser::new(0, source);
+    let escapable: bool = ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&&*context_tag);
+    if escapable || RAW_TEXT_ELEMENTS.contains(&&*context_tag) || context_tag == "plaintext" {
+      let text: String = if escapable {
+        html_parser.consume_decoded_while(|_| true, false)
+      } else {
+        html_parser.text_parser.consume_while(|_| true)
+      };
+      if text.is_empty() {
+        return Ok(vec![]);
+      }
+      return Ok(vec![dom::Node::text(text).with_span(html_parser.span_from(0))]);
+    }
+
+    let namespace: dom::Namespace = match &*context_tag {
+      "svg" => dom::Namespace::Svg,
+      "math" => dom::Namespace::MathMl,
+      _ => dom::Namespace::Html,
+    };
+    html_parser.open_elements = vec![
+      "html".to_string(),
+      foreign_content::open_element_name(namespace, &context_tag),
+    ];
+    html_parser.parse_fragment_nodes()
+  }
+
   // Parse an HTML document and return the root element
   pub fn parse(source: String) -> Result<dom::Node, String> {
-    let mut nodes: Vec<dom::Node> = HTMLParser::new(0, source).parse_nodes()?;
-
-    // If the document contains a root element, just return it. Otherwise, create one
-    Ok(if nodes.len() == 1 {
-      nodes.swap_remove(0)
-    } else {
-      dom::Node::element("html".to_string(), hashmap![], nodes)
-    })
+    Ok(HTMLParser::new(0, source).parse_top_level_nodes()?.1)
   }
 }
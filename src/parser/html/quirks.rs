@@ -0,0 +1,122 @@
+use crate::dom;
+
+// Public identifier prefixes of legacy doctypes that put the document in quirks mode
+// https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode
+const QUIRKS_PUBLIC_ID_PREFIXES: [&str; 55] = [
+  "+//Silmaril//dtd html Pro v0r11 19970101//",
+  "-//AS//DTD HTML 3.0 asWedit + extensions//",
+  "-//AdvaSoft Ltd//DTD HTML 3.0 asWedit + extensions//",
+  "-//IETF//DTD HTML 2.0 Level 1//",
+  "-//IETF//DTD HTML 2.0 Level 2//",
+  "-//IETF//DTD HTML 2.0 Strict Level 1//",
+  "-//IETF//DTD HTML 2.0 Strict Level 2//",
+  "-//IETF//DTD HTML 2.0 Strict//",
+  "-//IETF//DTD HTML 2.0//",
+  "-//IETF//DTD HTML 2.1E//",
+  "-//IETF//DTD HTML 3.0//",
+  "-//IETF//DTD HTML 3.2 Final//",
+  "-//IETF//DTD HTML 3.2//",
+  "-//IETF//DTD HTML 3//",
+  "-//IETF//DTD HTML Level 0//",
+  "-//IETF//DTD HTML Level 1//",
+  "-//IETF//DTD HTML Level 2//",
+  "-//IETF//DTD HTML Level 3//",
+  "-//IETF//DTD HTML Strict Level 0//",
+  "-//IETF//DTD HTML Strict Level 1//",
+  "-//IETF//DTD HTML Strict Level 2//",
+  "-//IETF//DTD HTML Strict Level 3//",
+  "-//IETF//DTD HTML Strict//",
+  "-//IETF//DTD HTML//",
+  "-//Metrius//DTD Metrius Presentational//",
+  "-//Microsoft//DTD Internet Explorer 2.0 HTML Strict//",
+  "-//Microsoft//DTD Internet Explorer 2.0 HTML//",
+  "-//Microsoft//DTD Internet Explorer 2.0 Tables//",
+  "-//Microsoft//DTD Internet Explorer 3.0 HTML Strict//",
+  "-//Microsoft//DTD Internet Explorer 3.0 HTML//",
+  "-//Microsoft//DTD Internet Explorer 3.0 Tables//",
+  "-//Netscape Comm. Corp.//DTD HTML//",
+  "-//Netscape Comm. Corp.//DTD Strict HTML//",
+  "-//O'Reilly and Associates//DTD HTML 2.0//",
+  "-//O'Reilly and Associates//DTD HTML Extended 1.0//",
+  "-//O'Reilly and Associates//DTD HTML Extended Relaxed 1.0//",
+  "-//SQ//DTD HTML 2.0 HoTMetaL + extensions//",
+  "-//SoftQuad Software//DTD HoTMetaL PRO 6.0::19990601::extensions to HTML 4.0//",
+  "-//SoftQuad//DTD HoTMetaL PRO 4.0::19970916::extensions to HTML 4.0//",
+  "-//Spyglass//DTD HTML 2.0 Extended//",
+  "-//Sun Microsystems Corp.//DTD HotJava HTML//",
+  "-//Sun Microsystems Corp.//DTD HotJava Strict HTML//",
+  "-//W3C//DTD HTML 3 1995-03-24//",
+  "-//W3C//DTD HTML 3.2 Draft//",
+  "-//W3C//DTD HTML 3.2 Final//",
+  "-//W3C//DTD HTML 3.2//",
+  "-//W3C//DTD HTML 3.2S Draft//",
+  "-//W3C//DTD HTML 4.0 Frameset//",
+  "-//W3C//DTD HTML 4.0 Transitional//",
+  "-//W3C//DTD HTML Experimental 19960712//",
+  "-//W3C//DTD HTML Experimental 970421//",
+  "-//W3C//DTD W3 HTML//",
+  "-//W3O//DTD W3 HTML 3.0//",
+  "-//WebTechs//DTD Mozilla HTML 2.0//",
+  "-//WebTechs//DTD Mozilla HTML//",
+];
+
+// Public identifiers that put the document in quirks mode when they match exactly
+const QUIRKS_PUBLIC_IDS: [&str; 3] = [
+  "-//W3O//DTD W3 HTML Strict 3.0//EN//",
+  "-/W3C/DTD HTML 4.0 Transitional/EN",
+  "HTML",
+];
+
+const QUIRKS_SYSTEM_ID: &str = "http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd";
+
+// HTML 4.01 doctypes: quirks mode without a system identifier, limited-quirks mode with one
+const HTML_401_PUBLIC_ID_PREFIXES: [&str; 2] = [
+  "-//W3C//DTD HTML 4.01 Frameset//",
+  "-//W3C//DTD HTML 4.01 Transitional//",
+];
+
+const LIMITED_QUIRKS_PUBLIC_ID_PREFIXES: [&str; 2] = [
+  "-//W3C//DTD XHTML 1.0 Frameset//",
+  "-//W3C//DTD XHTML 1.0 Transitional//",
+];
+
+// Case-insensitive (ASCII) prefix test
+fn starts_with_ignore_case(value: &str, prefix: &str) -> bool {
+  value
+    .get(..prefix.len())
+    .is_some_and(|start: &str| start.eq_ignore_ascii_case(prefix))
+}
+
+// Decide the document mode from the doctype at the start of the document, if any. A document
+// without a doctype, or whose doctype was malformed ('force_quirks'), is rendered in quirks mode
+pub(super) fn quirks_mode(doctype: Option<&dom::DoctypeData>, force_quirks: bool) -> dom::QuirksMode {
+  let doctype: &dom::DoctypeData = match doctype {
+    Some(doctype) if !force_quirks => doctype,
+    _ => return dom::QuirksMode::Quirks,
+  };
+  let public_id: &str = doctype.public_id().unwrap_or_default();
+  let has_prefix = |prefixes: &[&str]| -> bool {
+    prefixes
+      .iter()
+      .any(|prefix: &&str| starts_with_ignore_case(public_id, prefix))
+  };
+
+  if doctype.name() != "html"
+    || QUIRKS_PUBLIC_IDS
+      .iter()
+      .any(|id: &&str| public_id.eq_ignore_ascii_case(id))
+    || doctype
+      .system_id()
+      .is_some_and(|system_id: &str| system_id.eq_ignore_ascii_case(QUIRKS_SYSTEM_ID))
+    || has_prefix(&QUIRKS_PUBLIC_ID_PREFIXES)
+    || (doctype.system_id().is_none() && has_prefix(&HTML_401_PUBLIC_ID_PREFIXES))
+  {
+    dom::QuirksMode::Quirks
+  } else if has_prefix(&LIMITED_QUIRKS_PUBLIC_ID_PREFIXES)
+    || (doctype.system_id().is_some() && has_prefix(&HTML_401_PUBLIC_ID_PREFIXES))
+  {
+    dom::QuirksMode::LimitedQuirks
+  } else {
+    dom::QuirksMode::NoQuirks
+  }
+}
@@ -30,6 +30,49 @@ fn test_parse_text() {
   assert_eq!(html_parser.parse_text(), node);
 }
 
+// Test the method parse_character_reference of the HTMLParser struct implementation
+#[test]
+fn test_parse_character_reference() {
+  let mut html_parser: HTMLParser = HTMLParser::new(0, "&amp;".to_string());
+
+  // Assert that the parse_character_reference method correctly decodes the named reference "&amp;"
+  assert_eq!(html_parser.parse_character_reference(false), "&");
+
+  html_parser = HTMLParser::new(0, "&notin; &noti".to_string());
+  // Assert that the parse_character_reference method matches the longest name in the table
+  assert_eq!(html_parser.parse_character_reference(false), "\u{2209}");
+  html_parser.text_parser.consume_char();
+  // Assert that the legacy reference "&not" is decoded without its ';', leaving "i" as text
+  assert_eq!(html_parser.parse_character_reference(false), "\u{AC}");
+  assert_eq!(html_parser.text_parser.next_char(), Some('i'));
+
+  html_parser = HTMLParser::new(0, "&#169;&#x1F600;&#X41".to_string());
+  // Assert that the parse_character_reference method correctly decodes decimal and hex references
+  assert_eq!(html_parser.parse_character_reference(false), "\u{A9}");
+  assert_eq!(html_parser.parse_character_reference(false), "\u{1F600}");
+  assert_eq!(html_parser.parse_character_reference(false), "A");
+
+  html_parser = HTMLParser::new(0, "&#0;&#x80;&#xD800;&#x110000;".to_string());
+  // Assert that invalid code points are replaced and C1 controls are remapped to Windows-1252
+  assert_eq!(html_parser.parse_character_reference(false), "\u{FFFD}");
+  assert_eq!(html_parser.parse_character_reference(false), "\u{20AC}");
+  assert_eq!(html_parser.parse_character_reference(false), "\u{FFFD}");
+  assert_eq!(html_parser.parse_character_reference(false), "\u{FFFD}");
+
+  html_parser = HTMLParser::new(0, "&unknown; &#;".to_string());
+  // Assert that unknown names and references without digits are kept as text
+  assert_eq!(html_parser.parse_character_reference(false), "&");
+  assert_eq!(html_parser.text_parser.consume_while(|c: char| c != '&'), "unknown; ");
+  assert_eq!(html_parser.parse_character_reference(false), "&#");
+
+  html_parser = HTMLParser::new(0, "&copy=2".to_string());
+  // Assert that a legacy reference followed by '=' is not decoded inside an attribute value
+  assert_eq!(html_parser.parse_character_reference(true), "&");
+  html_parser = HTMLParser::new(0, "&copy=2".to_string());
+  // Assert that the same reference is decoded inside text
+  assert_eq!(html_parser.parse_character_reference(false), "\u{A9}");
+}
+
 // Test the method parse_element of the HTMLParser struct implementation
 #[test]
 fn test_parse_element() {
@@ -63,6 +106,91 @@ fn test_parse_element() {
   assert_eq!(html_parser.parse_element().unwrap(), node_3);
 }
 
+// Test the method parse_raw_text of the HTMLParser struct implementation
+#[test]
+fn test_parse_raw_text() {
+  let mut html_parser: HTMLParser =
+    HTMLParser::new(8, "<script>if (a < b && c) { x = '</p>'; }</SCRIPT >".to_string());
+
+  // Assert that the parse_raw_text method consumes markup verbatim up to the matching end tag
+  assert_eq!(
+    html_parser.parse_raw_text("script", false),
+    "if (a < b && c) { x = '</p>'; }"
+  );
+  assert!(html_parser.text_parser.starts_with("</SCRIPT"));
+
+  html_parser = HTMLParser::new(7, "<style>p:after { content: '</styles>' }".to_string());
+  // Assert that a longer tag name is not mistaken for the end tag, and EOF ends the raw text
+  assert_eq!(
+    html_parser.parse_raw_text("style", false),
+    "p:after { content: '</styles>' }"
+  );
+  assert!(html_parser.text_parser.eof());
+
+  html_parser = HTMLParser::new(10, "<textarea><b>Fish &amp; Chips</b></textarea>".to_string());
+  // Assert that character references are decoded in escapable raw text
+  assert_eq!(
+    html_parser.parse_raw_text("textarea", true),
+    "<b>Fish & Chips</b>"
+  );
+}
+
+// Test raw text and escapable raw text elements parsed by parse_element
+#[test]
+fn test_parse_raw_text_elements() {
+  let mut html_parser: HTMLParser = HTMLParser::new(0, "<style>a > b {}</style>".to_string());
+  let children: Vec<dom::Node> = vec![dom::Node::text("a > b {}".to_string())];
+  let expected: dom::Node = dom::Node::element("style".to_string(), hashmap![], children);
+
+  // Assert that the contents of a style element are stored as a single text child
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+
+  html_parser = HTMLParser::new(0, "<title>Q&amp;A <i>page</i></title>".to_string());
+  let children: Vec<dom::Node> = vec![dom::Node::text("Q&A <i>page</i>".to_string())];
+  let expected: dom::Node = dom::Node::element("title".to_string(), hashmap![], children);
+
+  // Assert that the contents of a title element are decoded but not parsed as markup
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+
+  html_parser = HTMLParser::new(0, "<script></script>".to_string());
+  let expected: dom::Node = dom::Node::element("script".to_string(), hashmap![], vec![]);
+
+  // Assert that an empty raw text element has no children
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+}
+
+// Test the whitespace of text nodes parsed by parse_element
+#[test]
+fn test_parse_whitespace() {
+  let mut html_parser: HTMLParser =
+    HTMLParser::new(0, "<p>\n  <b>a</b> <i>b</i>\n</p>".to_string());
+  let children: Vec<dom::Node> = vec![
+    dom::Node::text("\n  ".to_string()),
+    dom::Node::element("b".to_string(), hashmap![], vec![dom::Node::text("a".to_string())]),
+    dom::Node::text(" ".to_string()),
+    dom::Node::element("i".to_string(), hashmap![], vec![dom::Node::text("b".to_string())]),
+    dom::Node::text("\n".to_string()),
+  ];
+  let expected: dom::Node = dom::Node::element("p".to_string(), hashmap![], children);
+
+  // Assert that whitespace-only text is kept, for the style stage to collapse
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+
+  html_parser = HTMLParser::new(0, "<pre>\n\n  code</pre>".to_string());
+  let children: Vec<dom::Node> = vec![dom::Node::text("\n  code".to_string())];
+  let expected: dom::Node = dom::Node::element("pre".to_string(), hashmap![], children);
+
+  // Assert that only the first newline after the start tag of a pre element is skipped
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+
+  html_parser = HTMLParser::new(0, "<textarea>\nab</textarea>".to_string());
+  let children: Vec<dom::Node> = vec![dom::Node::text("ab".to_string())];
+  let expected: dom::Node = dom::Node::element("textarea".to_string(), hashmap![], children);
+
+  // Assert that the rule also applies to textarea elements
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+}
+
 // Test the method parse_comment of the HTMLParser struct implementation
 #[test]
 fn test_parse_comment() {
@@ -73,6 +201,54 @@ fn test_parse_comment() {
   assert_eq!(html_parser.parse_comment().unwrap(), node);
 }
 
+// Test the method parse_doctype of the HTMLParser struct implementation
+#[test]
+fn test_parse_doctype() {
+  let mut html_parser: HTMLParser = HTMLParser::new(0, "<!DOCTYPE HTML>".to_string());
+
+  // Assert that the parse_doctype method correctly parses and lowercases the name "html"
+  assert_eq!(
+    html_parser.parse_doctype().unwrap(),
+    dom::Node::doctype("html".to_string(), None, None)
+  );
+  assert!(!html_parser.force_quirks);
+
+  html_parser = HTMLParser::new(
+    0,
+    "<!doctype html public \"-//W3C//DTD HTML 4.01//EN\"\n  'http://www.w3.org/TR/html4/strict.dtd'>"
+      .to_string(),
+  );
+  // Assert that the parse_doctype method correctly parses the public and system identifiers
+  assert_eq!(
+    html_parser.parse_doctype().unwrap(),
+    dom::Node::doctype(
+      "html".to_string(),
+      Some("-//W3C//DTD HTML 4.01//EN".to_string()),
+      Some("http://www.w3.org/TR/html4/strict.dtd".to_string())
+    )
+  );
+
+  html_parser = HTMLParser::new(0, "<!DOCTYPE html SYSTEM>".to_string());
+  // Assert that a keyword without its identifier sets the force-quirks flag
+  assert_eq!(
+    html_parser.parse_doctype().unwrap(),
+    dom::Node::doctype("html".to_string(), None, None)
+  );
+  assert!(html_parser.force_quirks);
+}
+
+// Test the method parse_bogus_comment of the HTMLParser struct implementation
+#[test]
+fn test_parse_bogus_comment() {
+  let mut html_parser: HTMLParser = HTMLParser::new(0, "<!ELEMENT br EMPTY><p></p>".to_string());
+
+  // Assert that markup declarations other than comments and doctypes are kept as comments
+  assert_eq!(
+    html_parser.parse_node().unwrap(),
+    dom::Node::comment("ELEMENT br EMPTY".to_string())
+  );
+}
+
 // Test the method parse_node of the HTMLParser struct implementation
 #[test]
 fn test_parse_node() {
@@ -120,6 +296,31 @@ fn test_parse_attribute_value() {
   );
 }
 
+// Test character references inside text nodes and attribute values
+#[test]
+fn test_parse_character_references_in_content() {
+  let mut html_parser: HTMLParser =
+    HTMLParser::new(0, "Fish &amp; Chips&nbsp;&lt;3 &copy 2024</p>".to_string());
+  let node: dom::Node = dom::Node::text("Fish & Chips\u{A0}<3 \u{A9} 2024".to_string());
+
+  // Assert that the parse_text method decodes named references, with and without ';'
+  assert_eq!(html_parser.parse_text(), node);
+
+  html_parser = HTMLParser::new(0, "'?a=1&amp;b=2&copy=3&quot;'".to_string());
+  // Assert that the parse_attribute_value method decodes references in a quoted value
+  assert_eq!(
+    html_parser.parse_attribute_value().unwrap(),
+    "?a=1&b=2&copy=3\"".to_string()
+  );
+
+  html_parser = HTMLParser::new(0, "&lt;unquoted&#x3E;>".to_string());
+  // Assert that the parse_attribute_value method decodes references in an unquoted value
+  assert_eq!(
+    html_parser.parse_attribute_value().unwrap(),
+    "<unquoted>".to_string()
+  );
+}
+
 // Test the method parse_attributes of the HTMLParser struct implementation
 #[test]
 fn test_parse_attributes() {
@@ -189,6 +390,30 @@ fn test_recovery_unquoted_attribute() {
   assert_eq!(html_parser.parse_element().unwrap(), expected);
 }
 
+// Test recovery: repeated attribute
+#[test]
+fn test_recovery_duplicate_attribute() {
+  let mut html_parser: HTMLParser =
+    HTMLParser::new(0, "<div ID=a Class=x id=b title=t>text</div>".to_string());
+  let element: dom::Node = html_parser.parse_element().unwrap();
+  let dom::NodeType::Element(element) = element.node_type() else {
+    panic!("Expected an element");
+  };
+  let attributes: Vec<(&String, &String)> = element.attributes().iter().collect();
+
+  // Assert that attribute names are lowercased, that the first value of a repeated attribute
+  // wins, and that attributes keep their source order
+  assert_eq!(
+    attributes,
+    vec![
+      (&"id".to_string(), &"a".to_string()),
+      (&"class".to_string(), &"x".to_string()),
+      (&"title".to_string(), &"t".to_string()),
+    ]
+  );
+  assert_eq!(element.to_string(), "<div id='a' class='x' title='t'>");
+}
+
 // Test recovery: element not closed before EOF
 #[test]
 fn test_recovery_unclosed_element() {
@@ -205,14 +430,146 @@ fn test_recovery_unclosed_element() {
 #[test]
 fn test_recovery_mismatched_closing_tag() {
   let mut html_parser: HTMLParser =
-    HTMLParser::new(0, "<div>text</p>".to_string());
+    HTMLParser::new(0, "<div>text</span></div>".to_string());
   let children: Vec<dom::Node> = vec![dom::Node::text("text".to_string())];
   let expected: dom::Node = dom::Node::element("div".to_string(), hashmap![], children);
 
-  // Assert that a mismatched closing tag is skipped and the element is closed anyway
+  // Assert that a closing tag that closes no open element is ignored
+  assert_eq!(html_parser.parse_element().unwrap(), expected);
+
+  html_parser = HTMLParser::new(0, "<div>text</p></div>".to_string());
+  let children: Vec<dom::Node> = vec![
+    dom::Node::text("text".to_string()),
+    dom::Node::element("p".to_string(), hashmap![], vec![]),
+  ];
+  let expected: dom::Node = dom::Node::element("div".to_string(), hashmap![], children);
+
+  // Assert that a stray '</p>' becomes an empty paragraph, as in browsers
   assert_eq!(html_parser.parse_element().unwrap(), expected);
 }
 
+// Test recovery: end tags implied by the start tag of a sibling or by the end of the parent
+#[test]
+fn test_recovery_implied_end_tags() {
+  let element = |tag_name: &str, children: Vec<dom::Node>| -> dom::Node {
+    dom::Node::element(tag_name.to_string(), hashmap![], children)
+  };
+  let text = |text: &str| -> dom::Node { dom::Node::text(text.to_string()) };
+  let parse_body = |source: &str| -> Vec<dom::Node> {
+    HTMLParser::parse(source.to_string()).unwrap().children()[1].children().clone()
+  };
+
+  // Assert that a paragraph is closed by the next paragraph and by a block-level start tag
+  assert_eq!(
+    parse_body("<p>one<p>two<div>three</div>"),
+    vec![
+      element("p", vec![text("one")]),
+      element("p", vec![text("two")]),
+      element("div", vec![text("three")])
+    ]
+  );
+  // Assert that list items are closed by the next item and by the end of the list, but not by
+  // the items of a nested list
+  assert_eq!(
+    parse_body("<ul><li>a<li>b<ul><li>c</ul></ul>"),
+    vec![element(
+      "ul",
+      vec![
+        element("li", vec![text("a")]),
+        element("li", vec![text("b"), element("ul", vec![element("li", vec![text("c")])])])
+      ]
+    )]
+  );
+  // Assert that definition terms and descriptions close each other
+  assert_eq!(
+    parse_body("<dl><dt>term<dd>one<dd>two</dl>"),
+    vec![element(
+      "dl",
+      vec![
+        element("dt", vec![text("term")]),
+        element("dd", vec![text("one")]),
+        element("dd", vec![text("two")])
+      ]
+    )]
+  );
+  // Assert that options are closed by the next option
+  assert_eq!(
+    parse_body("<select><option>a<option>b</select>"),
+    vec![element(
+      "select",
+      vec![element("option", vec![text("a")]), element("option", vec![text("b")])]
+    )]
+  );
+  // Assert that a heading closes an open heading, and that any heading end tag closes it
+  assert_eq!(
+    parse_body("<h1>one<h2>two</h1><p>three"),
+    vec![
+      element("h1", vec![text("one")]),
+      element("h2", vec![text("two")]),
+      element("p", vec![text("three")])
+    ]
+  );
+  // Assert that an end tag closes the open elements nested in its element
+  assert_eq!(
+    parse_body("<div><p><b>bold</div>after"),
+    vec![
+      element("div", vec![element("p", vec![element("b", vec![text("bold")])])]),
+      text("after")
+    ]
+  );
+}
+
+// Test recovery: table cells and rows without end tags, and the row group and row they imply
+#[test]
+fn test_recovery_implied_table_elements() {
+  let element = |tag_name: &str, children: Vec<dom::Node>| -> dom::Node {
+    dom::Node::element(tag_name.to_string(), hashmap![], children)
+  };
+  let text = |text: &str| -> dom::Node { dom::Node::text(text.to_string()) };
+  let body: dom::Node =
+    HTMLParser::parse("<table><tr><td>a<td>b<tr><th>c</table>".to_string()).unwrap().children()[1].clone();
+
+  // Assert that cells and rows are closed implicitly and that the rows get an implied <tbody>
+  assert_eq!(
+    body.children(),
+    &vec![element(
+      "table",
+      vec![element(
+        "tbody",
+        vec![
+          element("tr", vec![element("td", vec![text("a")]), element("td", vec![text("b")])]),
+          element("tr", vec![element("th", vec![text("c")])])
+        ]
+      )]
+    )]
+  );
+
+  let body: dom::Node =
+    HTMLParser::parse("<table><thead><td>x</td></thead></table>".to_string()).unwrap().children()[1].clone();
+  // Assert that a cell directly inside a row group gets an implied <tr>
+  assert_eq!(
+    body.children(),
+    &vec![element(
+      "table",
+      vec![element("thead", vec![element("tr", vec![element("td", vec![text("x")])])])]
+    )]
+  );
+
+  let quirks_body: dom::Node =
+    HTMLParser::parse_document("<p>text<table></table>".to_string()).unwrap().root().children()[1].clone();
+  let no_quirks_body: dom::Node =
+    HTMLParser::parse_document("<!DOCTYPE html><p>text<table></table>".to_string()).unwrap().root().children()[1].clone();
+  // Assert that a table closes an open paragraph, except in quirks mode
+  assert_eq!(
+    quirks_body.children(),
+    &vec![element("p", vec![text("text"), element("table", vec![])])]
+  );
+  assert_eq!(
+    no_quirks_body.children(),
+    &vec![element("p", vec![text("text")]), element("table", vec![])]
+  );
+}
+
 // Test the function parse of the HTMLParser struct implementation
 #[test]
 fn test_parse() {
@@ -234,20 +591,408 @@ fn test_parse() {
     hashmap![String::from("class") => String::from("container-2")];
   let children_3: Vec<dom::Node> = vec![node_4];
   let node_3: dom::Node = dom::Node::element(tag_name_3, attributes_3, children_3);
-  // Node 1: <html>
-  let tag_name_1: String = String::from("html");
-  let attributes_1: dom::AttributeMap = hashmap![];
-  let children_1: Vec<dom::Node> = vec![node_2, node_3.clone()];
-  let node_1: dom::Node = dom::Node::element(tag_name_1, attributes_1, children_1);
+  // Node 1: <html>, with an empty <head> and a <body>
+  let head: dom::Node = dom::Node::element("head".to_string(), hashmap![], vec![]);
+  let body: dom::Node = dom::Node::element("body".to_string(), hashmap![], vec![node_2, node_3]);
+  let node_1: dom::Node =
+    dom::Node::element("html".to_string(), hashmap![], vec![head.clone(), body.clone()]);
 
-  // Assert that the parse function correctly parses the nodes without a root element and then add the "html" tag as root element, returning it
+  // Assert that the parse function inserts the "html", "head" and "body" elements that the source leaves out
   assert_eq!(HTMLParser::parse("<div class='container-1'></div><div class='container-2'><p class='paragraph'>Hello World!</p></div>".to_string()).unwrap(), node_1);
-  // Assert that the parse function correctly parses the nodes with a root element and then returning it
+  // Assert that the parse function uses the "html", "head" and "body" tags of the source, and
+  // that head elements go into the head
+  let title: dom::Node = dom::Node::element(
+    "title".to_string(),
+    hashmap![],
+    vec![dom::Node::text("Title".to_string())],
+  );
+  let head: dom::Node = dom::Node::element("head".to_string(), hashmap![], vec![title]);
+  let html_attributes: dom::AttributeMap = hashmap![String::from("lang") => String::from("en")];
+  let node_1: dom::Node =
+    dom::Node::element("html".to_string(), html_attributes, vec![head, body]);
   assert_eq!(
     HTMLParser::parse(
-      "<div class='container-2'><p class='paragraph'>Hello World!</p></div>".to_string()
+      "<html lang='en'><head><title>Title</title></head>\
+      <body><div class='container-1'></div><div class='container-2'><p class='paragraph'>Hello World!</p></div></body></html>"
+        .to_string()
     )
     .unwrap(),
-    node_3
+    node_1
+  );
+}
+
+// Test SVG and MathML elements parsed by the function parse
+#[test]
+fn test_parse_foreign_content() {
+  let body_children = |source: &str| -> Vec<dom::Node> {
+    let root: dom::Node = HTMLParser::parse(source.to_string()).unwrap();
+    root.children()[1].children().clone()
+  };
+  let svg = |tag_name: &str, attributes: dom::AttributeMap, children: Vec<dom::Node>| {
+    dom::Node::element_in_namespace(dom::Namespace::Svg, tag_name.to_string(), attributes, children)
+  };
+
+  // Assert that SVG elements are in the SVG namespace, with the case of their names restored,
+  // and that self-closing tags have no contents
+  let gradient: dom::Node = svg(
+    "linearGradient",
+    hashmap!["gradientUnits".to_string() => "userSpaceOnUse".to_string()],
+    vec![],
+  );
+  let rect: dom::Node = svg("rect", hashmap!["width".to_string() => "1".to_string()], vec![]);
+  let root: dom::Node = svg(
+    "svg",
+    hashmap!["viewBox".to_string() => "0 0 1 1".to_string()],
+    vec![svg("defs", hashmap![], vec![gradient]), rect],
+  );
+  assert_eq!(
+    body_children(
+      "<SVG VIEWBOX='0 0 1 1'><defs><lineargradient gradientunits='userSpaceOnUse'/></defs>\
+      <rect width=1 /></svg>"
+    ),
+    vec![root]
+  );
+  // Assert that a MathML element keeps its namespace, and that its text integration points
+  // contain HTML elements
+  let mi: dom::Node = dom::Node::element_in_namespace(
+    dom::Namespace::MathMl,
+    "mi".to_string(),
+    hashmap![],
+    vec![dom::Node::element(
+      "b".to_string(),
+      hashmap![],
+      vec![dom::Node::text("x".to_string())],
+    )],
+  );
+  let math: dom::Node = dom::Node::element_in_namespace(
+    dom::Namespace::MathMl,
+    "math".to_string(),
+    hashmap!["definitionURL".to_string() => "u".to_string()],
+    vec![mi],
+  );
+  assert_eq!(body_children("<math definitionurl=u><mi><b>x</b></mi></math>"), vec![math]);
+  // Assert that an <annotation-xml> contains HTML elements only if its encoding is HTML
+  for encoding in ["text/html", "Application/XHTML+XML"] {
+    let children: Vec<dom::Node> = body_children(&format!(
+      "<math><annotation-xml encoding='{}'><p>x</p></annotation-xml></math>",
+      encoding
+    ));
+    let annotation: &dom::Node = &children[0].children()[0];
+    let dom::NodeType::Element(paragraph) = annotation.children()[0].node_type() else {
+      panic!("Expected an element");
+    };
+    assert_eq!(children.len(), 1);
+    assert_eq!(
+      (paragraph.namespace(), paragraph.tag_name()),
+      (dom::Namespace::Html, "p")
+    );
+  }
+  let children: Vec<dom::Node> = body_children(
+    "<math><annotation-xml encoding='image/svg+xml'><p>x</p></annotation-xml></math>",
+  );
+  assert_eq!(children.len(), 2);
+  assert!(children[0].children()[0].children().is_empty());
+  // Assert that <foreignObject> contains HTML, and that an SVG <style> is not raw text
+  let children: Vec<dom::Node> =
+    body_children("<svg><foreignObject><p>a<p>b</p></foreignObject><style>a&amp;b</style></svg>");
+  let foreign_object: &dom::Node = &children[0].children()[0];
+  let dom::NodeType::Element(paragraph) = foreign_object.children()[1].node_type() else {
+    panic!("Expected an element");
+  };
+  assert_eq!(
+    (paragraph.namespace(), paragraph.tag_name()),
+    (dom::Namespace::Html, "p")
+  );
+  assert_eq!(foreign_object.children().len(), 2);
+  assert_eq!(children[0].children()[1].text_content(), "a&b");
+  // Assert that CDATA sections are text in SVG content, and bogus comments in HTML content
+  let children: Vec<dom::Node> = body_children("<svg>a<![CDATA[<b>&amp;]]>c</svg><![CDATA[x]]>");
+  assert_eq!(children[0].children(), &vec![dom::Node::text("a<b>&amp;c".to_string())]);
+  assert_eq!(children[1], dom::Node::comment("[CDATA[x]]".to_string()));
+  // Assert that an HTML start tag closes the open SVG elements, but an unknown one doesn't
+  let children: Vec<dom::Node> = body_children("<div><svg><g><p>a</p><font size=1>b");
+  let svg_element: &dom::Node = &children[0].children()[0];
+  assert_eq!(children[0].children().len(), 3);
+  assert_eq!(svg_element, &svg("svg", hashmap![], vec![svg("g", hashmap![], vec![])]));
+  let children: Vec<dom::Node> = body_children("<svg><font>a</font><P>b</svg>");
+  let font: dom::Node = svg("font", hashmap![], vec![dom::Node::text("a".to_string())]);
+  assert_eq!(children[0].children()[0], font);
+  assert_eq!(children.len(), 2);
+  // Assert that end tags close SVG elements ignoring case, and the HTML elements around them
+  let children: Vec<dom::Node> = body_children("<div><svg><clipPath><g></CLIPPATH>a</div>b");
+  assert_eq!(children[0].children()[0].children()[0].children().len(), 1);
+  assert_eq!(children[0].children()[0].children()[1], dom::Node::text("a".to_string()));
+  assert_eq!(children[1], dom::Node::text("b".to_string()));
+}
+
+// Test the function parse_fragment of the HTMLParser struct implementation
+#[test]
+fn test_parse_fragment() {
+  let fragment = |source: &str, context_tag: &str| -> Vec<dom::Node> {
+    HTMLParser::parse_fragment(source.to_string(), context_tag).unwrap()
+  };
+  let element = |tag_name: &str, children: Vec<dom::Node>| -> dom::Node {
+    dom::Node::element(tag_name.to_string(), hashmap![], children)
+  };
+  let text = |data: &str| -> dom::Node { dom::Node::text(data.to_string()) };
+
+  // Assert that the nodes of a fragment are returned without a wrapper document
+  assert_eq!(
+    fragment("<p>a<p>b", "div"),
+    vec![element("p", vec![text("a")]), element("p", vec![text("b")])]
+  );
+  assert_eq!(fragment("", "div"), vec![]);
+  // Assert that cells in a row context need no row group or row, but rows in a table do
+  assert_eq!(
+    fragment("<td>1<td>2", "TR"),
+    vec![element("td", vec![text("1")]), element("td", vec![text("2")])]
+  );
+  assert_eq!(
+    fragment("<tr><td>1", "table"),
+    vec![element("tbody", vec![element("tr", vec![element("td", vec![text("1")])])])]
+  );
+  // Assert that tags which would close the context element don't end the fragment
+  assert_eq!(
+    fragment("<li>a<li>b</li>c</li>", "li"),
+    vec![element("li", vec![text("a")]), element("li", vec![text("b")]), text("c")]
+  );
+  // Assert that the contents of raw text contexts are text
+  assert_eq!(fragment("<b>&lt;</b>", "textarea"), vec![text("<b><</b>")]);
+  assert_eq!(fragment("if (a < b) {}", "script"), vec![text("if (a < b) {}")]);
+  assert_eq!(fragment("", "style"), vec![]);
+  // Assert that the contents of an <svg> context are SVG elements
+  let nodes: Vec<dom::Node> = fragment("<circle r=1 /><p>a", "svg");
+  let circle: dom::Node = dom::Node::element_in_namespace(
+    dom::Namespace::Svg,
+    "circle".to_string(),
+    hashmap!["r".to_string() => "1".to_string()],
+    vec![],
+  );
+  assert_eq!(nodes, vec![circle, element("p", vec![text("a")])]);
+}
+
+// Test recovery: misplaced <html>, <head> and <body> tags
+#[test]
+fn test_recovery_misplaced_document_tags() {
+  let root: dom::Node = HTMLParser::parse(
+    "<title>A</title><p>text<html lang='en' id='x'><head><body class='b'><html id='y'>".to_string(),
+  )
+  .unwrap();
+  let html_attributes: dom::AttributeMap =
+    hashmap![String::from("lang") => String::from("en"), String::from("id") => String::from("x")];
+  let body_attributes: dom::AttributeMap = hashmap![String::from("class") => String::from("b")];
+  let title: dom::Node = dom::Node::element(
+    "title".to_string(),
+    hashmap![],
+    vec![dom::Node::text("A".to_string())],
+  );
+  let paragraph: dom::Node = dom::Node::element(
+    "p".to_string(),
+    hashmap![],
+    vec![dom::Node::text("text".to_string())],
+  );
+
+  // Assert that misplaced tags only add their attributes, the first value of each one winning
+  assert_eq!(
+    root,
+    dom::Node::element(
+      "html".to_string(),
+      html_attributes,
+      vec![
+        dom::Node::element("head".to_string(), hashmap![], vec![title]),
+        dom::Node::element("body".to_string(), body_attributes, vec![paragraph])
+      ]
+    )
+  );
+}
+
+// Test the function parse_document of the HTMLParser struct implementation
+#[test]
+fn test_parse_document() {
+  let document: dom::Document =
+    HTMLParser::parse_document("<!-- intro --><!DOCTYPE html><html><p>Hello</p></html>".to_string())
+      .unwrap();
+  let paragraph: dom::Node = dom::Node::element(
+    "p".to_string(),
+    hashmap![],
+    vec![dom::Node::text("Hello".to_string())],
+  );
+
+  // Assert that a doctype preceded only by comments is taken out of the tree
+  assert_eq!(
+    document.doctype(),
+    Some(&dom::Node::doctype("html".to_string(), None, None))
+  );
+  let root: dom::Node = dom::Node::element(
+    "html".to_string(),
+    hashmap![],
+    vec![
+      dom::Node::element(
+        "head".to_string(),
+        hashmap![],
+        vec![dom::Node::comment(" intro ".to_string())],
+      ),
+      dom::Node::element("body".to_string(), hashmap![], vec![paragraph.clone()]),
+    ],
+  );
+  assert_eq!(*document.root(), root);
+  assert_eq!(document.quirks_mode(), dom::QuirksMode::NoQuirks);
+
+  let document: dom::Document =
+    HTMLParser::parse_document("<p>Hello</p><!DOCTYPE html>".to_string()).unwrap();
+  // Assert that a document without a leading doctype is in quirks mode, and a misplaced doctype
+  // is dropped
+  assert_eq!(document.doctype(), None);
+  assert_eq!(document.root().children()[1].children(), &vec![paragraph]);
+  assert_eq!(document.quirks_mode(), dom::QuirksMode::Quirks);
+}
+
+// Test the quirks mode computed by the function parse_document for legacy doctypes
+#[test]
+fn test_parse_document_quirks_mode() {
+  let quirks_mode = |source: &str| -> dom::QuirksMode {
+    HTMLParser::parse_document(format!("{}<html></html>", source))
+      .unwrap()
+      .quirks_mode()
+  };
+
+  assert_eq!(quirks_mode("<!DOCTYPE html>"), dom::QuirksMode::NoQuirks);
+  assert_eq!(
+    quirks_mode("<!DOCTYPE html SYSTEM \"about:legacy-compat\">"),
+    dom::QuirksMode::NoQuirks
+  );
+  assert_eq!(
+    quirks_mode("<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01//EN\" \"http://www.w3.org/TR/html4/strict.dtd\">"),
+    dom::QuirksMode::NoQuirks
+  );
+  assert_eq!(
+    quirks_mode("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-transitional.dtd\">"),
+    dom::QuirksMode::LimitedQuirks
+  );
+  assert_eq!(
+    quirks_mode("<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\" \"http://www.w3.org/TR/html4/loose.dtd\">"),
+    dom::QuirksMode::LimitedQuirks
+  );
+  assert_eq!(
+    quirks_mode("<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\">"),
+    dom::QuirksMode::Quirks
+  );
+  assert_eq!(
+    quirks_mode("<!DOCTYPE HTML PUBLIC \"-//IETF//DTD HTML 2.0//EN\">"),
+    dom::QuirksMode::Quirks
+  );
+  assert_eq!(quirks_mode("<!DOCTYPE svg>"), dom::QuirksMode::Quirks);
+  assert_eq!(quirks_mode("<!DOCTYPE>"), dom::QuirksMode::Quirks);
+  assert_eq!(quirks_mode(""), dom::QuirksMode::Quirks);
+}
+
+// Test the spans of the nodes parsed by the function parse
+#[test]
+fn test_parse_spans() {
+  let source: &str = "<!DOCTYPE html>\n<p class=a>caf\u{E9}\n<!-- note --><br></p>";
+  let document: dom::Document = HTMLParser::parse_document(source.to_string()).unwrap();
+  let span = |node: &dom::Node| -> (usize, usize, usize, usize) {
+    let span: &dom::Span = node.span().unwrap();
+    (span.start(), span.end(), span.line(), span.column())
+  };
+  let root: &dom::Node = document.root();
+  let body: &dom::Node = &root.children()[1];
+  let paragraph: &dom::Node = &body.children()[0];
+
+  // Assert that the doctype and the root span their source
+  assert_eq!(span(document.doctype().unwrap()), (0, 15, 1, 1));
+  assert_eq!(span(root), (0, source.len(), 1, 1));
+  // Assert that an element spans from its start tag to its end tag, and its children their text
+  assert_eq!(span(paragraph), (16, 54, 2, 1));
+  assert_eq!(span(&paragraph.children()[0]), (27, 33, 2, 12));
+  assert_eq!(span(&paragraph.children()[1]), (33, 46, 3, 1));
+  assert_eq!(span(&paragraph.children()[2]), (46, 50, 3, 14));
+  // Assert that the body spans its contents, and that the empty head has no span
+  assert_eq!(span(body), (16, 54, 2, 1));
+  assert_eq!(root.children()[0].span(), None);
+
+  // Assert that spans are ignored when comparing nodes
+  let paragraph_without_span: dom::Node = dom::Node::element(
+    "p".to_string(),
+    hashmap![String::from("class") => String::from("a")],
+    vec![
+      dom::Node::text("caf\u{E9}\n".to_string()),
+      dom::Node::comment(" note ".to_string()),
+      dom::Node::element("br".to_string(), hashmap![], vec![]),
+    ],
+  );
+  assert_eq!(*paragraph, paragraph_without_span);
+}
+
+// Test the diagnostics reported by the function parse_document for the errors it recovers from
+#[test]
+fn test_parse_diagnostics() {
+  let diagnostics = |source: &str| -> Vec<(dom::DiagnosticKind, usize, usize)> {
+    HTMLParser::parse_document(source.to_string())
+      .unwrap()
+      .diagnostics()
+      .iter()
+      .map(|diagnostic: &dom::Diagnostic| {
+        (diagnostic.kind(), diagnostic.span().line(), diagnostic.span().column())
+      })
+      .collect()
+  };
+
+  // Assert that a valid document, with optional end tags left out, has no diagnostics
+  assert_eq!(
+    diagnostics("<!DOCTYPE html><title>T</title><p>one<p>two<ul><li>a<li>b</ul>"),
+    vec![]
+  );
+  // Assert that attribute recoveries are reported at the attribute
+  assert_eq!(
+    diagnostics("<!DOCTYPE html>\n<div hidden class=main></div>"),
+    vec![
+      (dom::DiagnosticKind::AttributeWithoutValue, 2, 6),
+      (dom::DiagnosticKind::UnquotedAttributeValue, 2, 19),
+    ]
+  );
+  assert_eq!(
+    diagnostics("<!DOCTYPE html><p id='a' ID='b'>"),
+    vec![(dom::DiagnosticKind::DuplicateAttribute, 1, 26)]
+  );
+  // Assert that a mismatched end tag is reported, and the element it leaves open too
+  assert_eq!(
+    diagnostics("<!DOCTYPE html><div><span>text</div></span>"),
+    vec![
+      (dom::DiagnosticKind::UnclosedElement, 1, 21),
+      (dom::DiagnosticKind::UnexpectedEndTag, 1, 37),
+    ]
+  );
+  // Assert that an element left open at EOF is reported
+  assert_eq!(
+    diagnostics("<!DOCTYPE html><div>text"),
+    vec![(dom::DiagnosticKind::UnclosedElement, 1, 16)]
+  );
+  // Assert that doctype, markup declaration, tag, and '<' errors are reported
+  assert_eq!(
+    diagnostics("<!DOCTYPE>\n<p>a < b<!ELEMENT br><body><!DOCTYPE html>"),
+    vec![
+      (dom::DiagnosticKind::MalformedDoctype, 1, 1),
+      (dom::DiagnosticKind::UnescapedLessThan, 2, 6),
+      (dom::DiagnosticKind::BogusComment, 2, 9),
+      (dom::DiagnosticKind::MisplacedTag, 2, 22),
+      (dom::DiagnosticKind::MisplacedDoctype, 2, 28),
+    ]
+  );
+  // Assert that an unterminated CDATA section is reported, and the elements it leaves open
+  assert_eq!(
+    diagnostics("<!DOCTYPE html><svg><![CDATA[x"),
+    vec![
+      (dom::DiagnosticKind::UnterminatedCdata, 1, 21),
+      (dom::DiagnosticKind::UnclosedElement, 1, 16),
+    ]
+  );
+
+  let document: dom::Document =
+    HTMLParser::parse_document("<!DOCTYPE html><div>text".to_string()).unwrap();
+  // Assert that a diagnostic displays its position and message
+  assert_eq!(
+    document.diagnostics()[0].to_string(),
+    "1:16: Element '<div>' is not closed"
   );
 }
@@ -0,0 +1,211 @@
+// Element categories and scope rules from the HTML tree construction algorithm
+// https://html.spec.whatwg.org/multipage/parsing.html#tree-construction
+
+// Elements that never have contents or an end tag
+const VOID_ELEMENTS: [&str; 16] = [
+  "area", "base", "basefont", "bgsound", "br", "col", "embed", "hr", "img", "input", "keygen",
+  "link", "meta", "param", "source", "track",
+];
+
+// Elements that belong in <head> when they appear before any body content
+const HEAD_ELEMENTS: [&str; 9] = [
+  "base", "basefont", "bgsound", "link", "meta", "script", "style", "template", "title",
+];
+
+// Elements with special parsing rules: an unknown end tag can't implicitly close them
+// https://html.spec.whatwg.org/multipage/parsing.html#special
+const SPECIAL_ELEMENTS: [&str; 83] = [
+  "address", "applet", "area", "article", "aside", "base", "basefont", "bgsound", "blockquote",
+  "body", "br", "button", "caption", "center", "col", "colgroup", "dd", "details", "dir", "div",
+  "dl", "dt", "embed", "fieldset", "figcaption", "figure", "footer", "form", "frame", "frameset",
+  "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "iframe", "img",
+  "input", "keygen", "li", "link", "listing", "main", "marquee", "menu", "meta", "nav", "noembed",
+  "noframes", "noscript", "object", "ol", "p", "param", "plaintext", "pre", "script", "search",
+  "section", "select", "source", "style", "summary", "table", "tbody", "td", "template",
+  "textarea", "tfoot", "th", "thead", "title", "tr", "track", "ul", "wbr", "xmp",
+];
+
+// Start tags that close an open <p> element
+const CLOSES_P_ELEMENTS: [&str; 40] = [
+  "address", "article", "aside", "blockquote", "center", "dd", "details", "dialog", "dir", "div",
+  "dl", "dt", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5",
+  "h6", "header", "hgroup", "hr", "li", "listing", "main", "menu", "nav", "ol", "p", "plaintext",
+  "pre", "search", "section", "summary", "ul", "xmp",
+];
+
+// Elements whose end tag may be left out, so that closing them implicitly is not an error
+// https://html.spec.whatwg.org/multipage/syntax.html#optional-tags
+const OPTIONAL_END_TAG_ELEMENTS: [&str; 21] = [
+  "html", "head", "body", "li", "dt", "dd", "p", "rt", "rp", "rb", "rtc", "optgroup", "option",
+  "colgroup", "caption", "thead", "tbody", "tfoot", "tr", "td", "th",
+];
+
+// SVG and MathML elements that are special too. They are in the list of open elements with the
+// prefix of their namespace (see foreign_content::open_element_name), and also bound the scopes
+const FOREIGN_SPECIAL_ELEMENTS: [&str; 9] = [
+  "math mi", "math mo", "math mn", "math ms", "math mtext", "math annotation-xml",
+  "svg foreignObject", "svg desc", "svg title",
+];
+
+const HEADING_ELEMENTS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+const TABLE_SECTION_ELEMENTS: [&str; 3] = ["tbody", "thead", "tfoot"];
+
+const TABLE_CELL_ELEMENTS: [&str; 2] = ["td", "th"];
+
+// The kinds of scope used to decide whether an element is still open "in scope": each kind
+// stops the search at a different set of elements
+// https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-scope
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(super) enum Scope {
+  Default,
+  ListItem,
+  Button,
+  Table,
+}
+
+impl Scope {
+  fn is_boundary(self, tag_name: &str) -> bool {
+    let default_boundary: bool = matches!(
+      tag_name,
+      "applet" | "caption" | "html" | "table" | "td" | "th" | "marquee" | "object" | "template"
+    ) || FOREIGN_SPECIAL_ELEMENTS.contains(&tag_name);
+    match self {
+      Scope::Default => default_boundary,
+      Scope::ListItem => default_boundary || matches!(tag_name, "ol" | "ul"),
+      Scope::Button => default_boundary || tag_name == "button",
+      Scope::Table => matches!(tag_name, "html" | "table" | "template"),
+    }
+  }
+}
+
+pub(crate) fn is_void_element(tag_name: &str) -> bool {
+  VOID_ELEMENTS.contains(&tag_name)
+}
+
+pub(super) fn is_head_element(tag_name: &str) -> bool {
+  HEAD_ELEMENTS.contains(&tag_name)
+}
+
+pub(super) fn has_optional_end_tag(tag_name: &str) -> bool {
+  OPTIONAL_END_TAG_ELEMENTS.contains(&tag_name)
+}
+
+fn is_special_element(tag_name: &str) -> bool {
+  SPECIAL_ELEMENTS.contains(&tag_name) || FOREIGN_SPECIAL_ELEMENTS.contains(&tag_name)
+}
+
+fn is_heading_element(tag_name: &str) -> bool {
+  HEADING_ELEMENTS.contains(&tag_name)
+}
+
+// Is one of 'targets' open, searching from the current node until a boundary of 'scope'?
+pub(super) fn has_element_in_scope(open_elements: &[String], targets: &[&str], scope: Scope) -> bool {
+  for tag_name in open_elements.iter().rev() {
+    if targets.contains(&&**tag_name) {
+      return true;
+    }
+    if scope.is_boundary(tag_name) {
+      return false;
+    }
+  }
+  false
+}
+
+// Is an open element with this tag name closed by the end tag of an "ordinary" element? The
+// search stops at the first special element
+// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody (any other end tag)
+fn has_element_closed_by_any_other_end_tag(open_elements: &[String], tag_name: &str) -> bool {
+  for open_element in open_elements.iter().rev() {
+    if open_element == tag_name {
+      return true;
+    }
+    if is_special_element(open_element) {
+      return false;
+    }
+  }
+  false
+}
+
+// Does the end tag close the current element, either because it's the current element's own
+// end tag or because it closes one of its ancestors? An end tag that closes nothing is ignored
+pub(super) fn end_tag_closes_current_element(open_elements: &[String], tag_name: &str) -> bool {
+  match tag_name {
+    "p" => has_element_in_scope(open_elements, &["p"], Scope::Button),
+    "li" => has_element_in_scope(open_elements, &["li"], Scope::ListItem),
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+      has_element_in_scope(open_elements, &HEADING_ELEMENTS, Scope::Default)
+    }
+    "table" | "tbody" | "thead" | "tfoot" | "tr" | "td" | "th" | "caption" => {
+      has_element_in_scope(open_elements, &[tag_name], Scope::Table)
+    }
+    _ if is_special_element(tag_name) => {
+      has_element_in_scope(open_elements, &[tag_name], Scope::Default)
+    }
+    _ => has_element_closed_by_any_other_end_tag(open_elements, tag_name),
+  }
+}
+
+// Does the end tag belong to the element being closed? A heading end tag closes any heading
+pub(super) fn end_tag_matches(element_tag_name: &str, end_tag_name: &str) -> bool {
+  element_tag_name == end_tag_name
+    || (is_heading_element(element_tag_name) && is_heading_element(end_tag_name))
+}
+
+// Search the open list items (or definition terms and descriptions) for one that a new item
+// implicitly closes. Items nested inside another special element, such as a sub-list, stay open
+fn has_open_list_item(open_elements: &[String], items: &[&str]) -> bool {
+  for tag_name in open_elements.iter().rev() {
+    if items.contains(&&**tag_name) {
+      return true;
+    }
+    if is_special_element(tag_name) && !matches!(&**tag_name, "address" | "div" | "p") {
+      return false;
+    }
+  }
+  false
+}
+
+// Does this start tag imply the end tag of the current element (e.g. '<p>one<p>two')? In quirks
+// mode a table doesn't close an open paragraph
+pub(super) fn start_tag_closes_current_element(
+  open_elements: &[String],
+  tag_name: &str,
+  quirks: bool,
+) -> bool {
+  let current: &str = match open_elements.last() {
+    Some(current) => current,
+    None => return false,
+  };
+  let closes_p: bool = CLOSES_P_ELEMENTS.contains(&tag_name) || (tag_name == "table" && !quirks);
+  if closes_p && has_element_in_scope(open_elements, &["p"], Scope::Button) {
+    return true;
+  }
+  match tag_name {
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => is_heading_element(current),
+    "li" => has_open_list_item(open_elements, &["li"]),
+    "dd" | "dt" => has_open_list_item(open_elements, &["dd", "dt"]),
+    "button" => has_element_in_scope(open_elements, &["button"], Scope::Default),
+    "option" => current == "option",
+    "optgroup" => current == "option" || current == "optgroup",
+    "td" | "th" => has_element_in_scope(open_elements, &TABLE_CELL_ELEMENTS, Scope::Table),
+    "tr" => has_element_in_scope(open_elements, &["tr", "td", "th"], Scope::Table),
+    "tbody" | "thead" | "tfoot" | "caption" | "colgroup" => has_element_in_scope(
+      open_elements,
+      &["tbody", "thead", "tfoot", "tr", "td", "th", "caption", "colgroup"],
+      Scope::Table,
+    ),
+    _ => false,
+  }
+}
+
+// Return the element that must be inserted between the current element and a new child, as
+// browsers do for table rows and cells written without their row group or row
+pub(super) fn implied_parent(current: &str, tag_name: &str) -> Option<&'static str> {
+  let is_cell: bool = TABLE_CELL_ELEMENTS.contains(&tag_name);
+  match current {
+    "table" if tag_name == "tr" || is_cell => Some("tbody"),
+    _ if TABLE_SECTION_ELEMENTS.contains(&current) && is_cell => Some("tr"),
+    _ => None,
+  }
+}
@@ -1,3 +1,8 @@
 pub mod css;
+pub mod document;
+pub mod encoding;
 pub mod html;
 pub mod text;
+
+#[cfg(test)]
+mod tests;
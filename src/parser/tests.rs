@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use super::css::CSSParser;
+use super::document::DocumentParser;
+use super::html::{HTMLParser, MAX_NESTING_DEPTH};
+
+// Well-formed and malformed documents that the corpus is derived from
+const HTML_SEEDS: [&str; 9] = [
+  "<!DOCTYPE html><html lang='en'><head><title>Q&amp;A</title><style>p { color: #ff0000; }</style></head><body><p class=intro>Hello</p></body></html>",
+  "<div id=\"main\" hidden><p>one<p>two<ul><li>a<li>b</ul></div>",
+  "<table><tr><td>1<td>2<tr><th>3</table>",
+  "<!-- caf\u{E9} --><p>\u{65E5}\u{672C}\u{8A9E} &eacute;&#233;&#xE9;&notin; \u{1F600}</p>",
+  "<script>if (a < b && c) { x = '</p>'; }</script><textarea>&lt;b&gt;</textarea>",
+  "<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01//EN\" 'http://www.w3.org/TR/html4/strict.dtd'><p>a < b</p>",
+  "<?xml version=\"1.0\"?><!ELEMENT br EMPTY></ 3><div / class=a/b>x</span></div>",
+  "<select><option>a<option>b</select><dl><dt>t<dd>d</dl><h1>x<h2>y</h1>",
+  "<svg viewBox='0 0 1 1'><![CDATA[a<b]]><foreignObject><p>x</foreignObject><rect/></svg><math><mi>y</math>",
+];
+
+// Context elements the fragments of the corpus are parsed in
+const FRAGMENT_CONTEXTS: [&str; 4] = ["div", "tr", "textarea", "svg"];
+
+const CSS_SEEDS: [&str; 12] = [
+  "div.note, #main > p, a[href^=\"http\" i] { width: 10px; color: #a3e4d7; display: block; }",
+  "* { margin: 0px; } h1 { font-size: 2em } p:hover { color: red }",
+  "ol { counter-reset: item } li::before, a:after { content: counter(item) \". \" attr(href) }",
+  "li:nth-child(2n+1 of .a):not(:has(> img, + p)), :is(ul, ol) > :where(li:empty) { margin: 0px }",
+  "form:focus-within, input:checked:not(:disabled), a:link:active { color: #000 }",
+  "/* caf\u{E9} */ .caf\u{E9} { content: '\u{1F600}'; } @media screen { p { color: #fff } }",
+  "p { color: rgba(255, 0, 0, 50%); background: hsl(120deg 50% 25% / .5) hwb(1turn 0 0) }",
+  "a { border-color: currentColor; color: #0f08; background: transparent RebeccaPurple }",
+  "p { margin: 0 auto; border: 2px solid #333; font: italic bold 12px/30px \"A B\", serif }",
+  "p { margin-left: -10px; width: +.5e1em; line-height: 1.4; z-index: 3; opacity: 80% }",
+  "a { color: #12; } b { width: 1.2.3px; } c { : ; } } { d {",
+  "/* a */ .a\\:b/**/{ content: '\\'/*' } @namespace x url( \"y\" ); [a=\"b\n] { c: url(d e) }",
+];
+
+// Fragments inserted at random positions to break the seeds in interesting ways
+const FRAGMENTS: [&str; 24] = [
+  "<", ">", "</", "/>", "<!--", "-->", "<!", "<!DOCTYPE", "&", "&#", "&#x", "&amp", ";", "\"",
+  "'", "=", "{", "}", ":", "#", "\u{E9}", "\u{1F600}", "\u{0}", "\r\n",
+];
+
+// A small deterministic xorshift generator, so that failures are reproducible
+struct Random(u64);
+
+impl Random {
+  fn next(&mut self, bound: usize) -> usize {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    (self.0 % bound as u64) as usize
+  }
+
+  // Return a random character boundary of 'source'
+  fn boundary(&mut self, source: &str) -> usize {
+    let boundaries: Vec<usize> = source
+      .char_indices()
+      .map(|(index, _)| index)
+      .chain(std::iter::once(source.len()))
+      .collect();
+    boundaries[self.next(boundaries.len())]
+  }
+}
+
+// Build the corpus for a list of seeds: every seed truncated at each character, and random
+// insertions, deletions and duplications of parts of it
+fn corpus(seeds: &[&str]) -> Vec<String> {
+  let mut random: Random = Random(0x2545_F491_4F6C_DD1D);
+  let mut inputs: Vec<String> = vec![String::new()];
+  for seed in seeds {
+    for (index, _) in seed.char_indices() {
+      inputs.push(seed[..index].to_string());
+    }
+    inputs.push(seed.to_string());
+
+    for _ in 0..200 {
+      let mut input: String = seed.to_string();
+      for _ in 0..1 + random.next(4) {
+        let start: usize = random.boundary(&input);
+        let end: usize = start.max(random.boundary(&input));
+        match random.next(3) {
+          0 => input.insert_str(start, FRAGMENTS[random.next(FRAGMENTS.len())]),
+          1 => input.replace_range(start..end, ""),
+          _ => {
+            let duplicate: String = input[start..end].to_string();
+            input.insert_str(end, &duplicate);
+          }
+        }
+      }
+      inputs.push(input);
+    }
+  }
+  inputs
+}
+
+// Test that the HTML parser returns for any input, without panicking
+#[test]
+fn test_parse_html_corpus() {
+  for input in corpus(&HTML_SEEDS) {
+    // Assert that parsing returns a document, or a descriptive error
+    if let Err(error) = HTMLParser::parse_document(input.clone()) {
+      assert!(!error.is_empty(), "Empty error for input {:?}", input);
+    }
+    if let Err(error) = DocumentParser::parse(input.clone(), Path::new("")) {
+      assert!(!error.is_empty(), "Empty error for input {:?}", input);
+    }
+    for context_tag in FRAGMENT_CONTEXTS {
+      if let Err(error) = HTMLParser::parse_fragment(input.clone(), context_tag) {
+        assert!(!error.is_empty(), "Empty error for input {:?}", input);
+      }
+    }
+  }
+}
+
+// Test that the CSS parser returns for any input, without panicking
+#[test]
+fn test_parse_css_corpus() {
+  for input in corpus(&CSS_SEEDS) {
+    // Assert that parsing returns a stylesheet, or a descriptive error
+    if let Err(error) = CSSParser::parse(input.clone()) {
+      assert!(!error.is_empty(), "Empty error for input {:?}", input);
+    }
+  }
+}
+
+// Test that deeply nested CSS doesn't exhaust the stack
+#[test]
+fn test_parse_deeply_nested_css() {
+  let stylesheet: crate::css::Stylesheet =
+    CSSParser::parse(format!("p {{ width: {} }}", "(".repeat(20_000))).unwrap();
+
+  // Assert that the rule is kept, without the declaration that can't be parsed
+  assert_eq!(stylesheet.rules().len(), 1);
+  assert!(stylesheet.rules()[0].declarations().is_empty());
+  // Assert that nested selectors are parsed, and that ones nested too deep are errors
+  let nested = |depth: usize| -> String {
+    format!("{}p{}", ":not(".repeat(depth), ")".repeat(depth))
+  };
+  assert!(CSSParser::parse_selector_list(nested(60)).is_ok());
+  assert!(CSSParser::parse_selector_list(nested(20_000)).is_err());
+}
+
+// Test that deeply nested input doesn't exhaust the stack
+#[test]
+fn test_parse_deeply_nested_html() {
+  let source: String = "<div>".repeat(20_000);
+  let document: crate::dom::Document = HTMLParser::parse_document(source).unwrap();
+  let mut depth: usize = 0;
+  let mut node: &crate::dom::Node = document.root();
+  while let Some(child) = node.children().last() {
+    depth += 1;
+    node = child;
+  }
+
+  // Assert that elements past the maximum depth become siblings
+  assert!(depth <= MAX_NESTING_DEPTH, "Tree is {} elements deep", depth);
+}
@@ -1,4 +1,4 @@
-use std::str::CharIndices;
+use std::cell::Cell;
 
 #[cfg(test)]
 mod tests;
@@ -6,52 +6,112 @@ mod tests;
 pub struct TextParser {
   position: usize,
   input: String,
+  // Byte offsets at which each line of the input starts
+  line_starts: Vec<usize>,
+  // The last position passed to line_column and its column, so that columns of increasing
+  // positions on a long line are counted from there instead of from the start of the line
+  last_column: Cell<(usize, usize)>,
+}
+
+// Move a byte offset forward to the next character boundary, and never past the end of the input
+fn next_char_boundary(input: &str, position: usize) -> usize {
+  (position..input.len())
+    .find(|&index: &usize| input.is_char_boundary(index))
+    .unwrap_or(input.len())
 }
 
 impl TextParser {
+  // Start reading at a byte offset. An offset inside a multibyte character starts at the next
+  // character instead
   pub fn new(position: usize, input: String) -> Self {
-    Self { position, input }
+    let line_starts: Vec<usize> = std::iter::once(0)
+      .chain(input.match_indices('\n').map(|(index, _)| index + 1))
+      .collect();
+    Self {
+      position: next_char_boundary(&input, position),
+      input,
+      line_starts,
+      last_column: Cell::new((0, 1)),
+    }
   }
 
   pub fn position(&self) -> usize {
     self.position
   }
 
+  // Advance the position by a number of bytes, never past the end of the input. An offset inside
+  // a multibyte character moves on to the next character, so the position is always on a
+  // character boundary
   pub fn increment_position(&mut self, value: usize) {
-    self.position += value;
+    self.position = next_char_boundary(&self.input, self.position.saturating_add(value));
   }
 
   pub fn input(&self) -> &str {
     &self.input
   }
 
-  // Read the current character without consuming it
-  pub fn next_char(&self) -> char {
-    self.input[self.position..].chars().next().unwrap()
+  // Return the input that has not been consumed yet
+  pub fn rest(&self) -> &str {
+    self.input.get(self.position..).unwrap_or("")
   }
 
-  // Read the character from the current position to a given offset without consuming it
-  pub fn next_offset_char(&self, offset: usize) -> char {
-    self.input[self.position..].chars().nth(offset).unwrap()
+  // Return the line and column, both starting at 1, of the character at a byte offset. The
+  // column counts characters, not bytes
+  pub fn line_column(&self, position: usize) -> (usize, usize) {
+    let line: usize = self.line_starts.partition_point(|&start: &usize| start <= position);
+    let line_start: usize = self.line_starts[line - 1];
+    let position: usize = position.min(self.input.len());
+    let (last_position, last_column): (usize, usize) = self.last_column.get();
+    let same_line: bool = (line_start..=position).contains(&last_position);
+    let (start, start_column): (usize, usize) = if same_line {
+      (last_position, last_column)
+    } else {
+      (line_start, 1)
+    };
+    let column: usize = self
+      .input
+      .get(start..position)
+      .map_or(0, |line: &str| line.chars().count())
+      + start_column;
+    self.last_column.set((position, column));
+    (line, column)
+  }
+
+  // Read the current character without consuming it, or None at the end of the input
+  pub fn next_char(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  // Read the character from the current position to a given offset without consuming it, or
+  // None if the input ends before it
+  pub fn next_offset_char(&self, offset: usize) -> Option<char> {
+    self.rest().chars().nth(offset)
   }
 
   // Do the next characters start with the given string?
   pub fn starts_with(&self, s: &str) -> bool {
-    self.input[self.position..].starts_with(s)
+    self.rest().starts_with(s)
+  }
+
+  // Do the next characters start with the given string, ignoring ASCII case?
+  pub fn starts_with_ignore_case(&self, s: &str) -> bool {
+    self
+      .rest()
+      .get(..s.len())
+      .is_some_and(|start: &str| start.eq_ignore_ascii_case(s))
   }
 
   // Return true if all input is consumed
   pub fn eof(&self) -> bool {
-    self.position >= self.input.len()
+    self.rest().is_empty()
   }
 
-  // Return the current character, and advance self.position to the next character
-  pub fn consume_char(&mut self) -> char {
-    let mut iter: CharIndices = self.input[self.position..].char_indices();
-    let (_, current_char): (_, char) = iter.next().unwrap();
-    let (next_position, _): (usize, _) = iter.next().unwrap_or((1, ' '));
-    self.position += next_position;
-    return current_char;
+  // Return the current character and advance the position past it, or None at the end of the
+  // input
+  pub fn consume_char(&mut self) -> Option<char> {
+    let current_char: char = self.next_char()?;
+    self.position += current_char.len_utf8();
+    Some(current_char)
   }
 
   // Consume characters until 'test' returns false
@@ -60,25 +120,20 @@ impl TextParser {
     F: Fn(char) -> bool,
   {
     let mut result: String = String::new();
-    while !self.eof() && test(self.next_char()) {
-      result.push(self.consume_char());
+    while let Some(c) = self.next_char().filter(|&c: &char| test(c)) {
+      result.push(c);
+      self.position += c.len_utf8();
     }
-    return result;
+    result
   }
 
+  // Consume characters up to (but not including) 'target', or up to the end of the input if
+  // 'target' is not found
   pub fn consume_until_match(&mut self, target: &str) -> String {
-    let mut result: String = String::new();
-    let mut target_found: bool = false;
-    let mut potential_match: &str = "";
-    while !self.eof() && !target_found {
-      potential_match = &self.input[self.position..self.position + target.len()];
-      if potential_match.starts_with(target) {
-        target_found = true;
-      } else {
-        result.push(self.consume_char());
-      }
-    }
-    return result;
+    let length: usize = self.rest().find(target).unwrap_or(self.rest().len());
+    let result: String = self.rest()[..length].to_string();
+    self.position += length;
+    result
   }
 
   // Consume and discard zero or more whitespace characters
@@ -88,11 +143,10 @@ impl TextParser {
 
   // Consume the next character and return it, or an error if it doesn't match expected
   pub fn expect_char(&mut self, expected: char) -> Result<char, String> {
-    let c: char = self.consume_char();
-    if c == expected {
-      Ok(c)
-    } else {
-      Err(format!("Expected '{}', found '{}'", expected, c))
+    match self.consume_char() {
+      Some(c) if c == expected => Ok(c),
+      Some(c) => Err(format!("Expected '{}', found '{}'", expected, c)),
+      None => Err(format!("Expected '{}', found end of input", expected)),
     }
   }
 }
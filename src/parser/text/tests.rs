@@ -6,15 +6,19 @@ fn test_next_char() {
   let mut text_parser: TextParser = TextParser::new(0, "<p>Hello World!</p>".to_string());
 
   // Assert that the next_char method correctly returns the character '<'
-  assert_eq!(text_parser.next_char(), '<');
+  assert_eq!(text_parser.next_char(), Some('<'));
 
   text_parser.position = 1;
   // Assert that the next_char method correctly returns the character 'p'
-  assert_eq!(text_parser.next_char(), 'p');
+  assert_eq!(text_parser.next_char(), Some('p'));
 
   text_parser.position = 18;
   // Assert that the next_char method correctly returns the character '>'
-  assert_eq!(text_parser.next_char(), '>');
+  assert_eq!(text_parser.next_char(), Some('>'));
+
+  text_parser.position = 19;
+  // Assert that the next_char method returns None at the end of the input
+  assert_eq!(text_parser.next_char(), None);
 }
 
 // Test the method next_offset_char of the TextParser struct implementation
@@ -23,13 +27,46 @@ fn test_next_offset_char() {
   let text_parser: TextParser = TextParser::new(0, "<p>Hello World!</p>".to_string());
 
   // Assert that the next_offset_char method correctly returns the character '<'
-  assert_eq!(text_parser.next_offset_char(1), 'p');
+  assert_eq!(text_parser.next_offset_char(1), Some('p'));
 
   // Assert that the next_offset_char method correctly returns the character 'p'
-  assert_eq!(text_parser.next_offset_char(9), 'W');
+  assert_eq!(text_parser.next_offset_char(9), Some('W'));
 
   // Assert that the next_offset_char method correctly returns the character '>'
-  assert_eq!(text_parser.next_offset_char(18), '>');
+  assert_eq!(text_parser.next_offset_char(18), Some('>'));
+
+  // Assert that the next_offset_char method returns None past the end of the input
+  assert_eq!(text_parser.next_offset_char(19), None);
+}
+
+// Test the method starts_with_ignore_case of the TextParser struct implementation
+#[test]
+fn test_starts_with_ignore_case() {
+  let text_parser: TextParser = TextParser::new(0, "<!DocType html>".to_string());
+
+  // Assert that the starts_with_ignore_case method ignores the case of ASCII letters
+  assert!(text_parser.starts_with_ignore_case("<!doctype"));
+  // Assert that the starts_with_ignore_case method returns false past the end of the input
+  assert!(!text_parser.starts_with_ignore_case("<!doctype html> and more"));
+}
+
+// Test the method line_column of the TextParser struct implementation
+#[test]
+fn test_line_column() {
+  let text_parser: TextParser = TextParser::new(0, "<p>\n  caf\u{E9} <b>\n</p>".to_string());
+
+  // Assert that the line_column method returns 1:1 for the first character
+  assert_eq!(text_parser.line_column(0), (1, 1));
+  // Assert that a line starts right after '\n' and that the newline belongs to the line it ends
+  assert_eq!(text_parser.line_column(3), (1, 4));
+  assert_eq!(text_parser.line_column(4), (2, 1));
+  // Assert that the column counts characters, not bytes ('\u{E9}' takes two bytes)
+  assert_eq!(text_parser.line_column(12), (2, 8));
+  assert_eq!(text_parser.line_column(16), (3, 1));
+  // Assert that positions can be looked up in any order
+  assert_eq!(text_parser.line_column(9), (2, 6));
+  assert_eq!(text_parser.line_column(6), (2, 3));
+  assert_eq!(text_parser.line_column(12), (2, 8));
 }
 
 // Test the method starts_with of the TextParser struct implementation
@@ -63,15 +100,46 @@ fn test_eof() {
   let mut text_parser: TextParser = TextParser::new(0, "<p>Hello World!</p>".to_string());
 
   // Assert that the eof method correctly returns false because the current position is not at the end of the input string
-  assert_eq!(text_parser.eof(), false);
+  assert!(!text_parser.eof());
 
   text_parser.increment_position(5);
   // Assert that the eof method correctly returns false because the current position is not at the end of the input string
-  assert_eq!(text_parser.eof(), false);
+  assert!(!text_parser.eof());
 
   text_parser.increment_position(19);
   // Assert that the eof method correctly returns true because the current position is at the end of the input string
-  assert_eq!(text_parser.eof(), true);
+  assert!(text_parser.eof());
+}
+
+// Test the method rest of the TextParser struct implementation
+#[test]
+fn test_rest() {
+  let text_parser: TextParser = TextParser::new(1, "caf\u{E9}!".to_string());
+
+  // Assert that the rest method returns the input from the current position
+  assert_eq!(text_parser.rest(), "af\u{E9}!");
+}
+
+// Test the methods new and increment_position of the TextParser struct implementation with an
+// offset inside a multibyte character
+#[test]
+fn test_position_not_on_char_boundary() {
+  let mut text_parser: TextParser = TextParser::new(4, "caf\u{E9}!".to_string());
+
+  // Assert that the new method moves an offset inside '\u{E9}' on to the next character
+  assert_eq!(text_parser.position(), 5);
+  assert_eq!(text_parser.rest(), "!");
+
+  text_parser = TextParser::new(0, "caf\u{E9}!".to_string());
+  text_parser.increment_position(4);
+  // Assert that the increment_position method does the same, so rest never drops the input
+  assert_eq!(text_parser.position(), 5);
+  assert_eq!(text_parser.rest(), "!");
+  assert_eq!(text_parser.next_char(), Some('!'));
+
+  text_parser.increment_position(usize::MAX);
+  // Assert that the increment_position method stops at the end of the input
+  assert!(text_parser.eof());
 }
 
 // Test the method consume_char of the TextParser struct implementation
@@ -81,23 +149,31 @@ fn test_consume_char() {
 
   // Assert that the consume_char method correctly consumes only the character '<'
   // and correctly returns the consumed characters as a character
-  assert_eq!(text_parser.consume_char(), '<');
+  assert_eq!(text_parser.consume_char(), Some('<'));
   // Assert that the position is correctly updated to 1 after consuming the character
   assert_eq!(text_parser.position, 1);
 
   text_parser.position = 3;
   // Assert that the consume_char method correctly consumes only the character 'H'
   // and correctly returns the consumed characters as a character
-  assert_eq!(text_parser.consume_char(), 'H');
+  assert_eq!(text_parser.consume_char(), Some('H'));
   // Assert that the position is correctly updated to 4 after consuming the character
   assert_eq!(text_parser.position, 4);
 
   text_parser.position = 8;
   // Assert that the consume_char method correctly consumes only the character ' '
   // and correctly returns the consumed characters as a character
-  assert_eq!(text_parser.consume_char(), ' ');
+  assert_eq!(text_parser.consume_char(), Some(' '));
   // Assert that the position is correctly updated to 9 after consuming the character
   assert_eq!(text_parser.position, 9);
+
+  text_parser = TextParser::new(0, "\u{E9}".to_string());
+  // Assert that the consume_char method moves past all the bytes of a multibyte character
+  assert_eq!(text_parser.consume_char(), Some('\u{E9}'));
+  assert_eq!(text_parser.position, 2);
+  // Assert that the consume_char method returns None at the end of the input, without moving
+  assert_eq!(text_parser.consume_char(), None);
+  assert_eq!(text_parser.position, 2);
 }
 
 // Test the method consume_while of the TextParser struct implementation
@@ -119,7 +195,7 @@ fn test_consume_while() {
 
   // Assert that the consume_while method correctly returns an empty string when no digits are found
   // and correctly returns an empty character as a string
-  assert_eq!(text_parser.consume_while(|c| c.is_digit(10)), "");
+  assert_eq!(text_parser.consume_while(|c| c.is_ascii_digit()), "");
   // Assert that the position is correctly updated to 9 after consuming the characters
   assert_eq!(text_parser.position, 9);
 }
@@ -134,4 +210,29 @@ fn test_consume_until_match() {
   assert_eq!(text_parser.consume_until_match("-->"), " Hello World! ");
   // Assert that the position is correctly updated to 8 after consuming the characters
   assert_eq!(text_parser.position, 18);
+
+  text_parser = TextParser::new(4, "<!-- caf\u{E9} -".to_string());
+  // Assert that the consume_until_match method consumes the rest of the input when the target
+  // is missing, even if the input ends with a multibyte character or part of the target
+  assert_eq!(text_parser.consume_until_match("-->"), " caf\u{E9} -");
+  assert!(text_parser.eof());
+}
+
+// Test the method expect_char of the TextParser struct implementation
+#[test]
+fn test_expect_char() {
+  let mut text_parser: TextParser = TextParser::new(0, "<p".to_string());
+
+  // Assert that the expect_char method consumes and returns the expected character
+  assert_eq!(text_parser.expect_char('<'), Ok('<'));
+  // Assert that the expect_char method returns a descriptive error for any other character
+  assert_eq!(
+    text_parser.expect_char('>'),
+    Err("Expected '>', found 'p'".to_string())
+  );
+  // Assert that the expect_char method returns an error at the end of the input
+  assert_eq!(
+    text_parser.expect_char('>'),
+    Err("Expected '>', found end of input".to_string())
+  );
 }
@@ -4,21 +4,67 @@ use crate::css;
 use crate::dom;
 use crate::hashmap;
 use super::matching::matching_rules;
+use super::shorthand;
+use super::state::StateMap;
 use super::tree::PropertyMap;
 
-// Apply styles to a single element, returning the specified values
-pub fn specified_values(element: &dom::ElementData, stylesheet: &css::Stylesheet) -> PropertyMap {
+// Apply styles to a single element, returning the specified values. 'ancestors' are the
+// ancestors of the element, from the root to its parent, for combinators and pseudo-classes.
+// Stylesheets are given in document order, so a later sheet wins over an earlier one at equal
+// specificity
+pub fn specified_values(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  stylesheets: &[css::Stylesheet],
+) -> PropertyMap {
+  specified_values_in_state(node, ancestors, stylesheets, &StateMap::new())
+}
+
+// Apply styles to a single element like specified_values, with the elements in the interaction
+// states of 'states' for the dynamic pseudo-classes
+pub fn specified_values_in_state(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  stylesheets: &[css::Stylesheet],
+  states: &StateMap,
+) -> PropertyMap {
+  cascade(node, ancestors, stylesheets, states, None)
+}
+
+// Apply the styles of the rules that select a pseudo-element of an element, e.g. 'p::before',
+// returning its specified values
+pub fn pseudo_element_values(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  stylesheets: &[css::Stylesheet],
+  states: &StateMap,
+  pseudo_element: css::PseudoElement,
+) -> PropertyMap {
+  cascade(node, ancestors, stylesheets, states, Some(pseudo_element))
+}
+
+// Apply the styles of the rules that match an element, or one of its pseudo-elements
+fn cascade(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  stylesheets: &[css::Stylesheet],
+  states: &StateMap,
+  pseudo_element: Option<css::PseudoElement>,
+) -> PropertyMap {
   let mut values: HashMap<String, css::Value> = hashmap![];
-  let mut rules: Vec<((usize, usize, usize), &css::Rule)> = matching_rules(element, stylesheet);
+  let mut rules: Vec<((usize, usize, usize), &css::Rule)> =
+    matching_rules(node, ancestors, stylesheets, states, pseudo_element);
 
-  // Go through the rules from lowest to highest specificity
-  rules.sort_by(|&(a, _), &(b, _)| a.cmp(&b));
+  // Go through the rules from lowest to highest specificity. The sort is stable, so rules with
+  // equal specificity keep their source order. Shorthands are expanded into their longhands in
+  // place, so that they override and are overridden by them in the same order
+  rules.sort_by_key(|&(specificity, _)| specificity);
   for (_, rule) in rules {
-    for declaration in rule.declarations() {
+    for declaration in rule.declarations().iter().flat_map(shorthand::expand) {
       values.insert(declaration.name().to_string(), declaration.value().clone());
     }
   }
-  return values;
+  values
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use crate::css;
 use crate::dom;
 use crate::hashmap;
 use crate::style::PropertyMap;
+use crate::parser::css::CSSParser;
 use super::specified_values;
 
 // Test the function specified_values
@@ -9,7 +10,7 @@ use super::specified_values;
 fn test_specified_values() {
   let tag_name: String = String::from("div");
   let attributes: dom::AttributeMap = hashmap![String::from("id") => String::from("container-id"), String::from("class") => String::from("container-class")];
-  let element: dom::ElementData = dom::ElementData::new(tag_name, attributes);
+  let node: dom::Node = dom::Node::element(tag_name, attributes, vec![]);
   let simple_selector: css::SimpleSelector = css::SimpleSelector::new(
     Some("div".to_string()),
     Some("container-id".to_string()),
@@ -19,11 +20,63 @@ fn test_specified_values() {
   let unit: css::Value = css::Value::Length(100.0, css::Unit::Px);
   let declaration: css::Declaration = css::Declaration::new("width".to_string(), unit);
   let rule: css::Rule = css::Rule::new(vec![selector], vec![declaration]);
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule.clone()]);
-  let values: PropertyMap = specified_values(&element, &stylesheet);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule.clone()])];
+  let values: PropertyMap = specified_values(&node, &[], &stylesheets);
 
   assert_eq!(
     values.get("width"),
     Some(&css::Value::Length(100.0, css::Unit::Px))
   );
 }
+
+// Test the function specified_values with several stylesheets
+#[test]
+fn test_specified_values_stylesheet_order() {
+  let node: dom::Node = dom::Node::element(String::from("div"), hashmap![], vec![]);
+  let selector: css::Selector =
+    css::Selector::Simple(css::SimpleSelector::new(Some("div".to_string()), None, vec![]));
+  let declaration_1: css::Declaration =
+    css::Declaration::new("width".to_string(), css::Value::Length(100.0, css::Unit::Px));
+  let declaration_2: css::Declaration =
+    css::Declaration::new("width".to_string(), css::Value::Length(200.0, css::Unit::Px));
+  let stylesheets: Vec<css::Stylesheet> = vec![
+    css::Stylesheet::new(vec![css::Rule::new(vec![selector.clone()], vec![declaration_1])]),
+    css::Stylesheet::new(vec![css::Rule::new(vec![selector], vec![declaration_2])]),
+  ];
+  let values: PropertyMap = specified_values(&node, &[], &stylesheets);
+
+  // Assert that at equal specificity the declaration from the later stylesheet wins
+  assert_eq!(
+    values.get("width"),
+    Some(&css::Value::Length(200.0, css::Unit::Px))
+  );
+}
+
+// Test the function specified_values with shorthands and their longhands
+#[test]
+fn test_specified_values_shorthands() {
+  let node: dom::Node = dom::Node::element(
+    String::from("div"),
+    hashmap![String::from("class") => String::from("box")],
+    vec![],
+  );
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    ".box { margin-top: 5px; } \
+     div { margin: 0 auto; margin-right: 1px; padding-left: 2px; } \
+     div { padding: 3px 4px; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let values: PropertyMap = specified_values(&node, &[], &stylesheets);
+  let px = |length: f32| -> Option<css::Value> { Some(css::Value::Length(length, css::Unit::Px)) };
+
+  // Assert that shorthands are expanded, and only their longhands are in the specified values
+  assert_eq!(values.get("margin"), None);
+  assert_eq!(values.get("margin-bottom").cloned(), Some(css::Value::Number(0.0)));
+  assert_eq!(values.get("margin-left").cloned(), Some(css::Value::Keyword(String::from("auto"))));
+  // Assert that longhands override a shorthand before them, and are overridden by one after them
+  assert_eq!(values.get("margin-right").cloned(), px(1.0));
+  assert_eq!(values.get("padding-left").cloned(), px(4.0));
+  // Assert that a longhand with a higher specificity wins over a shorthand
+  assert_eq!(values.get("margin-top").cloned(), px(5.0));
+}
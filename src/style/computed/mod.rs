@@ -0,0 +1,123 @@
+use crate::css;
+use super::tree::StyledNode;
+
+// The initial font size, 'medium'
+pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+// The initial color, black
+pub const DEFAULT_COLOR: css::Color = css::Color::new(0, 0, 0, 255);
+
+// Compute the font size of an element from its 'font-size' and the font sizes of its parent and
+// of the root element. Font sizes are inherited, and relative ones are relative to the parent.
+// The viewport is only known during layout, so font sizes in viewport units are not supported:
+// the font size of the parent is used instead
+fn compute_font_size(value: Option<&css::Value>, parent_font_size: f32, root_font_size: f32) -> f32 {
+  match value {
+    Some(&css::Value::Length(size, css::Unit::Em)) => size * parent_font_size,
+    Some(&css::Value::Percentage(size)) => size * parent_font_size / 100.0,
+    Some(&css::Value::Length(size, css::Unit::Rem)) => size * root_font_size,
+    Some(&css::Value::Length(_, css::Unit::Vw | css::Unit::Vh)) => parent_font_size,
+    Some(length @ css::Value::Length(..)) => length.to_px(),
+    _ => parent_font_size,
+  }
+}
+
+// Is the value of a property a length? A zero length may be written without a unit, and is
+// parsed as the number 0 then. Other numbers, e.g. in 'line-height', are not lengths
+fn is_length_property(name: &str) -> bool {
+  matches!(name, "width" | "height" | "font-size")
+    || name.starts_with("margin-")
+    || name.starts_with("padding-")
+    || (name.starts_with("border-") && name.ends_with("-width"))
+}
+
+// Return the width in px of the 'thin', 'medium' and 'thick' border widths
+// https://drafts.csswg.org/css-backgrounds/#border-width
+fn border_width_keyword(keyword: &str) -> Option<f32> {
+  match &*keyword.to_ascii_lowercase() {
+    "thin" => Some(1.0),
+    "medium" => Some(3.0),
+    "thick" => Some(5.0),
+    _ => None,
+  }
+}
+
+// Turn the specified values of a node and its descendants into computed values, as far as they
+// can be without layout: the font size of each node is computed, unitless zero lengths, lengths
+// in em and rem and border width keywords are resolved to px, and 'currentColor' to the color of the element.
+// Percentages and viewport units are left for layout
+// https://drafts.csswg.org/css-cascade/#computed
+fn compute_node_values(
+  node: &mut StyledNode,
+  parent_font_size: f32,
+  root_font_size: Option<f32>,
+  parent_color: css::Color,
+) {
+  // A unitless 0 in a length property is a zero length
+  for (name, value) in node.specified_values_mut().iter_mut() {
+    if *value == css::Value::Number(0.0) && is_length_property(name) {
+      *value = css::Value::Length(0.0, css::Unit::Px);
+    }
+  }
+  // The root element is the reference for rem, and its own rem lengths are relative to the
+  // initial font size
+  let font_size: f32 = compute_font_size(
+    node.specified_values().get("font-size"),
+    parent_font_size,
+    root_font_size.unwrap_or(DEFAULT_FONT_SIZE),
+  );
+  let root_font_size: f32 = root_font_size.unwrap_or(font_size);
+  // 'color' is inherited, and 'currentColor' in 'color' itself is the color of the parent
+  let color: css::Color = match node.specified_values().get("color") {
+    Some(&css::Value::ColorValue(color)) => color,
+    _ => parent_color,
+  };
+  node.set_font_size(font_size);
+  for (name, value) in node.specified_values_mut().iter_mut() {
+    *value = match (name.as_str(), &*value) {
+      ("font-size", css::Value::Length(..)) => css::Value::Length(font_size, css::Unit::Px),
+      (_, &css::Value::Length(length, css::Unit::Em)) => {
+        css::Value::Length(length * font_size, css::Unit::Px)
+      }
+      (_, &css::Value::Length(length, css::Unit::Rem)) => {
+        css::Value::Length(length * root_font_size, css::Unit::Px)
+      }
+      (_, css::Value::Keyword(keyword)) if keyword == "currentcolor" => {
+        css::Value::ColorValue(color)
+      }
+      (name, css::Value::Keyword(keyword))
+        if name.starts_with("border-") && name.ends_with("-width") =>
+      {
+        match border_width_keyword(keyword) {
+          Some(width) => css::Value::Length(width, css::Unit::Px),
+          None => continue,
+        }
+      }
+      _ => continue,
+    };
+  }
+  // A border side with the style 'none' or 'hidden' has no width, e.g. after 'border: 2px'. A side
+  // without a style keeps its width
+  for side in ["top", "right", "bottom", "left"] {
+    let hidden: bool = matches!(
+      node.value(&format!("border-{}-style", side)),
+      Some(css::Value::Keyword(style))
+        if style.eq_ignore_ascii_case("none") || style.eq_ignore_ascii_case("hidden")
+    );
+    if hidden {
+      let width: String = format!("border-{}-width", side);
+      node.specified_values_mut().insert(width, css::Value::Length(0.0, css::Unit::Px));
+    }
+  }
+  for child in node.children_mut() {
+    compute_node_values(child, font_size, Some(root_font_size), color);
+  }
+}
+
+// Compute the values of a style tree, from its root element
+pub fn compute_values(root: &mut StyledNode) {
+  compute_node_values(root, DEFAULT_FONT_SIZE, None, DEFAULT_COLOR);
+}
+
+#[cfg(test)]
+mod tests;
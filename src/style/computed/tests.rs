@@ -0,0 +1,161 @@
+use crate::css;
+use crate::dom;
+use crate::parser::css::CSSParser;
+use crate::parser::html::HTMLParser;
+use crate::style::{StateMap, StyledNode, style_tree};
+
+// Test the function compute_values, which style_tree calls
+#[test]
+fn test_compute_values() {
+  let root: dom::Node =
+    HTMLParser::parse("<div><p>a<span>b</span></p><h1>c</h1></div>".to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "html { font-size: 10px; } \
+     div { font-size: 2em; padding: 1.5em; margin: 2rem; width: 50%; } \
+     p { font-size: 150%; border-width: 0.5em; } \
+     span { font-size: 1rem; height: 1in; } \
+     h1 { font-size: 12pt; width: 2vw; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let div: &StyledNode = &style.children()[1].children()[0];
+  let paragraph: &StyledNode = &div.children()[0];
+  let span: &StyledNode = &paragraph.children()[1];
+  let heading: &StyledNode = &div.children()[1];
+  let px = |length: f32| -> Option<css::Value> { Some(css::Value::Length(length, css::Unit::Px)) };
+
+  // Assert that relative font sizes are relative to the parent, and rem to the root element
+  assert_eq!(style.font_size(), 10.0);
+  assert_eq!(div.font_size(), 20.0);
+  assert_eq!(paragraph.font_size(), 30.0);
+  assert_eq!(span.font_size(), 10.0);
+  assert_eq!(heading.font_size(), 16.0);
+  assert_eq!(div.value("font-size"), px(20.0));
+  // Assert that em lengths are relative to the font size of the element itself
+  assert_eq!(div.value("padding-top"), px(30.0));
+  assert_eq!(div.value("margin-left"), px(20.0));
+  assert_eq!(paragraph.value("border-bottom-width"), px(15.0));
+  // Assert that font sizes are inherited by text
+  assert_eq!(paragraph.children()[0].font_size(), 30.0);
+  // Assert that absolute lengths, percentages and viewport units are left for layout
+  assert_eq!(span.value("height"), Some(css::Value::Length(1.0, css::Unit::In)));
+  assert_eq!(div.value("width"), Some(css::Value::Percentage(50.0)));
+  assert_eq!(heading.value("width"), Some(css::Value::Length(2.0, css::Unit::Vw)));
+}
+
+// Test font sizes in viewport units, which the function compute_values can't resolve
+#[test]
+fn test_compute_values_viewport_font_size() {
+  let root: dom::Node = HTMLParser::parse("<div><p>a</p></div>".to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "div { font-size: 20px; } p { font-size: 5vw; padding: 1em; }".to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let paragraph: &StyledNode = &style.children()[1].children()[0].children()[0];
+
+  // Assert that a font size in viewport units is the font size of the parent, as documented,
+  // and that em lengths are relative to it
+  assert_eq!(paragraph.font_size(), 20.0);
+  assert_eq!(paragraph.value("padding-top"), Some(css::Value::Length(20.0, css::Unit::Px)));
+}
+
+// Test the resolution of unitless zero lengths by the function compute_values
+#[test]
+fn test_compute_values_unitless_zero() {
+  let root: dom::Node = HTMLParser::parse("<div><p>a</p><h1>b</h1></div>".to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "div { font-size: 0; margin: 0 2px; line-height: 0; }      p { font-size: 20px; border: 0 solid; height: 0; }      h1 { font: 0/1 serif; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let div: &StyledNode = &style.children()[1].children()[0];
+  let paragraph: &StyledNode = &div.children()[0];
+  let heading: &StyledNode = &div.children()[1];
+  let px = |length: f32| -> Option<css::Value> { Some(css::Value::Length(length, css::Unit::Px)) };
+
+  // Assert that a unitless 0 font size is a zero length, not the font size of the parent
+  assert_eq!(div.font_size(), 0.0);
+  assert_eq!(div.value("font-size"), px(0.0));
+  assert_eq!(heading.font_size(), 0.0);
+  // Assert that the other length properties get zero lengths too
+  assert_eq!(div.value("margin-top"), px(0.0));
+  assert_eq!(div.value("margin-left"), px(2.0));
+  assert_eq!(paragraph.value("border-top-width"), px(0.0));
+  assert_eq!(paragraph.value("height"), px(0.0));
+  // Assert that a number that is not a length stays a number
+  assert_eq!(div.value("line-height"), Some(css::Value::Number(0.0)));
+}
+
+// Test the resolution of 'currentColor' by the function compute_values
+#[test]
+fn test_compute_values_current_color() {
+  let root: dom::Node =
+    HTMLParser::parse("<div><p><span>a</span></p></div><h1>b</h1>".to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "div { color: red; background: currentColor; } \
+     p { color: currentColor; border-color: currentColor; } \
+     span { color: rgb(0 0 255 / 50%); background: currentColor; } \
+     h1 { background: currentColor; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let body: &StyledNode = &style.children()[1];
+  let div: &StyledNode = &body.children()[0];
+  let paragraph: &StyledNode = &div.children()[0];
+  let span: &StyledNode = &paragraph.children()[0];
+  let heading: &StyledNode = &body.children()[1];
+  let red: Option<css::Value> = Some(css::Value::ColorValue(css::Color::new(255, 0, 0, 255)));
+
+  // Assert that 'currentColor' is the color of the element
+  assert_eq!(div.value("background-color"), red);
+  assert_eq!(
+    span.value("background-color"),
+    Some(css::Value::ColorValue(css::Color::new(0, 0, 255, 128)))
+  );
+  // Assert that 'currentColor' in 'color' is the inherited color, which other values follow
+  assert_eq!(paragraph.value("color"), red);
+  assert_eq!(paragraph.value("border-top-color"), red);
+  // Assert that without a color, 'currentColor' is the initial color
+  assert_eq!(
+    heading.value("background-color"),
+    Some(css::Value::ColorValue(css::Color::new(0, 0, 0, 255)))
+  );
+}
+
+// Test the border widths computed by the function compute_values
+#[test]
+fn test_compute_values_border_width() {
+  let root: dom::Node =
+    HTMLParser::parse("<div></div><p></p><h1></h1>".to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "div { border: thick solid red; border-left-width: thin; } \
+     p { border: 2px; } \
+     h1 { border-width: 4px; border-top-style: hidden; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let body: &StyledNode = &style.children()[1];
+  let div: &StyledNode = &body.children()[0];
+  let paragraph: &StyledNode = &body.children()[1];
+  let heading: &StyledNode = &body.children()[2];
+  let px = |length: f32| -> Option<css::Value> { Some(css::Value::Length(length, css::Unit::Px)) };
+
+  // Assert that the border width keywords are resolved to px
+  assert_eq!(div.value("border-top-width"), px(5.0));
+  assert_eq!(div.value("border-left-width"), px(1.0));
+  // Assert that a border without a style has no width
+  assert_eq!(paragraph.value("border-right-width"), px(0.0));
+  // Assert that only the sides with the style 'none' or 'hidden' lose their width
+  assert_eq!(heading.value("border-top-width"), px(0.0));
+  assert_eq!(heading.value("border-bottom-width"), px(4.0));
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use crate::css;
+use crate::dom;
+use super::tree::PropertyMap;
+
+// The CSS counters in scope while the style tree is built in document order, for 'counter()'
+// in generated content
+// https://drafts.csswg.org/css-lists/#auto-numbering
+#[derive(Default)]
+pub(super) struct Counters {
+  values: HashMap<String, i32>,
+  // For each element whose children are being styled, the counters its children shadowed with
+  // 'counter-reset' and their values before, restored when the element ends: the scope of a
+  // counter is the element that resets it, its following siblings, and their descendants
+  scopes: Vec<Vec<(String, Option<i32>)>>,
+}
+
+impl Counters {
+  // The value of a counter, or 0 if it doesn't exist
+  pub(super) fn value(&self, name: &str) -> i32 {
+    self.values.get(name).copied().unwrap_or(0)
+  }
+
+  // Apply the 'counter-reset' and then the 'counter-increment' of an element or
+  // pseudo-element. Incrementing a counter that doesn't exist creates it. Like the parsed values,
+  // counters saturate at the limits of an i32 rather than overflow
+  pub(super) fn update(&mut self, values: &PropertyMap) {
+    if let Some(css::Value::Counters(counters)) = values.get("counter-reset") {
+      for (name, value) in counters {
+        let previous: Option<i32> = self.values.insert(name.clone(), *value);
+        if let Some(scope) = self.scopes.last_mut() {
+          scope.push((name.clone(), previous));
+        }
+      }
+    }
+    if let Some(css::Value::Counters(counters)) = values.get("counter-increment") {
+      for (name, value) in counters {
+        let counter: &mut i32 = self.values.entry(name.clone()).or_insert(0);
+        *counter = counter.saturating_add(*value);
+      }
+    }
+  }
+
+  // Start styling the children of an element
+  pub(super) fn enter(&mut self) {
+    self.scopes.push(vec![]);
+  }
+
+  // Finish styling the children of an element, which ends the counters they reset
+  pub(super) fn leave(&mut self) {
+    let scope: Vec<(String, Option<i32>)> = self.scopes.pop().unwrap_or_default();
+    for (name, previous) in scope.into_iter().rev() {
+      match previous {
+        Some(value) => self.values.insert(name, value),
+        None => self.values.remove(&name),
+      };
+    }
+  }
+}
+
+// Return the text generated by the 'content' property of a pseudo-element of an element, or
+// None if it generates no box ('none', 'normal', or no value)
+pub(super) fn generated_text(
+  element: &dom::ElementData,
+  values: &PropertyMap,
+  counters: &Counters,
+) -> Option<String> {
+  let Some(css::Value::Content(items)) = values.get("content") else {
+    return None;
+  };
+  let text: String = items
+    .iter()
+    .map(|item: &css::ContentItem| match item {
+      css::ContentItem::String(string) => string.clone(),
+      css::ContentItem::Attr(name) => element.attributes().get(name).cloned().unwrap_or_default(),
+      css::ContentItem::Counter(name) => counters.value(name).to_string(),
+    })
+    .collect();
+  Some(text)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use crate::css;
+use crate::dom;
+use crate::hashmap;
+use crate::style::PropertyMap;
+use super::{Counters, generated_text};
+
+// Test the methods of the Counters struct implementation
+#[test]
+fn test_counters() {
+  let counters = |property: &str, counters: Vec<(&str, i32)>| -> PropertyMap {
+    let counters: Vec<(String, i32)> = counters
+      .into_iter()
+      .map(|(name, value): (&str, i32)| (name.to_string(), value))
+      .collect();
+    hashmap![property.to_string() => css::Value::Counters(counters)]
+  };
+  let mut scope: Counters = Counters::default();
+
+  // Assert that a counter that doesn't exist is 0, and that incrementing it creates it
+  assert_eq!(scope.value("item"), 0);
+  scope.update(&counters("counter-increment", vec![("item", 2)]));
+  assert_eq!(scope.value("item"), 2);
+  // Assert that a counter reset by a child shadows the outer one until its parent ends
+  scope.enter();
+  scope.update(&counters("counter-reset", vec![("item", 10)]));
+  scope.update(&counters("counter-increment", vec![("item", 1), ("other", 1)]));
+  assert_eq!(scope.value("item"), 11);
+  scope.leave();
+  assert_eq!(scope.value("item"), 2);
+  // Assert that the counters created by an increment don't end with the parent
+  assert_eq!(scope.value("other"), 1);
+  // Assert that the reset of a counter applies before its increment
+  let mut values: PropertyMap = counters("counter-reset", vec![("item", 5)]);
+  values.extend(counters("counter-increment", vec![("item", -1)]));
+  scope.update(&values);
+  assert_eq!(scope.value("item"), 4);
+  // Assert that extreme increments saturate instead of overflowing
+  scope.update(&counters("counter-increment", vec![("item", i32::MAX)]));
+  scope.update(&counters("counter-increment", vec![("item", i32::MAX)]));
+  assert_eq!(scope.value("item"), i32::MAX);
+  scope.update(&counters("counter-reset", vec![("item", i32::MIN)]));
+  scope.update(&counters("counter-increment", vec![("item", -1)]));
+  assert_eq!(scope.value("item"), i32::MIN);
+}
+
+// Test the function generated_text
+#[test]
+fn test_generated_text() {
+  let node: dom::Node = dom::Node::element(
+    "a".to_string(),
+    hashmap!["href".to_string() => "/home".to_string()],
+    vec![],
+  );
+  let dom::NodeType::Element(element) = node.node_type() else {
+    panic!("Expected an element");
+  };
+  let mut counters: Counters = Counters::default();
+  counters.update(&hashmap![
+    "counter-reset".to_string() => css::Value::Counters(vec![("item".to_string(), 3)])
+  ]);
+  let content = |items: Vec<css::ContentItem>| -> PropertyMap {
+    hashmap!["content".to_string() => css::Value::Content(items)]
+  };
+
+  // Assert that strings, attributes and counters are concatenated
+  assert_eq!(
+    generated_text(
+      element,
+      &content(vec![
+        css::ContentItem::Counter("item".to_string()),
+        css::ContentItem::String(". (".to_string()),
+        css::ContentItem::Attr("href".to_string()),
+        css::ContentItem::Attr("title".to_string()),
+        css::ContentItem::String(")".to_string()),
+      ]),
+      &counters
+    ),
+    Some("3. (/home)".to_string())
+  );
+  // Assert that 'none', 'normal' and no value generate no text
+  let none: PropertyMap = hashmap!["content".to_string() => css::Value::Keyword("none".to_string())];
+  assert_eq!(generated_text(element, &none, &counters), None);
+  assert_eq!(generated_text(element, &hashmap![], &counters), None);
+}
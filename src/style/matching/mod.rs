@@ -2,20 +2,90 @@ use std::collections::HashSet;
 
 use crate::css;
 use crate::dom;
+use super::state::{ElementState, StateMap};
 
 // A single CSS rule and the specificity of its most specific matching selector
 pub(super) type MatchedRule<'a> = (css::Specificity, &'a css::Rule);
 
+// HTML attributes whose values are matched by attribute selectors ignoring ASCII case, unless the
+// selector has the 's' flag
+// https://html.spec.whatwg.org/multipage/semantics-other.html#case-sensitivity-of-selectors
+const CASE_INSENSITIVE_ATTRIBUTES: [&str; 46] = [
+  "accept", "accept-charset", "align", "alink", "axis", "bgcolor", "charset", "checked", "clear",
+  "codetype", "color", "compact", "declare", "defer", "dir", "direction", "disabled", "enctype",
+  "face", "frame", "hreflang", "http-equiv", "lang", "language", "link", "media", "method",
+  "multiple", "nohref", "noresize", "noshade", "nowrap", "readonly", "rel", "rev", "rules",
+  "scope", "scrolling", "selected", "shape", "target", "text", "type", "valign", "valuetype",
+  "vlink",
+];
+
+// Does an element have an attribute that passes an attribute selector? Attribute names ignore
+// case for HTML elements only, like type selectors
+fn matches_attribute_selector(
+  element: &dom::ElementData,
+  selector: &css::AttributeSelector,
+) -> bool {
+  let is_html: bool = element.namespace() == dom::Namespace::Html;
+  let name: String = if is_html {
+    selector.name().to_ascii_lowercase()
+  } else {
+    selector.name().to_string()
+  };
+  let Some(value) = element.attributes().get(&name) else {
+    return false;
+  };
+  let ignore_case: bool = match selector.case() {
+    css::AttributeCase::Insensitive => true,
+    css::AttributeCase::Sensitive => false,
+    css::AttributeCase::Default => is_html && CASE_INSENSITIVE_ATTRIBUTES.contains(&&*name),
+  };
+  let (value, expected): (String, String) = if ignore_case {
+    (value.to_ascii_lowercase(), selector.value().to_ascii_lowercase())
+  } else {
+    (value.clone(), selector.value().to_string())
+  };
+  match selector.operator() {
+    css::AttributeOperator::Exists => true,
+    css::AttributeOperator::Equals => value == expected,
+    // A value with whitespace can't be one of the words of the attribute
+    css::AttributeOperator::Includes => {
+      !expected.is_empty()
+        && !expected.contains(char::is_whitespace)
+        && value.split_whitespace().any(|word: &str| word == expected)
+    }
+    css::AttributeOperator::DashMatch => {
+      value == expected || value.starts_with(&format!("{}-", expected))
+    }
+    // An empty value matches nothing, rather than every element with the attribute
+    css::AttributeOperator::Prefix => !expected.is_empty() && value.starts_with(&expected),
+    css::AttributeOperator::Suffix => !expected.is_empty() && value.ends_with(&expected),
+    css::AttributeOperator::Substring => !expected.is_empty() && value.contains(&expected),
+  }
+}
+
+// Check the parts of a simple selector that only depend on the element itself. Pseudo-classes,
+// which depend on its place in the tree, are checked by matches_compound, and pseudo-elements by
+// match_rule
 pub(super) fn matches_simple_selector(element: &dom::ElementData, selector: &css::SimpleSelector) -> bool {
-  // Check type selector
+  // Check namespace: a selector with an empty namespace matches no element, as all of them are
+  // in the HTML, SVG or MathML namespace
   if selector
-    .tag_name()
+    .namespace()
     .iter()
-    .any(|name: &String| *element.tag_name() != *name)
+    .any(|namespace: &String| element.namespace().url() != namespace)
   {
     return false;
   }
 
+  // Check type selector. Names of HTML elements ignore case, names of SVG and MathML elements
+  // don't (e.g. 'linearGradient')
+  if selector.tag_name().iter().any(|name: &String| match element.namespace() {
+    dom::Namespace::Html => !element.tag_name().eq_ignore_ascii_case(name),
+    _ => element.tag_name() != name,
+  }) {
+    return false;
+  }
+
   // Check ID selector
   if selector
     .id()
@@ -35,39 +105,397 @@ pub(super) fn matches_simple_selector(element: &dom::ElementData, selector: &css
     return false;
   }
 
+  // Check attribute selectors
+  if selector
+    .attributes()
+    .iter()
+    .any(|attribute: &css::AttributeSelector| !matches_attribute_selector(element, attribute))
+  {
+    return false;
+  }
+
   // We didn't find any non-matching selector components
-  return true;
+  true
+}
+
+// Return the data of a node if it is an element
+fn element_of(node: &dom::Node) -> Option<&dom::ElementData> {
+  match node.node_type() {
+    dom::NodeType::Element(element) => Some(element),
+    _ => None,
+  }
+}
+
+// The context a selector is matched in, beyond the element and its ancestors
+#[derive(Copy, Clone, Default)]
+struct MatchingContext<'a> {
+  // The element the relative selectors of ':has()' are anchored to
+  anchor: Option<&'a dom::Node>,
+  // The interaction states of the elements, for the dynamic pseudo-classes. Without them, no
+  // element is hovered, active, focused or visited
+  states: Option<&'a StateMap>,
+}
+
+// Form elements that can be disabled, and so match ':disabled' or ':enabled'
+// https://html.spec.whatwg.org/multipage/semantics-other.html#selector-enabled
+const DISABLEABLE_ELEMENTS: [&str; 7] =
+  ["button", "input", "select", "textarea", "optgroup", "option", "fieldset"];
+
+// Is an element in an interaction state in the state map of the context?
+fn has_state(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  state: ElementState,
+  context: MatchingContext,
+) -> bool {
+  context
+    .states
+    .is_some_and(|states: &StateMap| states.has_state(node, ancestors, state))
 }
 
-// Selector matching:
-fn matches(element: &dom::ElementData, selector: &css::Selector) -> bool {
+// Is an element, or one of its descendants, in an interaction state? An element is hovered or
+// active while one of its descendants is
+fn has_state_in_subtree<'a>(
+  node: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+  state: ElementState,
+  context: MatchingContext<'a>,
+) -> bool {
+  if context.states.is_none_or(|states: &StateMap| states.is_empty()) {
+    return false;
+  }
+  let test = |candidate: &'a dom::Node, candidate_ancestors: &[&'a dom::Node]| -> bool {
+    has_state(candidate, candidate_ancestors, state, context)
+  };
+  any_in_subtree(node, &mut ancestors.to_vec(), &test)
+}
+
+// Return the siblings of a node, which are the children of its parent (the last of its
+// ancestors), and the index of the node among them. A node without a parent is its only sibling
+fn siblings<'a>(node: &'a dom::Node, ancestors: &[&'a dom::Node]) -> (&'a [dom::Node], usize) {
+  let siblings: &[dom::Node] = match ancestors.last() {
+    Some(parent) => parent.children(),
+    None => &[],
+  };
+  match siblings.iter().position(|sibling: &dom::Node| std::ptr::eq(sibling, node)) {
+    Some(index) => (siblings, index),
+    None => (std::slice::from_ref(node), 0),
+  }
+}
+
+// Return the element siblings that come before a node, nearest first
+fn previous_siblings<'a>(
+  node: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+) -> impl Iterator<Item = &'a dom::Node> {
+  let (siblings, index): (&[dom::Node], usize) = siblings(node, ancestors);
+  siblings[..index]
+    .iter()
+    .rev()
+    .filter(|sibling: &&dom::Node| element_of(sibling).is_some())
+}
+
+// Return the element siblings that come after a node, nearest first
+fn next_siblings<'a>(
+  node: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+) -> impl Iterator<Item = &'a dom::Node> {
+  let (siblings, index): (&[dom::Node], usize) = siblings(node, ancestors);
+  siblings[index + 1..]
+    .iter()
+    .filter(|sibling: &&dom::Node| element_of(sibling).is_some())
+}
+
+// Return the combinator a relative selector starts with, right after its anchor, e.g. '>' for
+// '> img + p'. Other selectors have none
+fn leading_combinator(selector: &css::Selector) -> Option<css::Combinator> {
   match *selector {
-    css::Selector::Simple(ref simple_selector) => matches_simple_selector(element, simple_selector),
+    css::Selector::Complex(ref selector, combinator, _) => match **selector {
+      css::Selector::Anchor => Some(combinator),
+      _ => leading_combinator(selector),
+    },
+    _ => None,
   }
 }
 
-// If 'rule' matches 'element', return a 'MatchedRule'. Otherwise return 'None'
-pub(super) fn match_rule<'a>(
+// Does 'test' pass for a node or one of its descendants? It is given each node and its
+// ancestors, which start as 'ancestors'
+fn any_in_subtree<'a>(
+  node: &'a dom::Node,
+  ancestors: &mut Vec<&'a dom::Node>,
+  test: &dyn Fn(&'a dom::Node, &[&'a dom::Node]) -> bool,
+) -> bool {
+  if test(node, ancestors) {
+    return true;
+  }
+  ancestors.push(node);
+  let found: bool = node
+    .children()
+    .iter()
+    .any(|child: &dom::Node| any_in_subtree(child, ancestors, test));
+  ancestors.pop();
+  found
+}
+
+// Does an element match a pseudo-class? 'element' is the data of 'node'
+fn matches_pseudo_class<'a>(
+  node: &'a dom::Node,
   element: &dom::ElementData,
+  ancestors: &[&'a dom::Node],
+  pseudo_class: &css::PseudoClass,
+  context: MatchingContext<'a>,
+) -> bool {
+  let same_type = |sibling: &dom::Node| -> bool {
+    element_of(sibling).is_some_and(|sibling: &dom::ElementData| {
+      sibling.namespace() == element.namespace() && sibling.tag_name() == element.tag_name()
+    })
+  };
+  // Siblings share the ancestors of the element. An empty list of selectors matches everything
+  let matches_any = |node: &'a dom::Node, selectors: &[css::Selector]| -> bool {
+    selectors.is_empty()
+      || selectors
+        .iter()
+        .any(|selector: &css::Selector| matches_in_context(node, ancestors, selector, context))
+  };
+  let is_html: bool = element.namespace() == dom::Namespace::Html;
+  let is_disableable: bool = is_html && DISABLEABLE_ELEMENTS.contains(&element.tag_name());
+  // Only links with an address are links, visited or not
+  let is_link: bool = is_html
+    && ["a", "area", "link"].contains(&element.tag_name())
+    && element.attributes().contains_key("href");
+  match pseudo_class {
+    css::PseudoClass::Hover => has_state_in_subtree(node, ancestors, ElementState::Hover, context),
+    css::PseudoClass::Active => {
+      has_state_in_subtree(node, ancestors, ElementState::Active, context)
+    }
+    css::PseudoClass::Focus => has_state(node, ancestors, ElementState::Focus, context),
+    css::PseudoClass::FocusWithin => {
+      has_state_in_subtree(node, ancestors, ElementState::Focus, context)
+    }
+    // Checkboxes and radio buttons are checked by their 'checked' attribute, options by their
+    // 'selected' attribute, unless the state map checks them
+    css::PseudoClass::Checked => {
+      has_state(node, ancestors, ElementState::Checked, context)
+        || is_html && match element.tag_name() {
+          "input" => {
+            element.attributes().contains_key("checked")
+              && element.attributes().get("type").is_some_and(|input_type: &String| {
+                input_type.eq_ignore_ascii_case("checkbox")
+                  || input_type.eq_ignore_ascii_case("radio")
+              })
+          }
+          "option" => element.attributes().contains_key("selected"),
+          _ => false,
+        }
+    }
+    css::PseudoClass::Disabled => {
+      is_disableable
+        && (element.attributes().contains_key("disabled")
+          || has_state(node, ancestors, ElementState::Disabled, context))
+    }
+    css::PseudoClass::Enabled => {
+      is_disableable
+        && !element.attributes().contains_key("disabled")
+        && !has_state(node, ancestors, ElementState::Disabled, context)
+    }
+    css::PseudoClass::Link => {
+      is_link && !has_state(node, ancestors, ElementState::Visited, context)
+    }
+    css::PseudoClass::Visited => {
+      is_link && has_state(node, ancestors, ElementState::Visited, context)
+    }
+    css::PseudoClass::Root => ancestors.is_empty(),
+    // Comments don't count as content, but whitespace does
+    css::PseudoClass::Empty => node.children().iter().all(|child: &dom::Node| {
+      match child.node_type() {
+        dom::NodeType::Text(text) => text.is_empty(),
+        dom::NodeType::Comment(_) => true,
+        _ => false,
+      }
+    }),
+    css::PseudoClass::FirstChild => previous_siblings(node, ancestors).next().is_none(),
+    css::PseudoClass::LastChild => next_siblings(node, ancestors).next().is_none(),
+    css::PseudoClass::OnlyChild => {
+      previous_siblings(node, ancestors).next().is_none()
+        && next_siblings(node, ancestors).next().is_none()
+    }
+    css::PseudoClass::FirstOfType => !previous_siblings(node, ancestors).any(same_type),
+    css::PseudoClass::LastOfType => !next_siblings(node, ancestors).any(same_type),
+    css::PseudoClass::OnlyOfType => {
+      !previous_siblings(node, ancestors).any(same_type)
+        && !next_siblings(node, ancestors).any(same_type)
+    }
+    // The position of the element among the siblings that match the 'of S' selectors
+    css::PseudoClass::NthChild(nth, selectors) => {
+      matches_any(node, selectors)
+        && nth.matches(
+          1 + previous_siblings(node, ancestors)
+            .filter(|sibling: &&dom::Node| matches_any(sibling, selectors))
+            .count(),
+        )
+    }
+    css::PseudoClass::NthLastChild(nth, selectors) => {
+      matches_any(node, selectors)
+        && nth.matches(
+          1 + next_siblings(node, ancestors)
+            .filter(|sibling: &&dom::Node| matches_any(sibling, selectors))
+            .count(),
+        )
+    }
+    css::PseudoClass::NthOfType(nth) => nth.matches(
+      1 + previous_siblings(node, ancestors)
+        .filter(|sibling: &&dom::Node| same_type(sibling))
+        .count(),
+    ),
+    css::PseudoClass::NthLastOfType(nth) => nth.matches(
+      1 + next_siblings(node, ancestors)
+        .filter(|sibling: &&dom::Node| same_type(sibling))
+        .count(),
+    ),
+    css::PseudoClass::Not(selectors) => !matches_any(node, selectors),
+    css::PseudoClass::Is(selectors) | css::PseudoClass::Where(selectors) => {
+      matches_any(node, selectors)
+    }
+    // Relative selectors are anchored at the element. The combinator they start with decides
+    // where they can match: '>' and ' ' only in the descendants of the element, '+' in its next
+    // sibling and '~' in its following siblings, with their descendants
+    css::PseudoClass::Has(selectors) => {
+      let context: MatchingContext = MatchingContext {
+        anchor: Some(node),
+        ..context
+      };
+      selectors.iter().any(|selector: &css::Selector| {
+        let test = |candidate: &'a dom::Node, candidate_ancestors: &[&'a dom::Node]| -> bool {
+          matches_in_context(candidate, candidate_ancestors, selector, context)
+        };
+        match leading_combinator(selector) {
+          Some(css::Combinator::Descendant | css::Combinator::Child) => {
+            let mut child_ancestors: Vec<&dom::Node> = ancestors.to_vec();
+            child_ancestors.push(node);
+            node
+              .children()
+              .iter()
+              .any(|child: &dom::Node| any_in_subtree(child, &mut child_ancestors, &test))
+          }
+          Some(css::Combinator::NextSibling) => next_siblings(node, ancestors)
+            .next()
+            .is_some_and(|sibling: &dom::Node| {
+              any_in_subtree(sibling, &mut ancestors.to_vec(), &test)
+            }),
+          Some(css::Combinator::SubsequentSibling) => next_siblings(node, ancestors)
+            .any(|sibling: &dom::Node| any_in_subtree(sibling, &mut ancestors.to_vec(), &test)),
+          None => false,
+        }
+      })
+    }
+  }
+}
+
+// Does an element match a simple selector, including its pseudo-classes?
+fn matches_compound<'a>(
+  node: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+  selector: &css::SimpleSelector,
+  context: MatchingContext<'a>,
+) -> bool {
+  let Some(element) = element_of(node) else {
+    return false;
+  };
+  matches_simple_selector(element, selector)
+    && selector.pseudo_classes().iter().all(|pseudo_class: &css::PseudoClass| {
+      matches_pseudo_class(node, element, ancestors, pseudo_class, context)
+    })
+}
+
+// Match a selector in a context. Combinators are matched from right to left: the last simple
+// selector is matched against the element, then the rest of the selector against its ancestors
+// or previous siblings
+fn matches_in_context<'a>(
+  node: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+  selector: &css::Selector,
+  context: MatchingContext<'a>,
+) -> bool {
+  match *selector {
+    css::Selector::Simple(ref simple_selector) => {
+      matches_compound(node, ancestors, simple_selector, context)
+    }
+    css::Selector::Complex(ref selector, combinator, ref simple_selector) => {
+      if !matches_compound(node, ancestors, simple_selector, context) {
+        return false;
+      }
+      match combinator {
+        css::Combinator::Descendant => (0..ancestors.len()).rev().any(|index: usize| {
+          matches_in_context(ancestors[index], &ancestors[..index], selector, context)
+        }),
+        css::Combinator::Child => match ancestors.split_last() {
+          Some((parent, rest)) => matches_in_context(parent, rest, selector, context),
+          None => false,
+        },
+        css::Combinator::NextSibling => previous_siblings(node, ancestors)
+          .next()
+          .is_some_and(|sibling: &dom::Node| {
+            matches_in_context(sibling, ancestors, selector, context)
+          }),
+        css::Combinator::SubsequentSibling => previous_siblings(node, ancestors)
+          .any(|sibling: &dom::Node| matches_in_context(sibling, ancestors, selector, context)),
+      }
+    }
+    css::Selector::Anchor => {
+      context.anchor.is_some_and(|anchor: &dom::Node| std::ptr::eq(anchor, node))
+    }
+  }
+}
+
+// Selector matching, also used by the DOM query methods. 'ancestors' are the ancestors of the
+// node, from the root to its parent, which selectors need for combinators and pseudo-classes.
+// A selector with a pseudo-element matches no node
+pub(crate) fn matches(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  selector: &css::Selector,
+) -> bool {
+  selector.pseudo_element().is_none()
+    && matches_in_context(node, ancestors, selector, MatchingContext::default())
+}
+
+// If 'rule' matches the element 'node', or its pseudo-element 'pseudo_element', with the
+// elements in the given states, return a 'MatchedRule'. Otherwise return 'None'
+pub(super) fn match_rule<'a>(
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
   rule: &'a css::Rule,
+  states: &StateMap,
+  pseudo_element: Option<css::PseudoElement>,
 ) -> Option<MatchedRule<'a>> {
+  let context: MatchingContext = MatchingContext {
+    anchor: None,
+    states: Some(states),
+  };
   // Find the first (highest-specificity) matching selector
   rule
     .selectors()
     .iter()
-    .find(|selector: &&css::Selector| matches(element, *selector))
+    .find(|selector: &&css::Selector| {
+      selector.pseudo_element() == pseudo_element
+        && matches_in_context(node, ancestors, selector, context)
+    })
     .map(|selector: &css::Selector| (selector.specificity(), rule))
 }
 
-// Find all CSS rules that match the given element
+// Find all CSS rules that match the given element, or one of its pseudo-elements, in stylesheet
+// order
 pub(super) fn matching_rules<'a>(
-  element: &dom::ElementData,
-  stylesheet: &'a css::Stylesheet,
+  node: &dom::Node,
+  ancestors: &[&dom::Node],
+  stylesheets: &'a [css::Stylesheet],
+  states: &StateMap,
+  pseudo_element: Option<css::PseudoElement>,
 ) -> Vec<MatchedRule<'a>> {
-  stylesheet
-    .rules()
+  stylesheets
     .iter()
-    .filter_map(|rule: &css::Rule| match_rule(element, rule))
+    .flat_map(|stylesheet: &css::Stylesheet| stylesheet.rules().iter())
+    .filter_map(|rule: &css::Rule| match_rule(node, ancestors, rule, states, pseudo_element))
     .collect()
 }
 
@@ -1,7 +1,10 @@
 use crate::css;
 use crate::dom;
 use crate::hashmap;
-use super::{match_rule, matching_rules, matches_simple_selector};
+use crate::parser::css::CSSParser;
+use crate::parser::html::HTMLParser;
+use crate::style::{ElementState, StateMap};
+use super::{match_rule, matches, matching_rules, matches_simple_selector};
 
 // Test the function matches_simple_selector
 #[test]
@@ -30,10 +33,252 @@ fn test_matches_simple_selector() {
     vec!["different-class".to_string()],
   );
 
-  assert_eq!(matches_simple_selector(&element, &simple_selector_1), true);
-  assert_eq!(matches_simple_selector(&element, &simple_selector_2), false);
-  assert_eq!(matches_simple_selector(&element, &simple_selector_3), false);
-  assert_eq!(matches_simple_selector(&element, &simple_selector_4), false);
+  assert!(matches_simple_selector(&element, &simple_selector_1));
+  assert!(!matches_simple_selector(&element, &simple_selector_2));
+  assert!(!matches_simple_selector(&element, &simple_selector_3));
+  assert!(!matches_simple_selector(&element, &simple_selector_4));
+}
+
+// Test the function matches_simple_selector with SVG elements and namespaced selectors
+#[test]
+fn test_matches_namespaced_selector() {
+  let html: dom::ElementData = dom::ElementData::new("div".to_string(), hashmap![]);
+  let svg: dom::ElementData = dom::ElementData::new_in_namespace(
+    dom::Namespace::Svg,
+    "linearGradient".to_string(),
+    hashmap![],
+  );
+  let type_selector = |name: &str, namespace: Option<&str>| -> css::SimpleSelector {
+    let mut selector: css::SimpleSelector =
+      css::SimpleSelector::new(Some(name.to_string()), None, vec![]);
+    selector.set_namespace(namespace.map(str::to_string));
+    selector
+  };
+
+  // Assert that type selectors ignore case for HTML elements only
+  assert!(matches_simple_selector(&html, &type_selector("DIV", None)));
+  assert!(matches_simple_selector(&svg, &type_selector("linearGradient", None)));
+  assert!(!matches_simple_selector(&svg, &type_selector("lineargradient", None)));
+  // Assert that a selector's namespace must be the element's namespace
+  let svg_namespace: Option<&str> = Some(dom::Namespace::Svg.url());
+  assert!(matches_simple_selector(&svg, &type_selector("linearGradient", svg_namespace)));
+  assert!(!matches_simple_selector(&html, &type_selector("div", svg_namespace)));
+  assert!(!matches_simple_selector(&html, &type_selector("div", Some(""))));
+}
+
+// Test the function matches_simple_selector with attribute selectors
+#[test]
+fn test_matches_attribute_selector() {
+  let attributes: dom::AttributeMap = hashmap![
+    String::from("type") => String::from("Checkbox"),
+    String::from("href") => String::from("https://example.com/a.png"),
+    String::from("data-state") => String::from("open  active"),
+    String::from("lang") => String::from("en-US"),
+    String::from("title") => String::from("Hello")
+  ];
+  let element: dom::ElementData = dom::ElementData::new("input".to_string(), attributes);
+  let svg: dom::ElementData = dom::ElementData::new_in_namespace(
+    dom::Namespace::Svg,
+    "svg".to_string(),
+    hashmap![String::from("viewBox") => String::from("0 0 10 10")],
+  );
+  let matches_selector = |element: &dom::ElementData, source: &str| -> bool {
+    match CSSParser::parse_selector_list(source.to_string()).unwrap().remove(0) {
+      css::Selector::Simple(simple) => matches_simple_selector(element, &simple),
+      _ => panic!("Expected a simple selector"),
+    }
+  };
+
+  // Assert that each operator compares the value as specified
+  assert!(matches_selector(&element, "[title]"));
+  assert!(!matches_selector(&element, "[alt]"));
+  assert!(matches_selector(&element, "[title=Hello]"));
+  assert!(!matches_selector(&element, "[title=Hell]"));
+  assert!(matches_selector(&element, "[data-state~=active]"));
+  assert!(!matches_selector(&element, "[data-state~='open active']"));
+  assert!(!matches_selector(&element, "[data-state~='']"));
+  assert!(matches_selector(&element, "[lang|=en]"));
+  assert!(!matches_selector(&element, "[lang|=e]"));
+  assert!(matches_selector(&element, "[href^='http']"));
+  assert!(matches_selector(&element, "[href$='.png']"));
+  assert!(matches_selector(&element, "[href*=example]"));
+  assert!(!matches_selector(&element, "[href*='']"));
+  // Assert that values are case-sensitive, except for some HTML attributes and the 'i' flag
+  assert!(!matches_selector(&element, "[title=hello]"));
+  assert!(matches_selector(&element, "[title=hello i]"));
+  assert!(matches_selector(&element, "input[type=checkbox]"));
+  assert!(!matches_selector(&element, "input[type=checkbox s]"));
+  // Assert that attribute names ignore case for HTML elements only
+  assert!(matches_selector(&element, "[TITLE]"));
+  assert!(matches_selector(&svg, "[viewBox]"));
+  assert!(!matches_selector(&svg, "[viewbox]"));
+}
+
+// Test the function matches with selectors that have combinators
+#[test]
+fn test_matches_combinators() {
+  // <ul class='menu'><li id='a'></li><li id='b'><a></a></li><li id='c'></li></ul>
+  let link: dom::Node = dom::Node::element("a".to_string(), hashmap![], vec![]);
+  let item = |id: &str, children: Vec<dom::Node>| -> dom::Node {
+    dom::Node::element("li".to_string(), hashmap![String::from("id") => id.to_string()], children)
+  };
+  let list: dom::Node = dom::Node::element(
+    "ul".to_string(),
+    hashmap![String::from("class") => String::from("menu")],
+    vec![item("a", vec![]), item("b", vec![link]), item("c", vec![])],
+  );
+  let selector = |source: &str| -> css::Selector {
+    CSSParser::parse_selector_list(source.to_string()).unwrap().remove(0)
+  };
+  let matches_selector = |node: &dom::Node, ancestors: &[&dom::Node], source: &str| -> bool {
+    matches(node, ancestors, &selector(source))
+  };
+  let items: &Vec<dom::Node> = list.children();
+  let link: &dom::Node = &items[1].children()[0];
+
+  // Assert that descendant and child combinators match the ancestors of the element
+  assert!(matches_selector(link, &[&list, &items[1]], ".menu a"));
+  assert!(matches_selector(link, &[&list, &items[1]], "ul > #b > a"));
+  assert!(!matches_selector(link, &[&list, &items[1]], ".menu > a"));
+  assert!(!matches_selector(link, &[&items[1]], ".menu a"));
+  // Assert that the descendant combinator tries every ancestor, not only the nearest match
+  assert!(matches_selector(link, &[&list, &items[1]], "ul li a"));
+  assert!(!matches_selector(link, &[&list, &items[1]], "li ul a"));
+  // Assert that sibling combinators match the element siblings before the element
+  assert!(matches_selector(&items[2], &[&list], "#b + li"));
+  assert!(!matches_selector(&items[2], &[&list], "#a + li"));
+  assert!(matches_selector(&items[2], &[&list], "#a ~ li"));
+  assert!(!matches_selector(&items[0], &[&list], "li ~ #a"));
+  assert!(matches_selector(&items[2], &[&list], ".menu > #a ~ #c"));
+  // Assert that an element without ancestors doesn't match combinators
+  assert!(!matches_selector(&items[2], &[], "#b + li"));
+  // Assert that siblings are found from the element itself, not from an equal copy of it
+  assert!(!matches(&items[2].clone(), &[&list], &selector("#b + li")));
+}
+
+// Test the function matches with pseudo-classes, through the selector queries of a document
+#[test]
+fn test_matches_pseudo_classes() {
+  let document: dom::Document = HTMLParser::parse_document(
+    "<ul id='list'>\
+      <li id='a' class='x'></li><!-- comment --><li id='b'> </li><li id='c' class='x'><p></p></li>\
+      <li id='d'><img id='e'><span id='f'></span><img id='g'></li><li id='h' class='x'></li>\
+    </ul>"
+      .to_string(),
+  )
+  .unwrap();
+  let ids = |selectors: &str| -> Vec<String> {
+    let nodes: Vec<&dom::Node> = document.query_selector_all(selectors).unwrap();
+    nodes
+      .iter()
+      .filter_map(|node: &&dom::Node| match node.node_type() {
+        dom::NodeType::Element(element) => element.id().cloned(),
+        _ => None,
+      })
+      .collect()
+  };
+
+  // Assert that the tree-structural pseudo-classes count element siblings only
+  assert_eq!(ids("li:first-child, li:last-child"), vec!["a", "h"]);
+  assert_eq!(ids("li:empty"), vec!["a", "h"]);
+  assert_eq!(ids(":root > body > :only-child"), vec!["list"]);
+  assert_eq!(ids("img:first-of-type, img:last-of-type, span:only-of-type"), vec!["e", "f", "g"]);
+  // Assert that An+B counts from 1, from the start or from the end
+  assert_eq!(ids("li:nth-child(odd)"), vec!["a", "c", "h"]);
+  assert_eq!(ids("li:nth-child(-n+2)"), vec!["a", "b"]);
+  assert_eq!(ids("li:nth-last-child(2n)"), vec!["b", "d"]);
+  assert_eq!(ids("#d > :nth-of-type(2), #d > :nth-last-of-type(1)"), vec!["f", "g"]);
+  // Assert that 'of S' filters the siblings that are counted
+  assert_eq!(ids("li:nth-child(2 of .x)"), vec!["c"]);
+  assert_eq!(ids("li:nth-last-child(1 of .x)"), vec!["h"]);
+  // Assert the logical pseudo-classes
+  assert_eq!(ids("li:not(.x, #b)"), vec!["d"]);
+  assert_eq!(ids("li:is(.x, #d):where(:not(:first-child))"), vec!["c", "d", "h"]);
+  assert_eq!(ids(":is(ul #d) > img"), vec!["e", "g"]);
+  // Assert that ':has()' matches relative selectors anchored at the element
+  assert_eq!(ids("li:has(p)"), vec!["c"]);
+  assert_eq!(ids("li:has(> img + span)"), vec!["d"]);
+  assert_eq!(ids("li:has(+ li:empty)"), vec!["d"]);
+  assert_eq!(ids("li:has(~ #d img)"), vec!["a", "b", "c"]);
+  assert_eq!(ids("ul:has(> li > p)"), vec!["list"]);
+  assert_eq!(ids("li:has(li), li:has(> span > img)"), Vec::<String>::new());
+  // Assert that each relative selector searches where its leading combinator allows: '+' the next
+  // sibling only, and ' ' the descendants only
+  assert_eq!(ids("li:has(+ li img)"), vec!["c"]);
+  assert_eq!(ids("li:has(img, ~ li > p)"), vec!["a", "b", "d"]);
+  assert_eq!(ids(":not(:has(*)):not(head)"), vec!["a", "b", "e", "f", "g", "h"]);
+}
+
+// Return the IDs of the elements of a tree that match a selector list in the given states
+fn ids_in_state(root: &dom::Node, selectors: &str, states: &StateMap) -> Vec<String> {
+  fn collect<'a>(
+    node: &'a dom::Node,
+    ancestors: &mut Vec<&'a dom::Node>,
+    rule: &css::Rule,
+    states: &StateMap,
+    ids: &mut Vec<String>,
+  ) {
+    if let dom::NodeType::Element(element) = node.node_type() {
+      if match_rule(node, ancestors, rule, states, None).is_some() {
+        ids.extend(element.id().cloned());
+      }
+    }
+    ancestors.push(node);
+    for child in node.children() {
+      collect(child, ancestors, rule, states, ids);
+    }
+    ancestors.pop();
+  }
+  let rule: css::Rule =
+    css::Rule::new(CSSParser::parse_selector_list(selectors.to_string()).unwrap(), vec![]);
+  let mut ids: Vec<String> = vec![];
+  collect(root, &mut vec![], &rule, states, &mut ids);
+  ids
+}
+
+// Test the matching of the dynamic pseudo-classes, with and without a state map
+#[test]
+fn test_matches_dynamic_pseudo_classes() {
+  let document: dom::Document = HTMLParser::parse_document(
+    "<form id='form'>\
+      <a id='a' href='/'><span id='b'></span></a><a id='c'></a><a id='d' href='/d'></a>\
+      <input id='e' type='checkbox' checked><input id='f' type='text' checked disabled>\
+      <select id='g'><option id='h' selected></option><option id='i'></option></select>\
+    </form>"
+      .to_string(),
+  )
+  .unwrap();
+  let root: &dom::Node = document.root();
+
+  // Assert that without states only the attributes of the elements are matched
+  let states: StateMap = StateMap::new();
+  assert_eq!(
+    ids_in_state(root, ":hover, :active, :focus, :focus-within", &states),
+    Vec::<String>::new()
+  );
+  assert_eq!(ids_in_state(root, ":link", &states), vec!["a", "d"]);
+  assert_eq!(ids_in_state(root, ":visited", &states), Vec::<String>::new());
+  assert_eq!(ids_in_state(root, ":checked", &states), vec!["e", "h"]);
+  assert_eq!(ids_in_state(root, ":disabled", &states), vec!["f"]);
+  assert_eq!(ids_in_state(root, ":enabled", &states), vec!["e", "g", "h", "i"]);
+
+  // Assert that a hovered or focused element puts its ancestors in ':hover' and ':focus-within'
+  let mut states: StateMap = StateMap::new();
+  states.add_selector_state("#b", ElementState::Hover).unwrap();
+  states.add_selector_state("#e", ElementState::Focus).unwrap();
+  assert_eq!(ids_in_state(root, ":hover", &states), vec!["form", "a", "b"]);
+  assert_eq!(ids_in_state(root, ":focus", &states), vec!["e"]);
+  assert_eq!(ids_in_state(root, "form:focus-within, a:focus-within", &states), vec!["form"]);
+
+  // Assert that states given by id and by selector override the attributes
+  let mut states: StateMap = StateMap::new();
+  states.add_id_state("i", ElementState::Checked);
+  states.add_id_state("i", ElementState::Disabled);
+  states.add_selector_state("a", ElementState::Visited).unwrap();
+  assert_eq!(ids_in_state(root, ":checked", &states), vec!["e", "h", "i"]);
+  assert_eq!(ids_in_state(root, ":enabled", &states), vec!["e", "g", "h"]);
+  assert_eq!(ids_in_state(root, ":link", &states), Vec::<String>::new());
+  assert_eq!(ids_in_state(root, ":visited", &states), vec!["a", "d"]);
 }
 
 // Test the function match_rule
@@ -41,7 +286,7 @@ fn test_matches_simple_selector() {
 fn test_match_rule() {
   let tag_name: String = String::from("div");
   let attributes: dom::AttributeMap = hashmap![String::from("id") => String::from("container-id"), String::from("class") => String::from("container-class")];
-  let element: dom::ElementData = dom::ElementData::new(tag_name, attributes);
+  let node: dom::Node = dom::Node::element(tag_name, attributes, vec![]);
   let simple_selector: css::SimpleSelector = css::SimpleSelector::new(
     Some("div".to_string()),
     Some("container-id".to_string()),
@@ -53,7 +298,28 @@ fn test_match_rule() {
   let rule: css::Rule = css::Rule::new(vec![selector], vec![declaration]);
   let specificity: css::Specificity = (1, 1, 1);
 
-  assert_eq!(match_rule(&element, &rule), Some((specificity, &rule)));
+  assert_eq!(match_rule(&node, &[], &rule, &StateMap::new(), None), Some((specificity, &rule)));
+}
+
+// Test the function match_rule with pseudo-elements
+#[test]
+fn test_match_rule_pseudo_element() {
+  let node: dom::Node = dom::Node::element("p".to_string(), hashmap![], vec![]);
+  let rule: css::Rule = css::Rule::new(
+    CSSParser::parse_selector_list("p::before, div".to_string()).unwrap(),
+    vec![],
+  );
+  let states: StateMap = StateMap::new();
+
+  // Assert that a selector with a pseudo-element only applies to that pseudo-element
+  assert_eq!(match_rule(&node, &[], &rule, &states, None), None);
+  assert_eq!(
+    match_rule(&node, &[], &rule, &states, Some(css::PseudoElement::Before)),
+    Some(((0, 0, 2), &rule))
+  );
+  assert_eq!(match_rule(&node, &[], &rule, &states, Some(css::PseudoElement::After)), None);
+  // Assert that DOM queries never match pseudo-elements
+  assert!(!matches(&node, &[], &rule.selectors()[0]));
 }
 
 // Test the function matching_rules
@@ -61,7 +327,7 @@ fn test_match_rule() {
 fn test_matching_rules() {
   let tag_name: String = String::from("div");
   let attributes: dom::AttributeMap = hashmap![String::from("id") => String::from("container-id"), String::from("class") => String::from("container-class")];
-  let element: dom::ElementData = dom::ElementData::new(tag_name, attributes);
+  let node: dom::Node = dom::Node::element(tag_name, attributes, vec![]);
   let simple_selector_1: css::SimpleSelector = css::SimpleSelector::new(
     Some("div".to_string()),
     Some("container-id".to_string()),
@@ -79,10 +345,10 @@ fn test_matching_rules() {
   let rule_2: css::Rule = css::Rule::new(vec![selector_2], vec![declaration_2]);
   let specificity_1: css::Specificity = (1, 1, 1);
   let specificity_2: css::Specificity = (0, 1, 0);
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule_1.clone(), rule_2.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule_1.clone(), rule_2.clone()])];
 
   assert_eq!(
-    matching_rules(&element, &stylesheet),
+    matching_rules(&node, &[], &stylesheets, &StateMap::new(), None),
     vec![(specificity_1, &rule_1), (specificity_2, &rule_2)]
   );
 }
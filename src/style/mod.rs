@@ -1,6 +1,13 @@
 pub mod cascade;
+pub mod computed;
+pub mod content;
 pub mod matching;
+pub mod shorthand;
+pub mod state;
 pub mod tree;
+pub mod whitespace;
 
-pub use cascade::specified_values;
+pub use cascade::{pseudo_element_values, specified_values, specified_values_in_state};
+pub use state::{ElementState, StateMap};
 pub use tree::{Display, PropertyMap, StyledNode, style_tree};
+pub use whitespace::WhiteSpace;
@@ -0,0 +1,237 @@
+use crate::css;
+
+// The sides of a box, in the order the box shorthands give them
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+const BORDER_STYLES: [&str; 10] = [
+  "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
+
+const FONT_STYLES: [&str; 2] = ["italic", "oblique"];
+const FONT_WEIGHTS: [&str; 3] = ["bold", "bolder", "lighter"];
+const FONT_SIZES: [&str; 9] = [
+  "xx-small", "x-small", "small", "medium", "large", "x-large", "xx-large", "smaller", "larger",
+];
+
+// Expand a declaration into the longhand declarations it sets, e.g. 'margin: 0 auto' into
+// 'margin-top: 0', 'margin-right: auto', 'margin-bottom: 0' and 'margin-left: auto'. Other
+// declarations are returned as they are, and an invalid shorthand expands to nothing, as if the
+// parser had dropped it
+// https://drafts.csswg.org/css-cascade/#shorthand
+pub fn expand(declaration: &css::Declaration) -> Vec<css::Declaration> {
+  let name: &str = declaration.name();
+  let value: &css::Value = declaration.value();
+  let longhands: Result<Vec<(String, css::Value)>, String> = match name {
+    "margin" | "padding" => expand_box(value).map(|values: [css::Value; 4]| {
+      sides_longhands(values, |side: &str| format!("{}-{}", name, side))
+    }),
+    "border-width" | "border-style" | "border-color" => {
+      let property: &str = &name["border-".len()..];
+      expand_box(value).map(|values: [css::Value; 4]| {
+        sides_longhands(values, |side: &str| format!("border-{}-{}", side, property))
+      })
+    }
+    "border" => expand_border(value).map(|border: Vec<(&str, css::Value)>| {
+      SIDES
+        .iter()
+        .flat_map(|side: &&str| border_side_longhands(side, &border))
+        .collect()
+    }),
+    "border-top" | "border-right" | "border-bottom" | "border-left" => expand_border(value)
+      .map(|border: Vec<(&str, css::Value)>| {
+        border_side_longhands(&name["border-".len()..], &border)
+      }),
+    "background" => expand_background(value),
+    "font" => expand_font(value),
+    _ => return vec![declaration.clone()],
+  };
+  longhands
+    .unwrap_or_default()
+    .into_iter()
+    .map(|(name, value): (String, css::Value)| css::Declaration::new(name, value))
+    .collect()
+}
+
+// Return the component values of a value, which is a list for a value of several components
+fn components(value: &css::Value) -> &[css::Value] {
+  match value {
+    css::Value::List(values) => values,
+    value => std::slice::from_ref(value),
+  }
+}
+
+fn is_keyword_in(value: &css::Value, keywords: &[&str]) -> bool {
+  match value {
+    css::Value::Keyword(keyword) => keywords.contains(&&*keyword.to_ascii_lowercase()),
+    _ => false,
+  }
+}
+
+// Is a value a length? A zero length can be written without a unit
+fn is_length(value: &css::Value) -> bool {
+  matches!(value, css::Value::Length(..)) || *value == css::Value::Number(0.0)
+}
+
+fn is_color(value: &css::Value) -> bool {
+  matches!(value, css::Value::ColorValue(_)) || is_keyword_in(value, &["currentcolor"])
+}
+
+// Expand the 1 to 4 values of a box shorthand into the values of the top, right, bottom and left
+// sides. A missing side takes the value of the opposite one
+// https://drafts.csswg.org/css-box/#margin-shorthand
+fn expand_box(value: &css::Value) -> Result<[css::Value; 4], String> {
+  let values: &[css::Value] = components(value);
+  if values.iter().any(|value: &css::Value| is_keyword_in(value, &["/", ","])) {
+    return Err(format!("Unexpected separator in box shorthand: '{}'", value));
+  }
+  match values {
+    [all] => Ok([all.clone(), all.clone(), all.clone(), all.clone()]),
+    [vertical, horizontal] => {
+      Ok([vertical.clone(), horizontal.clone(), vertical.clone(), horizontal.clone()])
+    }
+    [top, horizontal, bottom] => {
+      Ok([top.clone(), horizontal.clone(), bottom.clone(), horizontal.clone()])
+    }
+    [top, right, bottom, left] => Ok([top.clone(), right.clone(), bottom.clone(), left.clone()]),
+    _ => Err(format!("Expected 1 to 4 values in box shorthand: '{}'", value)),
+  }
+}
+
+fn sides_longhands<F>(values: [css::Value; 4], longhand_name: F) -> Vec<(String, css::Value)>
+where
+  F: Fn(&str) -> String,
+{
+  SIDES.iter().map(|side: &&str| longhand_name(side)).zip(values).collect()
+}
+
+// Expand the value of 'border' or one of its sides into a width, a style and a color, given in
+// any order. The ones that are missing are set to their initial value
+// https://drafts.csswg.org/css-backgrounds/#border-shorthands
+fn expand_border(value: &css::Value) -> Result<Vec<(&'static str, css::Value)>, String> {
+  let mut width: Option<css::Value> = None;
+  let mut style: Option<css::Value> = None;
+  let mut color: Option<css::Value> = None;
+  for component in components(value) {
+    let longhand: &mut Option<css::Value> = match component {
+      _ if is_length(component) => &mut width,
+      _ if is_keyword_in(component, &["thin", "medium", "thick"]) => &mut width,
+      _ if is_keyword_in(component, &BORDER_STYLES) => &mut style,
+      _ if is_color(component) => &mut color,
+      _ => return Err(format!("Invalid border value: '{}'", component)),
+    };
+    if longhand.replace(component.clone()).is_some() {
+      return Err(format!("Repeated border value: '{}'", value));
+    }
+  }
+  Ok(vec![
+    ("width", width.unwrap_or(css::Value::Keyword("medium".to_string()))),
+    ("style", style.unwrap_or(css::Value::Keyword("none".to_string()))),
+    ("color", color.unwrap_or(css::Value::Keyword("currentcolor".to_string()))),
+  ])
+}
+
+fn border_side_longhands(side: &str, border: &[(&str, css::Value)]) -> Vec<(String, css::Value)> {
+  border
+    .iter()
+    .map(|(property, value): &(&str, css::Value)| {
+      (format!("border-{}-{}", side, property), value.clone())
+    })
+    .collect()
+}
+
+// Expand the value of 'background' into a color and an image, of which only 'none' is
+// supported. The one that is missing is set to its initial value
+// https://drafts.csswg.org/css-backgrounds/#background
+fn expand_background(value: &css::Value) -> Result<Vec<(String, css::Value)>, String> {
+  let mut color: Option<css::Value> = None;
+  let mut image: Option<css::Value> = None;
+  for component in components(value) {
+    let longhand: &mut Option<css::Value> = match component {
+      _ if is_color(component) => &mut color,
+      _ if is_keyword_in(component, &["none"]) => &mut image,
+      _ => return Err(format!("Unsupported background value: '{}'", component)),
+    };
+    if longhand.replace(component.clone()).is_some() {
+      return Err(format!("Repeated background value: '{}'", value));
+    }
+  }
+  Ok(vec![
+    (
+      "background-color".to_string(),
+      color.unwrap_or(css::Value::ColorValue(css::Color::new(0, 0, 0, 0))),
+    ),
+    ("background-image".to_string(), image.unwrap_or(css::Value::Keyword("none".to_string()))),
+  ])
+}
+
+// Expand the value of 'font': an optional style, variant and weight in any order, a size with
+// an optional line height after a '/', and a list of families, e.g. 'italic bold 12px/30px
+// Georgia, serif'. The optional ones that are missing are set to 'normal'
+// https://drafts.csswg.org/css-fonts/#font-prop
+fn expand_font(value: &css::Value) -> Result<Vec<(String, css::Value)>, String> {
+  let normal: css::Value = css::Value::Keyword("normal".to_string());
+  let values: &[css::Value] = components(value);
+  let mut style: Option<css::Value> = None;
+  let mut variant: Option<css::Value> = None;
+  let mut weight: Option<css::Value> = None;
+  let mut index: usize = 0;
+  while let Some(component) = values.get(index) {
+    let longhand: &mut Option<css::Value> = match component {
+      _ if is_keyword_in(component, &FONT_STYLES) => &mut style,
+      _ if is_keyword_in(component, &["small-caps"]) => &mut variant,
+      _ if is_keyword_in(component, &FONT_WEIGHTS) => &mut weight,
+      &css::Value::Number(weight_number) if (1.0..=1000.0).contains(&weight_number) => &mut weight,
+      // 'normal' sets one of the three, which are all 'normal' by default
+      _ if is_keyword_in(component, &["normal"]) => {
+        index += 1;
+        continue;
+      }
+      _ => break,
+    };
+    if longhand.replace(component.clone()).is_some() {
+      return Err(format!("Repeated font value: '{}'", value));
+    }
+    index += 1;
+  }
+
+  let size: css::Value = match values.get(index) {
+    Some(size @ css::Value::Percentage(_)) => size.clone(),
+    Some(size) if is_length(size) || is_keyword_in(size, &FONT_SIZES) => size.clone(),
+    _ => return Err(format!("Expected a font size in: '{}'", value)),
+  };
+  index += 1;
+  let mut line_height: css::Value = normal.clone();
+  if values.get(index).is_some_and(|slash: &css::Value| is_keyword_in(slash, &["/"])) {
+    line_height = match values.get(index + 1) {
+      Some(height @ (css::Value::Number(_) | css::Value::Percentage(_))) => height.clone(),
+      Some(height) if is_length(height) || is_keyword_in(height, &["normal"]) => height.clone(),
+      _ => return Err(format!("Expected a line height in: '{}'", value)),
+    };
+    index += 2;
+  }
+
+  let families: &[css::Value] = &values[index..];
+  let valid_family = |family: &css::Value| -> bool {
+    matches!(family, css::Value::Keyword(_) | css::Value::String(_))
+      && !is_keyword_in(family, &["/"])
+  };
+  if families.is_empty() || !families.iter().all(valid_family) {
+    return Err(format!("Expected font families in: '{}'", value));
+  }
+  let family: css::Value = match families {
+    [family] => family.clone(),
+    families => css::Value::List(families.to_vec()),
+  };
+
+  Ok(vec![
+    ("font-style".to_string(), style.unwrap_or(normal.clone())),
+    ("font-variant".to_string(), variant.unwrap_or(normal.clone())),
+    ("font-weight".to_string(), weight.unwrap_or(normal)),
+    ("font-size".to_string(), size),
+    ("line-height".to_string(), line_height),
+    ("font-family".to_string(), family),
+  ])
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,169 @@
+use crate::css;
+use crate::parser::css::CSSParser;
+use super::expand;
+
+// Parse the declarations of a rule and expand them, returning the longhands as '(name, value)'
+// pairs written as CSS
+fn expand_declarations(declarations: &str) -> Vec<(String, String)> {
+  let stylesheet: css::Stylesheet = CSSParser::parse(format!("p {{ {} }}", declarations)).unwrap();
+  stylesheet.rules()[0]
+    .declarations()
+    .iter()
+    .flat_map(expand)
+    .map(|declaration: css::Declaration| {
+      (declaration.name().to_string(), declaration.value().to_string())
+    })
+    .collect()
+}
+
+fn longhands(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+  pairs
+    .iter()
+    .map(|&(name, value): &(&str, &str)| (name.to_string(), value.to_string()))
+    .collect()
+}
+
+// Test the function expand with the box shorthands
+#[test]
+fn test_expand_box() {
+  // Assert that 1 to 4 values give the top, right, bottom and left sides
+  assert_eq!(
+    expand_declarations("margin: 1px;"),
+    longhands(&[
+      ("margin-top", "1px"),
+      ("margin-right", "1px"),
+      ("margin-bottom", "1px"),
+      ("margin-left", "1px"),
+    ])
+  );
+  assert_eq!(
+    expand_declarations("margin: 0 auto;"),
+    longhands(&[
+      ("margin-top", "0"),
+      ("margin-right", "auto"),
+      ("margin-bottom", "0"),
+      ("margin-left", "auto"),
+    ])
+  );
+  assert_eq!(
+    expand_declarations("padding: 1px 2px 3px;"),
+    longhands(&[
+      ("padding-top", "1px"),
+      ("padding-right", "2px"),
+      ("padding-bottom", "3px"),
+      ("padding-left", "2px"),
+    ])
+  );
+  assert_eq!(
+    expand_declarations("border-width: 1px 2px 3px 4px;"),
+    longhands(&[
+      ("border-top-width", "1px"),
+      ("border-right-width", "2px"),
+      ("border-bottom-width", "3px"),
+      ("border-left-width", "4px"),
+    ])
+  );
+  assert_eq!(
+    expand_declarations("border-style: solid none;")[1],
+    ("border-right-style".to_string(), "none".to_string())
+  );
+  // Assert that box shorthands with more than 4 values or separators expand to nothing
+  assert_eq!(expand_declarations("margin: 1px 2px 3px 4px 5px;"), vec![]);
+  assert_eq!(expand_declarations("padding: 1px / 2px;"), vec![]);
+  // Assert that longhands and other properties are kept as they are
+  assert_eq!(
+    expand_declarations("margin-top: 1px; width: 2px;"),
+    longhands(&[("margin-top", "1px"), ("width", "2px")])
+  );
+}
+
+// Test the function expand with 'border' and its sides
+#[test]
+fn test_expand_border() {
+  let solid: Vec<(String, String)> = expand_declarations("border: solid 2px #ff0000;");
+  // Assert that 'border' sets the width, style and color of the 4 sides, given in any order
+  assert_eq!(solid.len(), 12);
+  assert_eq!(
+    solid[..3],
+    longhands(&[
+      ("border-top-width", "2px"),
+      ("border-top-style", "solid"),
+      ("border-top-color", "rgba(255,0,0,255)"),
+    ])
+  );
+  assert_eq!(solid[9].0, "border-left-width");
+  // Assert that the missing values are set to their initial values
+  assert_eq!(
+    expand_declarations("border-top: 1px;"),
+    longhands(&[
+      ("border-top-width", "1px"),
+      ("border-top-style", "none"),
+      ("border-top-color", "currentcolor"),
+    ])
+  );
+  assert_eq!(expand_declarations("border-left: dashed;")[0].1, "medium");
+  // Assert that repeated or unknown values expand to nothing
+  assert_eq!(expand_declarations("border: 1px 2px;"), vec![]);
+  assert_eq!(expand_declarations("border: solid wavy;"), vec![]);
+}
+
+// Test the function expand with 'background'
+#[test]
+fn test_expand_background() {
+  // Assert that 'background' sets the color, and the image to 'none'
+  assert_eq!(
+    expand_declarations("background: #00ff00;"),
+    longhands(&[
+      ("background-color", "rgba(0,255,0,255)"),
+      ("background-image", "none"),
+    ])
+  );
+  // Assert that the color is transparent by default
+  assert_eq!(
+    expand_declarations("background: none;"),
+    longhands(&[
+      ("background-color", "rgba(0,0,0,0)"),
+      ("background-image", "none"),
+    ])
+  );
+  // Assert that unsupported values expand to nothing
+  assert_eq!(expand_declarations("background: red no-repeat;"), vec![]);
+}
+
+// Test the function expand with 'font'
+#[test]
+fn test_expand_font() {
+  // Assert that 'font' sets the style, variant, weight, size, line height and families
+  assert_eq!(
+    expand_declarations("font: italic bold 12px/30px Georgia, \"Times New Roman\", serif;"),
+    longhands(&[
+      ("font-style", "italic"),
+      ("font-variant", "normal"),
+      ("font-weight", "bold"),
+      ("font-size", "12px"),
+      ("line-height", "30px"),
+      ("font-family", "Georgia, \"Times New Roman\", serif"),
+    ])
+  );
+  // Assert that the optional values are 'normal' by default
+  assert_eq!(
+    expand_declarations("font: normal small-caps 2em sans-serif;"),
+    longhands(&[
+      ("font-style", "normal"),
+      ("font-variant", "small-caps"),
+      ("font-weight", "normal"),
+      ("font-size", "2em"),
+      ("line-height", "normal"),
+      ("font-family", "sans-serif"),
+    ])
+  );
+  // Assert that the weight can be a number, and the line height a number without a unit
+  let numeric: Vec<(String, String)> = expand_declarations("font: 700 120%/1.4 serif;");
+  assert_eq!(numeric[2].1, "700");
+  assert_eq!(numeric[3].1, "120%");
+  assert_eq!(numeric[4].1, "1.4");
+  // Assert that a font without a size or families expands to nothing
+  assert_eq!(expand_declarations("font: bold serif;"), vec![]);
+  assert_eq!(expand_declarations("font: 12px;"), vec![]);
+  assert_eq!(expand_declarations("font: 12px/ serif;"), vec![]);
+}
@@ -0,0 +1,98 @@
+use crate::css;
+use crate::dom;
+use crate::parser::css::CSSParser;
+use super::matching;
+
+// Interaction states of an element, which the dynamic pseudo-classes such as ':hover' match
+// https://drafts.csswg.org/selectors/#useraction-pseudos
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ElementState {
+  Hover,
+  Active,
+  Focus,
+  Checked,
+  Disabled,
+  Visited,
+}
+
+impl ElementState {
+  // Return the state named like its pseudo-class, e.g. 'hover'
+  pub fn from_name(name: &str) -> Option<ElementState> {
+    match &*name.to_ascii_lowercase() {
+      "hover" => Some(ElementState::Hover),
+      "active" => Some(ElementState::Active),
+      "focus" => Some(ElementState::Focus),
+      "checked" => Some(ElementState::Checked),
+      "disabled" => Some(ElementState::Disabled),
+      "visited" => Some(ElementState::Visited),
+      _ => None,
+    }
+  }
+}
+
+// The elements a document is rendered with in an interaction state, e.g. as if the pointer was
+// over a link or a field had the focus, so that the same tree can be rendered in different
+// states. Elements are given by their 'id' attribute, or by a selector list that matches them.
+// The map doesn't borrow the tree, so it can be built before the tree or kept across trees
+#[derive(Default)]
+pub struct StateMap {
+  ids: Vec<(String, ElementState)>,
+  selectors: Vec<(Vec<css::Selector>, ElementState)>,
+}
+
+impl StateMap {
+  pub fn new() -> Self {
+    Self {
+      ids: vec![],
+      selectors: vec![],
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ids.is_empty() && self.selectors.is_empty()
+  }
+
+  // Put the element with an 'id' in a state. Like '#id' selectors, ids are case-sensitive
+  pub fn add_id_state(&mut self, id: &str, state: ElementState) {
+    self.ids.push((id.to_string(), state));
+  }
+
+  // Put the elements that match a selector list in a state, or return the error that makes the
+  // list invalid
+  pub fn add_selector_state(
+    &mut self,
+    selectors: &str,
+    state: ElementState,
+  ) -> Result<(), String> {
+    let selectors: Vec<css::Selector> = CSSParser::parse_selector_list(selectors.to_string())?;
+    self.selectors.push((selectors, state));
+    Ok(())
+  }
+
+  // Is an element in a state? 'ancestors' are the ancestors of the element, from the root to its
+  // parent, for the selectors of the map
+  pub fn has_state(&self, node: &dom::Node, ancestors: &[&dom::Node], state: ElementState) -> bool {
+    let id: Option<&String> = match node.node_type() {
+      dom::NodeType::Element(element) => element.id(),
+      _ => None,
+    };
+    let in_ids: bool = id.is_some_and(|id: &String| {
+      self
+        .ids
+        .iter()
+        .any(|(state_id, id_state): &(String, ElementState)| *id_state == state && state_id == id)
+    });
+    in_ids
+      || self
+        .selectors
+        .iter()
+        .filter(|&(_, selector_state): &&(Vec<css::Selector>, ElementState)| {
+          *selector_state == state
+        })
+        .flat_map(|(selectors, _): &(Vec<css::Selector>, ElementState)| selectors.iter())
+        .any(|selector: &css::Selector| matching::matches(node, ancestors, selector))
+  }
+}
+
+#[cfg(test)]
+mod tests;
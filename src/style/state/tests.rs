@@ -0,0 +1,55 @@
+use crate::dom;
+use crate::parser::html::HTMLParser;
+use super::{ElementState, StateMap};
+
+// Test the function from_name
+#[test]
+fn test_element_state_from_name() {
+  // Assert that states are named like their pseudo-classes, ignoring case
+  assert_eq!(ElementState::from_name("hover"), Some(ElementState::Hover));
+  assert_eq!(ElementState::from_name("FOCUS"), Some(ElementState::Focus));
+  assert_eq!(ElementState::from_name("visited"), Some(ElementState::Visited));
+  // Assert that pseudo-classes that are not states have no state
+  assert_eq!(ElementState::from_name("focus-within"), None);
+  assert_eq!(ElementState::from_name("enabled"), None);
+}
+
+// Test the function has_state
+#[test]
+fn test_has_state() {
+  let document: dom::Document =
+    HTMLParser::parse_document("<p><a id='a'></a><a id='b' class='menu'></a></p>".to_string())
+      .unwrap();
+  let paragraph: &dom::Node = document.query_selector("p").unwrap().unwrap();
+  let body: &dom::Node = document.query_selector("body").unwrap().unwrap();
+  let html: &dom::Node = document.query_selector("html").unwrap().unwrap();
+  let ancestors: Vec<&dom::Node> = vec![html, body, paragraph];
+  let link_a: &dom::Node = &paragraph.children()[0];
+  let link_b: &dom::Node = &paragraph.children()[1];
+
+  // Assert that an empty map has no state for any element
+  let mut states: StateMap = StateMap::new();
+  assert!(states.is_empty());
+  assert!(!states.has_state(link_a, &ancestors, ElementState::Hover));
+
+  // Assert that a state given by id applies to the element with that id, in any copy of the tree
+  states.add_id_state("a", ElementState::Hover);
+  assert!(!states.is_empty());
+  assert!(states.has_state(link_a, &ancestors, ElementState::Hover));
+  assert!(!states.has_state(link_a, &ancestors, ElementState::Active));
+  assert!(!states.has_state(link_b, &ancestors, ElementState::Hover));
+  let copy: dom::Node = link_a.clone();
+  assert!(states.has_state(&copy, &ancestors, ElementState::Hover));
+  // Assert that ids are case-sensitive, like '#id' selectors
+  states.add_id_state("B", ElementState::Active);
+  assert!(!states.has_state(link_b, &ancestors, ElementState::Active));
+
+  // Assert that a state given by selector applies to the elements that match it in context
+  states.add_selector_state("p > .menu", ElementState::Focus).unwrap();
+  assert!(states.has_state(link_b, &ancestors, ElementState::Focus));
+  assert!(!states.has_state(link_b, &[], ElementState::Focus));
+  assert!(!states.has_state(link_a, &ancestors, ElementState::Focus));
+
+  // Assert that an invalid selector list is an error
+  assert!(states.add_selector_state("p >", ElementState::Focus).is_err());
+}
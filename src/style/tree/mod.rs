@@ -3,7 +3,11 @@ use std::collections::HashMap;
 use crate::css;
 use crate::dom;
 use crate::hashmap;
-use super::cascade::specified_values;
+use super::cascade::{pseudo_element_values, specified_values_in_state};
+use super::computed::{DEFAULT_FONT_SIZE, compute_values};
+use super::content::{Counters, generated_text};
+use super::state::StateMap;
+use super::whitespace::process_white_space;
 
 // Map from CSS property names to values
 pub type PropertyMap = HashMap<String, css::Value>;
@@ -21,6 +25,16 @@ pub struct StyledNode<'a> {
   node: &'a dom::Node, // pointer to a DOM node
   specified_values: PropertyMap,
   children: Vec<StyledNode<'a>>,
+  // Mode of the document the node belongs to, for quirks-mode style and layout behavior
+  quirks_mode: dom::QuirksMode,
+  // Text of a text node after white space processing, if it differs from the DOM text, or the
+  // generated text of a pseudo-element
+  text: Option<String>,
+  // The pseudo-element the node is generated for, with 'node' the element it belongs to. A
+  // pseudo-element has one child, the text of its 'content'
+  pseudo_element: Option<css::PseudoElement>,
+  // The computed font size of the node in px, which text is rendered at
+  font_size: f32,
 }
 
 impl<'a> PartialEq for StyledNode<'a> {
@@ -28,6 +42,10 @@ impl<'a> PartialEq for StyledNode<'a> {
     self.node == other.node
       && self.specified_values == other.specified_values
       && self.children == other.children
+      && self.quirks_mode == other.quirks_mode
+      && self.text() == other.text()
+      && self.pseudo_element == other.pseudo_element
+      && self.font_size == other.font_size
   }
 }
 
@@ -41,9 +59,28 @@ impl<'a> StyledNode<'a> {
       node,
       specified_values,
       children,
+      quirks_mode: dom::QuirksMode::NoQuirks,
+      text: None,
+      pseudo_element: None,
+      font_size: DEFAULT_FONT_SIZE,
     }
   }
 
+  // A pseudo-element of the element 'node', with its generated text as its only child
+  pub fn new_pseudo_element(
+    node: &'a dom::Node,
+    pseudo_element: css::PseudoElement,
+    specified_values: PropertyMap,
+    text: String,
+  ) -> Self {
+    let mut text_node: StyledNode = StyledNode::new(node, hashmap![], vec![]);
+    text_node.pseudo_element = Some(pseudo_element);
+    text_node.text = Some(text);
+    let mut styled_node: StyledNode = StyledNode::new(node, specified_values, vec![text_node]);
+    styled_node.pseudo_element = Some(pseudo_element);
+    styled_node
+  }
+
   pub fn node(&self) -> &'a dom::Node {
     self.node
   }
@@ -52,27 +89,62 @@ impl<'a> StyledNode<'a> {
     &self.specified_values
   }
 
+  pub(in crate::style) fn specified_values_mut(&mut self) -> &mut PropertyMap {
+    &mut self.specified_values
+  }
+
   pub fn children(&self) -> &Vec<StyledNode<'a>> {
     &self.children
   }
 
+  pub(in crate::style) fn children_mut(&mut self) -> &mut Vec<StyledNode<'a>> {
+    &mut self.children
+  }
+
+  pub fn quirks_mode(&self) -> dom::QuirksMode {
+    self.quirks_mode
+  }
+
+  pub fn font_size(&self) -> f32 {
+    self.font_size
+  }
+
+  pub(in crate::style) fn set_font_size(&mut self, font_size: f32) {
+    self.font_size = font_size;
+  }
+
+  // The pseudo-element the node is generated for, or "None" for the nodes of the DOM
+  pub fn pseudo_element(&self) -> Option<css::PseudoElement> {
+    self.pseudo_element
+  }
+
+  // The text of a text node as it is rendered, after white space processing, or "None" for
+  // other nodes. The text of a pseudo-element is its generated content
+  pub fn text(&self) -> Option<&str> {
+    match self.node.node_type() {
+      dom::NodeType::Text(text) => Some(self.text.as_deref().unwrap_or(text)),
+      _ if self.pseudo_element.is_some() => self.text.as_deref(),
+      _ => None,
+    }
+  }
+
+  pub(in crate::style) fn set_text(&mut self, text: String) {
+    self.text = Some(text);
+  }
+
   // Return the specified value of a property if it exists, otherwise "None"
   pub fn value(&self, name: &str) -> Option<css::Value> {
-    self
-      .specified_values
-      .get(name)
-      .map(|v: &css::Value| v.clone())
+    self.specified_values.get(name).cloned()
   }
 
-  // Return the specified value of property "name", or property "fallback_name" if that doesn't
-  // exist, or value "default" if neither does.
-  pub fn lookup(&self, name: &str, fallback_name: &str, default: &css::Value) -> css::Value {
-    self
-      .value(name)
-      .unwrap_or_else(|| self.value(fallback_name).unwrap_or_else(|| default.clone()))
+  // Return the specified value of property "name", or value "default" if it doesn't exist.
+  // Shorthands are expanded by the cascade, so "name" is a longhand such as "margin-left"
+  pub fn value_or(&self, name: &str, default: &css::Value) -> css::Value {
+    self.value(name).unwrap_or_else(|| default.clone())
   }
 
-  // The value of the "display" property (defaults to inline).
+  // The value of the "display" property. Without one, it defaults to the user-agent default of
+  // the element, and to inline for other elements and nodes
   pub fn display(&self) -> Display {
     match self.value("display") {
       Some(css::Value::Keyword(s)) => match &*s {
@@ -81,6 +153,24 @@ impl<'a> StyledNode<'a> {
         "none" => Display::None,
         _ => Display::Inline,
       },
+      _ => self.default_display(),
+    }
+  }
+
+  // The user-agent default of "display" for the HTML elements that the parser always inserts or
+  // whose contents are not page text: the document and its body are blocks, and the head and
+  // the elements of its metadata are not rendered
+  // https://html.spec.whatwg.org/multipage/rendering.html#hidden-elements
+  fn default_display(&self) -> Display {
+    let dom::NodeType::Element(element) = self.node.node_type() else {
+      return Display::Inline;
+    };
+    if self.pseudo_element.is_some() || element.namespace() != dom::Namespace::Html {
+      return Display::Inline;
+    }
+    match &*element.tag_name().to_ascii_lowercase() {
+      "html" | "body" => Display::Block,
+      "head" | "script" | "style" | "title" => Display::None,
       _ => Display::Inline,
     }
   }
@@ -94,6 +184,18 @@ impl<'a> StyledNode<'a> {
   }
 
   pub fn print_style_node_tree(style_node: &'a StyledNode, indent: usize) {
+    if let Some(pseudo_element) = style_node.pseudo_element() {
+      match style_node.text() {
+        Some(text) => println!("{:spaces$}{}", "", text, spaces = indent),
+        None => {
+          println!("{:spaces$}{}", "", pseudo_element, spaces = indent);
+          for child in style_node.children() {
+            StyledNode::print_style_node_tree(child, indent + 2);
+          }
+        }
+      }
+      return;
+    }
     match style_node.node().node_type() {
       dom::NodeType::Text(ref text) => {
         println!("{:spaces$}{}", "", text, spaces = indent);
@@ -101,6 +203,9 @@ impl<'a> StyledNode<'a> {
       dom::NodeType::Comment(ref comment) => {
         println!("{:spaces$}<!--{}-->", "", comment, spaces = indent);
       }
+      dom::NodeType::Doctype(ref doctype) => {
+        println!("{:spaces$}{}", "", doctype, spaces = indent);
+      }
       dom::NodeType::Element(ref element) => {
         if *style_node.specified_values() != hashmap![] {
           println!(
@@ -122,21 +227,89 @@ impl<'a> StyledNode<'a> {
   }
 }
 
-// Apply a stylesheet to an entire DOM tree, returning a StyledNode tree
-pub fn style_tree<'a>(root: &'a dom::Node, stylesheet: &'a css::Stylesheet) -> StyledNode<'a> {
-  StyledNode::new(
-    root,
-    match root.node_type() {
-      dom::NodeType::Element(ref elem) => specified_values(elem, stylesheet),
-      dom::NodeType::Text(_) => hashmap![],
-      dom::NodeType::Comment(_) => hashmap![],
-    },
-    root
-      .children()
-      .iter()
-      .map(|child: &dom::Node| style_tree(child, stylesheet))
-      .collect(),
-  )
+// The stylesheets, states and counters a style tree is built with
+struct StyleContext<'a, 'b> {
+  stylesheets: &'a [css::Stylesheet],
+  quirks_mode: dom::QuirksMode,
+  states: &'b StateMap,
+  counters: Counters,
+}
+
+// Generate a pseudo-element of an element, if its 'content' generates one. Its counters are
+// updated before its content is, so that '::before { counter-increment: item; content:
+// counter(item) }' numbers the elements from 1
+fn build_pseudo_element<'a>(
+  root: &'a dom::Node,
+  ancestors: &[&'a dom::Node],
+  pseudo_element: css::PseudoElement,
+  context: &mut StyleContext<'a, '_>,
+) -> Option<StyledNode<'a>> {
+  let dom::NodeType::Element(element) = root.node_type() else {
+    return None;
+  };
+  let specified_values: PropertyMap =
+    pseudo_element_values(root, ancestors, context.stylesheets, context.states, pseudo_element);
+  context.counters.update(&specified_values);
+  let text: String = generated_text(element, &specified_values, &context.counters)?;
+  let mut styled_node: StyledNode =
+    StyledNode::new_pseudo_element(root, pseudo_element, specified_values, text);
+  styled_node.quirks_mode = context.quirks_mode;
+  styled_node.children[0].quirks_mode = context.quirks_mode;
+  Some(styled_node)
+}
+
+// Apply an ordered list of stylesheets to a DOM node and its descendants. 'ancestors' are the
+// ancestors of the node, from the root to its parent. The '::before' and '::after'
+// pseudo-elements of an element are its first and last children
+fn build_style_tree<'a>(
+  root: &'a dom::Node,
+  ancestors: &mut Vec<&'a dom::Node>,
+  context: &mut StyleContext<'a, '_>,
+) -> StyledNode<'a> {
+  let specified_values: PropertyMap = match root.node_type() {
+    dom::NodeType::Element(_) => {
+      specified_values_in_state(root, ancestors, context.stylesheets, context.states)
+    }
+    dom::NodeType::Text(_) => hashmap![],
+    dom::NodeType::Comment(_) => hashmap![],
+    dom::NodeType::Doctype(_) => hashmap![],
+  };
+  context.counters.update(&specified_values);
+  context.counters.enter();
+  let mut children: Vec<StyledNode> = vec![];
+  children.extend(build_pseudo_element(root, ancestors, css::PseudoElement::Before, context));
+  ancestors.push(root);
+  for child in root.children() {
+    children.push(build_style_tree(child, ancestors, context));
+  }
+  ancestors.pop();
+  children.extend(build_pseudo_element(root, ancestors, css::PseudoElement::After, context));
+  context.counters.leave();
+  let mut styled_node: StyledNode = StyledNode::new(root, specified_values, children);
+  styled_node.quirks_mode = context.quirks_mode;
+  styled_node
+}
+
+// Apply an ordered list of stylesheets to an entire DOM tree, returning a StyledNode tree. The
+// quirks mode of the document is recorded on every node, values are computed, and the white
+// space of the text is processed for layout. 'states' are the interaction states the tree is
+// rendered in, e.g. which element is hovered, for the dynamic pseudo-classes
+pub fn style_tree<'a>(
+  root: &'a dom::Node,
+  stylesheets: &'a [css::Stylesheet],
+  quirks_mode: dom::QuirksMode,
+  states: &StateMap,
+) -> StyledNode<'a> {
+  let mut context: StyleContext = StyleContext {
+    stylesheets,
+    quirks_mode,
+    states,
+    counters: Counters::default(),
+  };
+  let mut styled_node: StyledNode = build_style_tree(root, &mut vec![], &mut context);
+  compute_values(&mut styled_node);
+  process_white_space(&mut styled_node);
+  styled_node
 }
 
 #[cfg(test)]
@@ -1,8 +1,10 @@
 use crate::css;
 use crate::dom;
 use crate::hashmap;
-use crate::style::{PropertyMap, StyledNode};
+use crate::style::{Display, PropertyMap, StateMap, StyledNode};
 use crate::style::cascade::specified_values;
+use crate::parser::css::CSSParser;
+use crate::parser::html::HTMLParser;
 use super::style_tree;
 
 // Test the function style_tree
@@ -34,31 +36,27 @@ fn test_style_tree() {
   let declaration_2: css::Declaration = css::Declaration::new("background".to_string(), color);
   let rule_1: css::Rule = css::Rule::new(vec![selector_1], vec![declaration_1]);
   let rule_2: css::Rule = css::Rule::new(vec![selector_2], vec![declaration_2]);
-  let stylesheet: css::Stylesheet = css::Stylesheet::new(vec![rule_1.clone(), rule_2.clone()]);
+  let stylesheets: Vec<css::Stylesheet> = vec![css::Stylesheet::new(vec![rule_1.clone(), rule_2.clone()])];
   let mut values_2: PropertyMap = hashmap![];
   let mut values_4: PropertyMap = hashmap![];
   let mut values_3: PropertyMap = hashmap![];
   let mut values_1: PropertyMap = hashmap![];
 
-  match node_2.node_type() {
-    dom::NodeType::Element(element) => { values_2 = specified_values(&element, &stylesheet); }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_2.node_type() {
+    values_2 = specified_values(&node_2, &[], &stylesheets);
   }
-  match node_4.node_type() {
-    dom::NodeType::Element(element) => { values_4 = specified_values(&element, &stylesheet); }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_4.node_type() {
+    values_4 = specified_values(&node_4, &[], &stylesheets);
   }
-  match node_3.node_type() {
-    dom::NodeType::Element(element) => { values_3 = specified_values(&element, &stylesheet); }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_3.node_type() {
+    values_3 = specified_values(&node_3, &[], &stylesheets);
   }
-  match node_1.node_type() {
-    dom::NodeType::Element(element) => { values_1 = specified_values(&element, &stylesheet); }
-    _ => {}
+  if let dom::NodeType::Element(_) = node_1.node_type() {
+    values_1 = specified_values(&node_1, &[], &stylesheets);
   }
 
   assert_eq!(
-    style_tree(&node_1, &stylesheet),
+    style_tree(&node_1, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new()),
     StyledNode::new(
       &node_1,
       values_1,
@@ -81,3 +79,115 @@ fn test_style_tree() {
     )
   );
 }
+
+// Test that the function style_tree records the document quirks mode on every node
+#[test]
+fn test_style_tree_quirks_mode() {
+  let node: dom::Node = dom::Node::element(
+    String::from("p"),
+    hashmap![],
+    vec![dom::Node::text("Hello World!".to_string())],
+  );
+  let stylesheets: Vec<css::Stylesheet> = vec![];
+  let style_root: StyledNode =
+    style_tree(&node, &stylesheets, dom::QuirksMode::Quirks, &StateMap::new());
+
+  assert_eq!(style_root.quirks_mode(), dom::QuirksMode::Quirks);
+  assert_eq!(style_root.children()[0].quirks_mode(), dom::QuirksMode::Quirks);
+}
+
+// Test the method display of the StyledNode struct implementation
+#[test]
+fn test_display() {
+  let root: dom::Node = HTMLParser::parse(
+    "<title>t</title><style>p {}</style><script>x()</script>\
+     <p>a</p><svg><title>b</title></svg><div>c</div>"
+      .to_string(),
+  )
+  .unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "div { display: none; } body::after { content: 'e'; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let head: &StyledNode = &style.children()[0];
+  let body: &StyledNode = &style.children()[1];
+
+  // Assert that the document and its body are blocks without a stylesheet saying so
+  assert!(matches!(style.display(), Display::Block));
+  assert!(matches!(body.display(), Display::Block));
+  // Assert that the head and its title, style and script are not rendered
+  assert!(matches!(head.display(), Display::None));
+  assert!(head
+    .children()
+    .iter()
+    .all(|child: &StyledNode| matches!(child.display(), Display::None)));
+  // Assert that other elements, a title in SVG and pseudo-elements of the body are inline
+  assert!(matches!(body.children()[0].display(), Display::Inline));
+  assert!(matches!(body.children()[1].children()[0].display(), Display::Inline));
+  assert!(matches!(body.children().last().unwrap().display(), Display::Inline));
+  // Assert that a value from a stylesheet overrides the default
+  assert!(matches!(body.children()[2].display(), Display::None));
+}
+
+// Test the pseudo-elements generated by the function style_tree
+#[test]
+fn test_style_tree_pseudo_elements() {
+  let root: dom::Node = HTMLParser::parse(
+    "<ol><li>a</li><li title='b'>b</li></ol><ol><li>c</li></ol><p>d</p>".to_string(),
+  )
+  .unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(
+    "ol { counter-reset: item; } \
+     li { counter-increment: item; } \
+     li::before { content: counter(item) '. '; color: #ff0000; } \
+     li[title]::after { content: ' (' attr(title) ')'; } \
+     p::after { content: none; }"
+      .to_string(),
+  )
+  .unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let body: &StyledNode = &style.children()[1];
+  let items: Vec<&StyledNode> = body.children()[..2]
+    .iter()
+    .flat_map(|list: &StyledNode| list.children())
+    .collect();
+  let texts = |item: &StyledNode| -> Vec<(Option<css::PseudoElement>, String)> {
+    item
+      .children()
+      .iter()
+      .map(|child: &StyledNode| {
+        let text: Option<&str> =
+          child.text().or_else(|| child.children().first().and_then(StyledNode::text));
+        (child.pseudo_element(), text.unwrap_or_default().to_string())
+      })
+      .collect()
+  };
+
+  // Assert that '::before' and '::after' are the first and last children of the element, with
+  // the text of their content, and that counters are scoped to the lists that reset them
+  assert_eq!(
+    texts(items[0]),
+    vec![(Some(css::PseudoElement::Before), "1. ".to_string()), (None, "a".to_string())]
+  );
+  assert_eq!(
+    texts(items[1]),
+    vec![
+      (Some(css::PseudoElement::Before), "2. ".to_string()),
+      (None, "b".to_string()),
+      (Some(css::PseudoElement::After), " (b)".to_string()),
+    ]
+  );
+  assert_eq!(texts(items[2])[0], (Some(css::PseudoElement::Before), "1. ".to_string()));
+  // Assert that a pseudo-element has the values of the rules that select it only
+  assert_eq!(
+    items[0].children()[0].value("color"),
+    Some(css::Value::ColorValue(css::Color::new(255, 0, 0, 255)))
+  );
+  assert_eq!(items[0].value("color"), None);
+  // Assert that 'content: none' generates no pseudo-element
+  assert_eq!(texts(&body.children()[2]), vec![(None, "d".to_string())]);
+}
@@ -0,0 +1,224 @@
+use crate::css;
+use crate::dom;
+use super::tree::{Display, StyledNode};
+
+// The values of the 'white-space' property, which decide what happens to the spaces, tabs and
+// newlines of text
+// https://www.w3.org/TR/css-text-3/#white-space-property
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum WhiteSpace {
+  #[default]
+  Normal,
+  Pre,
+  Nowrap,
+  PreWrap,
+  PreLine,
+}
+
+impl WhiteSpace {
+  // The value of the 'white-space' property of a node, or None if it is not specified (or
+  // invalid), in which case it is inherited from the parent
+  pub fn of(style: &StyledNode) -> Option<WhiteSpace> {
+    match style.value("white-space") {
+      Some(css::Value::Keyword(keyword)) => match &*keyword {
+        "normal" => Some(WhiteSpace::Normal),
+        "pre" => Some(WhiteSpace::Pre),
+        "nowrap" => Some(WhiteSpace::Nowrap),
+        "pre-wrap" => Some(WhiteSpace::PreWrap),
+        "pre-line" => Some(WhiteSpace::PreLine),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  // Are runs of spaces and tabs collapsed into a single space?
+  pub fn collapses_spaces(self) -> bool {
+    matches!(self, WhiteSpace::Normal | WhiteSpace::Nowrap | WhiteSpace::PreLine)
+  }
+
+  // Are newlines kept as forced line breaks, rather than turned into spaces?
+  pub fn preserves_newlines(self) -> bool {
+    !matches!(self, WhiteSpace::Normal | WhiteSpace::Nowrap)
+  }
+}
+
+// A piece of the inline content of a block, in document order
+enum InlineItem<'b, 'a> {
+  Text(&'b mut StyledNode<'a>, WhiteSpace),
+  // An inline-block, which separates the spaces before and after it like a visible character
+  Atomic,
+  // A <br> element, which ends a line
+  LineBreak,
+}
+
+// Split the content of a block container into runs of inline items, one per line box the
+// container would get with infinitely wide lines: block-level descendants end a run and start a
+// new one, and the contents of inline-blocks make runs of their own. Nodes with
+// 'display: none' are not rendered, so they are skipped
+fn collect_inline_runs<'b, 'a>(
+  node: &'b mut StyledNode<'a>,
+  white_space: WhiteSpace,
+  run: &mut Vec<InlineItem<'b, 'a>>,
+  runs: &mut Vec<Vec<InlineItem<'b, 'a>>>,
+) {
+  let white_space: WhiteSpace = WhiteSpace::of(node).unwrap_or(white_space);
+  // Text nodes, and the text generated for pseudo-elements
+  if node.text().is_some() {
+    run.push(InlineItem::Text(node, white_space));
+    return;
+  }
+  let is_line_break: bool = match node.node().node_type() {
+    dom::NodeType::Element(element) => {
+      element.tag_name() == "br" && node.pseudo_element().is_none()
+    }
+    _ => return,
+  };
+  match node.display() {
+    Display::None => {}
+    Display::Block => {
+      runs.push(std::mem::take(run));
+      let mut inner_run: Vec<InlineItem> = vec![];
+      for child in node.children_mut() {
+        collect_inline_runs(child, white_space, &mut inner_run, runs);
+      }
+      runs.push(inner_run);
+    }
+    Display::InlineBlock => {
+      run.push(InlineItem::Atomic);
+      let mut inner_run: Vec<InlineItem> = vec![];
+      for child in node.children_mut() {
+        collect_inline_runs(child, white_space, &mut inner_run, runs);
+      }
+      runs.push(inner_run);
+    }
+    Display::Inline if is_line_break => run.push(InlineItem::LineBreak),
+    Display::Inline => {
+      for child in node.children_mut() {
+        collect_inline_runs(child, white_space, run, runs);
+      }
+    }
+  }
+}
+
+// Collapse the white space of a run of inline content, as browsers do before breaking it into
+// lines: in 'normal', 'nowrap' and 'pre-line' text, spaces and tabs around newlines are removed,
+// newlines become spaces (except in 'pre-line'), a space following another space is removed,
+// even across elements, and spaces at the start and end of lines are removed. 'pre' and
+// 'pre-wrap' text is kept as it is
+// https://www.w3.org/TR/css-text-3/#white-space-processing
+fn process_inline_run(run: Vec<InlineItem>) {
+  let mut texts: Vec<String> = vec![];
+  // Whether a collapsible space here would be removed: at the start of a line or after another
+  // space
+  let mut after_space: bool = true;
+  // The text that ends with a collapsible space, removed if the line ends there
+  let mut trailing_space: Option<usize> = None;
+  let mut nodes: Vec<&mut StyledNode> = vec![];
+
+  for item in run {
+    let (node, white_space): (&mut StyledNode, WhiteSpace) = match item {
+      InlineItem::Text(node, white_space) => (node, white_space),
+      InlineItem::Atomic => {
+        after_space = false;
+        trailing_space = None;
+        continue;
+      }
+      InlineItem::LineBreak => {
+        if let Some(index) = trailing_space.take() {
+          texts[index].pop();
+        }
+        after_space = true;
+        continue;
+      }
+    };
+    let index: usize = texts.len();
+    let source: String = node.text().unwrap_or_default().replace("\r\n", "\n").replace('\r', "\n");
+    let mut text: String = String::with_capacity(source.len());
+    // Spaces and tabs around a newline go away with it
+    let source: String = if white_space.collapses_spaces() {
+      let is_space = |c: char| c == ' ' || c == '\t';
+      let lines: Vec<&str> = source.split('\n').collect();
+      let last: usize = lines.len() - 1;
+      lines
+        .iter()
+        .enumerate()
+        .map(|(number, line): (usize, &&str)| {
+          let line: &str = if number > 0 { line.trim_start_matches(is_space) } else { line };
+          if number < last { line.trim_end_matches(is_space) } else { line }
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+    } else {
+      source
+    };
+    texts.push(String::new());
+    for c in source.chars() {
+      let is_space: bool = match c {
+        ' ' | '\t' => white_space.collapses_spaces(),
+        '\n' => !white_space.preserves_newlines(),
+        _ => false,
+      };
+      if is_space {
+        if !after_space {
+          text.push(' ');
+          after_space = true;
+          trailing_space = Some(index);
+        }
+        continue;
+      }
+      if c == '\n' {
+        // A preserved newline ends the line
+        match trailing_space.take() {
+          Some(previous) if previous == index => {
+            text.pop();
+          }
+          Some(previous) => {
+            texts[previous].pop();
+          }
+          None => {}
+        }
+        after_space = true;
+      } else {
+        after_space = false;
+        trailing_space = None;
+      }
+      text.push(c);
+    }
+    texts[index] = text;
+    nodes.push(node);
+  }
+  // The end of the run ends the last line
+  if let Some(index) = trailing_space {
+    texts[index].pop();
+  }
+  for (node, text) in nodes.into_iter().zip(texts) {
+    node.set_text(text);
+  }
+}
+
+// Remove the text nodes left empty by white space processing, so that they don't generate boxes
+fn remove_empty_texts(node: &mut StyledNode) {
+  node
+    .children_mut()
+    .retain(|child: &StyledNode| child.text().is_none_or(|text: &str| !text.is_empty()));
+  for child in node.children_mut() {
+    remove_empty_texts(child);
+  }
+}
+
+// Process the white space of all the text of a style tree according to the 'white-space'
+// property, which is inherited, before layout
+pub fn process_white_space(root: &mut StyledNode) {
+  let mut runs: Vec<Vec<InlineItem>> = vec![];
+  let mut run: Vec<InlineItem> = vec![];
+  collect_inline_runs(root, WhiteSpace::Normal, &mut run, &mut runs);
+  runs.push(run);
+  for run in runs {
+    process_inline_run(run);
+  }
+  remove_empty_texts(root);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,90 @@
+use crate::css;
+use crate::dom;
+use crate::parser::css::CSSParser;
+use crate::parser::html::HTMLParser;
+use crate::style::{StateMap, StyledNode, style_tree};
+use super::WhiteSpace;
+
+// Collect the processed text of the text nodes of a style tree, in document order
+fn collect_texts(style: &StyledNode, texts: &mut Vec<String>) {
+  if let Some(text) = style.text() {
+    texts.push(text.to_string());
+  }
+  for child in style.children() {
+    collect_texts(child, texts);
+  }
+}
+
+// Parse a document and a stylesheet, and return the text of the styled document
+fn processed_texts(html: &str, css: &str) -> Vec<String> {
+  let root: dom::Node = HTMLParser::parse(html.to_string()).unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![CSSParser::parse(css.to_string()).unwrap()];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+  let mut texts: Vec<String> = vec![];
+  collect_texts(&style, &mut texts);
+  texts
+}
+
+// Test the function process_white_space
+#[test]
+fn test_process_white_space() {
+  let blocks: &str = "html, body, div, p, pre { display: block; } head { display: none; } ";
+
+  // Assert that runs of spaces, tabs and newlines collapse into one space, even across elements,
+  // and that the spaces at the start and end of a line are removed
+  assert_eq!(
+    processed_texts("<p>\n  Hello \t <b> big </b>\n  world  </p>", blocks),
+    vec!["Hello ", "big ", "world"]
+  );
+  // Assert that whitespace-only text between blocks is removed, but not between inline elements
+  assert_eq!(
+    processed_texts("<div>\n  <p>a</p>\n  <p><b>b</b> <i>c</i></p>\n</div>", blocks),
+    vec!["a", "b", " ", "c"]
+  );
+  // Assert that 'pre' keeps all white space, and that the property is inherited
+  let pre: String = format!("{} pre {{ white-space: pre; }}", blocks);
+  assert_eq!(
+    processed_texts("<pre>  a  <b> b\t</b>\n</pre><p>  c  </p>", &pre),
+    vec!["  a  ", " b\t", "\n", "c"]
+  );
+  // Assert that 'pre-line' keeps newlines but collapses the spaces around them
+  let pre_line: String = format!("{} p {{ white-space: pre-line; }}", blocks);
+  assert_eq!(
+    processed_texts("<p>  one  \n\n  two \r\n three  </p>", &pre_line),
+    vec!["one\n\ntwo\nthree"]
+  );
+  // Assert that 'nowrap' collapses like 'normal', and that 'pre-wrap' preserves like 'pre'
+  let nowrap: String =
+    format!("{} p {{ white-space: nowrap; }} b {{ white-space: pre-wrap; }}", blocks);
+  assert_eq!(processed_texts("<p> a \n b <b> c \n</b></p>", &nowrap), vec!["a b ", " c \n"]);
+  // Assert that <br> ends a line and that inline-blocks keep the spaces around them
+  let inline_block: String = format!("{} span {{ display: inline-block; }}", blocks);
+  assert_eq!(
+    processed_texts("<p>a <br> b <span> c </span> d </p>", &inline_block),
+    vec!["a", "b ", "c", " d"]
+  );
+  // Assert that text that is not rendered is left as it is
+  assert_eq!(processed_texts("<title> a  b </title>", blocks), vec![" a  b "]);
+}
+
+// Test the function WhiteSpace::of
+#[test]
+fn test_white_space_of() {
+  let root: dom::Node = HTMLParser::parse("<p></p>".to_string()).unwrap();
+  let stylesheet: css::Stylesheet =
+    CSSParser::parse("html { white-space: pre-wrap; } body { white-space: wrap; }".to_string())
+      .unwrap();
+  let stylesheets: Vec<css::Stylesheet> = vec![stylesheet];
+  let style: StyledNode =
+    style_tree(&root, &stylesheets, dom::QuirksMode::NoQuirks, &StateMap::new());
+
+  // Assert that valid keywords are recognized and that others are ignored
+  assert_eq!(WhiteSpace::of(&style), Some(WhiteSpace::PreWrap));
+  assert_eq!(WhiteSpace::of(&style.children()[1]), None);
+  assert_eq!(WhiteSpace::of(&style.children()[1].children()[0]), None);
+  // Assert which values collapse spaces and preserve newlines
+  assert!(WhiteSpace::Normal.collapses_spaces() && !WhiteSpace::Normal.preserves_newlines());
+  assert!(WhiteSpace::PreLine.collapses_spaces() && WhiteSpace::PreLine.preserves_newlines());
+  assert!(!WhiteSpace::PreWrap.collapses_spaces() && WhiteSpace::Pre.preserves_newlines());
+}